@@ -78,6 +78,10 @@ CREATE TABLE IF NOT EXISTS entity_types (
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 
+-- Object catalogue definition (shape, mass, verbs, tags) as loaded from data/objects/.
+-- The server seeds and refreshes this column on startup.
+ALTER TABLE entity_types ADD COLUMN IF NOT EXISTS definition JSONB;
+
 -- Insert default entity types
 INSERT INTO entity_types (name) VALUES ('human'), ('ball')
 ON CONFLICT (name) DO NOTHING;
@@ -104,3 +108,134 @@ CREATE TRIGGER update_entities_updated_at BEFORE UPDATE ON entities
 CREATE INDEX IF NOT EXISTS idx_entities_type ON entities(entity_type_id);
 CREATE INDEX IF NOT EXISTS idx_entities_updated_at ON entities(updated_at);
 
+-- Player who owns an entity (items, furniture), if anyone
+ALTER TABLE entities ADD COLUMN IF NOT EXISTS owner_id VARCHAR(255);
+-- Properties set on the entity (e.g. {"on": true}); unset ones have the type's default
+ALTER TABLE entities ADD COLUMN IF NOT EXISTS properties JSONB NOT NULL DEFAULT '{}'::jsonb;
+-- Entity ID as used by the game (the row ID is derived from it), and the zone the
+-- entity is in; objects the level does not place are restored from these
+ALTER TABLE entities ADD COLUMN IF NOT EXISTS entity_key VARCHAR(255);
+ALTER TABLE entities ADD COLUMN IF NOT EXISTS zone VARCHAR(255);
+CREATE INDEX IF NOT EXISTS idx_entities_zone ON entities(zone);
+
+-- Inventory contents of characters and containers (fridges, cupboards),
+-- one row per occupied slot
+CREATE TABLE IF NOT EXISTS inventory_items (
+    owner_id VARCHAR(255) NOT NULL,  -- player ID or container entity ID
+    slot INTEGER NOT NULL CHECK (slot >= 0),
+    item_type VARCHAR(255) NOT NULL REFERENCES entity_types(name),
+    count INTEGER NOT NULL CHECK (count > 0),
+    item_owner VARCHAR(255),  -- player who owns the items, if anyone
+    PRIMARY KEY (owner_id, slot)
+);
+-- Properties the items carry (e.g. {"freshness": 0.8})
+ALTER TABLE inventory_items ADD COLUMN IF NOT EXISTS properties JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+-- Money of every character (cents)
+CREATE TABLE IF NOT EXISTS accounts (
+    player_id VARCHAR(255) PRIMARY KEY,
+    balance_cents BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_accounts_updated_at BEFORE UPDATE ON accounts
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Shop orders; delivered_at_minute is NULL until the goods arrive
+CREATE TABLE IF NOT EXISTS orders (
+    id UUID PRIMARY KEY,
+    player_id VARCHAR(255) NOT NULL,
+    lines JSONB NOT NULL,  -- [{"object_type", "count", "unit_price"}]
+    total_cents BIGINT NOT NULL,
+    placed_at_minute BIGINT NOT NULL,
+    deliver_at_minute BIGINT NOT NULL,
+    delivered_at_minute BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_orders_pending ON orders(deliver_at_minute)
+    WHERE delivered_at_minute IS NULL;
+
+-- Transaction log: every change to a balance, append-only for auditing
+CREATE TABLE IF NOT EXISTS transactions (
+    id UUID PRIMARY KEY,
+    player_id VARCHAR(255) NOT NULL,
+    amount_cents BIGINT NOT NULL,  -- negative for payments
+    balance_after_cents BIGINT NOT NULL,
+    kind VARCHAR(32) NOT NULL CHECK (kind IN ('grant', 'purchase', 'refund')),
+    description TEXT NOT NULL,
+    order_id UUID REFERENCES orders(id),
+    game_minute BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_transactions_player ON transactions(player_id, created_at);
+
+-- Reject changes to logged transactions
+CREATE OR REPLACE FUNCTION reject_transaction_changes()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'transactions are append-only';
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER transactions_append_only BEFORE UPDATE OR DELETE ON transactions
+    FOR EACH ROW EXECUTE FUNCTION reject_transaction_changes();
+
+-- Relationship graph: one row per pair of characters who have met, with
+-- player_a < player_b (relationships are symmetric)
+CREATE TABLE IF NOT EXISTS relationships (
+    player_a VARCHAR(255) NOT NULL,
+    player_b VARCHAR(255) NOT NULL,
+    familiarity REAL NOT NULL CHECK (familiarity BETWEEN 0 AND 100),
+    affection REAL NOT NULL CHECK (affection BETWEEN -100 AND 100),
+    trust REAL NOT NULL CHECK (trust BETWEEN -100 AND 100),
+    last_interaction_minute BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (player_a, player_b),
+    CHECK (player_a < player_b)
+);
+
+CREATE INDEX IF NOT EXISTS idx_relationships_player_b ON relationships(player_b);
+
+CREATE TRIGGER update_relationships_updated_at BEFORE UPDATE ON relationships
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Chat history, only written when PERSIST_CHAT is enabled
+CREATE TABLE IF NOT EXISTS chat_messages (
+    id UUID PRIMARY KEY,
+    sender_id VARCHAR(255) NOT NULL,
+    channel VARCHAR(16) NOT NULL CHECK (channel IN ('say', 'whisper', 'group')),
+    target VARCHAR(255),  -- whisper recipient or group channel name
+    text TEXT NOT NULL,
+    game_minute BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_chat_messages_sender ON chat_messages(sender_id, created_at);
+
+-- Accounts of people who log in; each owns a few characters
+CREATE TABLE IF NOT EXISTS user_accounts (
+    id VARCHAR(255) PRIMARY KEY,
+    username VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_user_accounts_updated_at BEFORE UPDATE ON user_accounts
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Characters (player IDs) owned by an account, in creation order
+CREATE TABLE IF NOT EXISTS characters (
+    id VARCHAR(255) PRIMARY KEY,
+    account_id VARCHAR(255) NOT NULL REFERENCES user_accounts(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    position INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (account_id, name)
+);
+-- Heritable genome and rendered appearance of each character
+ALTER TABLE characters ADD COLUMN IF NOT EXISTS genome JSONB NOT NULL DEFAULT '{}'::jsonb;
+ALTER TABLE characters ADD COLUMN IF NOT EXISTS appearance JSONB NOT NULL DEFAULT '{}'::jsonb;
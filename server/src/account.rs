@@ -0,0 +1,193 @@
+//! Accounts module.
+//!
+//! An account is the person who logs in; characters are the avatars that live in
+//! the world. One account owns up to [`MAX_CHARACTERS_PER_ACCOUNT`] characters,
+//! each a `Player` in the game state keyed by its character ID. A connection
+//! controls one character at a time, chosen after logging in; the account's other
+//! characters keep living on their schedules (or the needs autopilot) like any
+//! character whose owner is offline.
+//!
+//! Accounts and their character lists are persisted in the `user_accounts` and
+//! `characters` tables.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::game::Activity;
+use crate::genetics::{Appearance, Genome};
+
+/// Most characters one account may own.
+pub const MAX_CHARACTERS_PER_ACCOUNT: usize = 4;
+/// Longest character name (characters).
+const MAX_NAME_CHARS: usize = 32;
+
+/// A character owned by an account.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Character {
+    /// Character (player) ID
+    pub id: String,
+    /// Display name
+    pub name: String,
+    #[serde(default)]
+    pub genome: Genome,
+    #[serde(default)]
+    pub appearance: Appearance,
+}
+
+/// Someone who logs in and owns characters.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Account {
+    /// Account ID
+    pub id: String,
+    /// Display username
+    pub username: String,
+    /// Characters in the order they were created
+    pub characters: Vec<Character>,
+}
+
+/// A character as listed for selection.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CharacterSummary {
+    pub id: String,
+    pub name: String,
+    /// What the character is doing right now
+    pub activity: Activity,
+    /// Whether no connection controls the character
+    pub offline: bool,
+    pub appearance: Appearance,
+    /// Zone the character is in
+    pub zone: String,
+}
+
+/// Every known account, and which ones changed since the last save.
+#[derive(Clone, Debug, Default)]
+pub struct Accounts {
+    accounts: HashMap<String, Account>,
+    unsaved: HashSet<String>,
+}
+
+impl Accounts {
+    pub fn get(&self, account_id: &str) -> Option<&Account> {
+        self.accounts.get(account_id)
+    }
+
+    /// Log in to an account, opening it if it is new.
+    ///
+    /// No credential is checked: this stands in for a real sign-in during
+    /// development.
+    pub fn login(&mut self, account_id: &str, username: &str) -> Result<&Account, String> {
+        if account_id.is_empty() {
+            return Err("missing account ID".to_string());
+        }
+        let account = self
+            .accounts
+            .entry(account_id.to_string())
+            .or_insert_with(|| Account {
+                id: account_id.to_string(),
+                username: String::new(),
+                characters: Vec::new(),
+            });
+        if account.username != username {
+            account.username = username.to_string();
+            self.unsaved.insert(account_id.to_string());
+        }
+        Ok(account)
+    }
+
+    /// Add a new character with a genome and appearance to an account.
+    pub fn create_character(
+        &mut self,
+        account_id: &str,
+        name: &str,
+        genome: Genome,
+        appearance: Appearance,
+    ) -> Result<Character, String> {
+        let Some(account) = self.accounts.get_mut(account_id) else {
+            return Err("not logged in".to_string());
+        };
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+            return Err(format!("name must be 1 to {MAX_NAME_CHARS} characters"));
+        }
+        if account.characters.len() >= MAX_CHARACTERS_PER_ACCOUNT {
+            return Err(format!(
+                "at most {MAX_CHARACTERS_PER_ACCOUNT} characters per account"
+            ));
+        }
+        if account.characters.iter().any(|c| c.name == name) {
+            return Err(format!("already have a character called {name}"));
+        }
+        let character = Character {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            genome,
+            appearance,
+        };
+        account.characters.push(character.clone());
+        self.unsaved.insert(account_id.to_string());
+        Ok(character)
+    }
+
+    /// Whether an account owns a character.
+    pub fn owns(&self, account_id: &str, character_id: &str) -> bool {
+        self.accounts
+            .get(account_id)
+            .is_some_and(|account| account.characters.iter().any(|c| c.id == character_id))
+    }
+
+    /// Whether any account owns a character.
+    pub fn has_character(&self, character_id: &str) -> bool {
+        self.accounts
+            .values()
+            .any(|account| account.characters.iter().any(|c| c.id == character_id))
+    }
+
+    /// Set accounts loaded from the database.
+    pub fn restore(&mut self, accounts: Vec<Account>) {
+        for account in accounts {
+            self.accounts.insert(account.id.clone(), account);
+        }
+    }
+
+    /// Take the accounts changed since the last call, for persisting.
+    pub fn take_unsaved(&mut self) -> Vec<Account> {
+        std::mem::take(&mut self.unsaved)
+            .into_iter()
+            .filter_map(|id| self.accounts.get(&id).cloned())
+            .collect()
+    }
+
+    /// Mark accounts that failed to persist as unsaved again.
+    pub fn return_unsaved(&mut self, accounts: Vec<Account>) {
+        self.unsaved
+            .extend(accounts.into_iter().map(|account| account.id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accounts_own_a_limited_number_of_characters() {
+        let mut accounts = Accounts::default();
+        let create = |accounts: &mut Accounts, name: &str| {
+            accounts.create_character("ada", name, Genome::default(), Appearance::default())
+        };
+        assert!(create(&mut accounts, "Ada").is_err());
+        accounts.login("ada", "ada_l").unwrap();
+        let first = create(&mut accounts, " Ada ").unwrap();
+        assert_eq!(first.name, "Ada");
+        assert!(create(&mut accounts, "Ada").is_err());
+        assert!(accounts.owns("ada", &first.id));
+        assert!(!accounts.owns("bruno", &first.id));
+
+        for n in 1..MAX_CHARACTERS_PER_ACCOUNT {
+            create(&mut accounts, &format!("Ada {n}")).unwrap();
+        }
+        assert!(create(&mut accounts, "One too many").is_err());
+        let saved = accounts.take_unsaved();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].characters.len(), MAX_CHARACTERS_PER_ACCOUNT);
+    }
+}
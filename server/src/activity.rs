@@ -0,0 +1,198 @@
+//! Timed activities module.
+//!
+//! An activity is a timed action: it starts at a game minute, runs for an
+//! expected number of game minutes, may need an object within reach (a bed for
+//! Sleeping, a stove for Cooking), can only be abandoned after a minimum time,
+//! is interrupted when a need it does not satisfy becomes critical, and applies
+//! one-off effects when it completes. The server broadcasts start, progress and
+//! finish events.
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::Activity;
+use crate::needs::{NeedRates, Needs, NEED_CRITICAL};
+
+/// Distance (m) from the character within which a required object must be.
+pub const REACH_DISTANCE: f32 = 2.5;
+/// Game minutes between progress events.
+pub const PROGRESS_INTERVAL_MINUTES: i64 = 10;
+/// Needs that interrupt an activity when they become critical, unless the
+/// activity satisfies them.
+const INTERRUPTING_NEEDS: [&str; 4] = ["hunger", "thirst", "sleepiness", "exhaustion"];
+
+/// How an activity is carried out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActivitySpec {
+    /// Expected duration in game minutes (0 = open-ended)
+    pub duration_minutes: i64,
+    /// Catalogue tag of an object that must be within reach (e.g. "bed")
+    pub required_tag: Option<&'static str>,
+    /// Game minutes before the character agrees to switch to something else
+    pub min_minutes: i64,
+    /// One-off change to needs when the activity completes
+    pub completion: NeedRates,
+}
+
+impl Activity {
+    /// Duration, requirements and effects of this activity.
+    pub fn spec(&self) -> ActivitySpec {
+        let zero = NeedRates::default();
+        let (duration_minutes, required_tag, min_minutes, completion) = match self {
+            Activity::Idle => (0, None, 0, zero),
+            Activity::Sleeping => (
+                8 * 60,
+                Some("bed"),
+                0,
+                NeedRates {
+                    sleepiness: -10.0,
+                    ..zero
+                },
+            ),
+            Activity::Eating => (30, None, 10, zero),
+            Activity::Cooking => (
+                45,
+                Some("stove"),
+                15,
+                NeedRates {
+                    boredom: -10.0,
+                    ..zero
+                },
+            ),
+            Activity::Working => (8 * 60, None, 0, zero),
+            Activity::Exercising => (
+                60,
+                None,
+                0,
+                NeedRates {
+                    boredom: -10.0,
+                    ..zero
+                },
+            ),
+            Activity::Socializing => (60, None, 0, zero),
+            Activity::Shopping => (60, None, 0, zero),
+            Activity::Cleaning => (
+                45,
+                None,
+                0,
+                NeedRates {
+                    boredom: -5.0,
+                    ..zero
+                },
+            ),
+            Activity::Bathing => (
+                20,
+                Some("hygiene"),
+                10,
+                NeedRates {
+                    dirtiness: -20.0,
+                    ..zero
+                },
+            ),
+            Activity::Reading => (60, None, 0, zero),
+            Activity::WatchingTv => (90, Some("electronics"), 0, zero),
+            Activity::Gaming => (90, Some("electronics"), 0, zero),
+            Activity::Commuting => (30, None, 0, zero),
+        };
+        ActivitySpec {
+            duration_minutes,
+            required_tag,
+            min_minutes,
+            completion,
+        }
+    }
+
+    /// The critical need, if any, that makes the character abandon this activity.
+    ///
+    /// A need interrupts an activity unless the activity (on top of the base
+    /// decay) brings it down.
+    pub fn interrupting_need(&self, needs: &Needs) -> Option<&'static str> {
+        let net = NeedRates::BASE.plus(self.need_effects());
+        INTERRUPTING_NEEDS.into_iter().find(|&name| {
+            let satisfied = net.get(name).is_some_and(|rate| rate < 0.0);
+            !satisfied && needs.get(name).is_some_and(|level| level >= NEED_CRITICAL)
+        })
+    }
+}
+
+/// An activity in progress.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TimedActivity {
+    pub activity: Activity,
+    /// Game minute the activity started
+    pub started_at: i64,
+    /// Expected duration in game minutes (0 = open-ended)
+    pub duration_minutes: i64,
+    /// Object being used (e.g. the bed), if the activity needs one
+    pub object_id: Option<String>,
+}
+
+impl TimedActivity {
+    /// Game minutes since the activity started.
+    pub fn elapsed(&self, now_minutes: i64) -> i64 {
+        (now_minutes - self.started_at).max(0)
+    }
+
+    /// Whether the activity has run its expected duration.
+    pub fn is_complete(&self, now_minutes: i64) -> bool {
+        self.duration_minutes > 0 && self.elapsed(now_minutes) >= self.duration_minutes
+    }
+}
+
+/// How an activity ended.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityOutcome {
+    /// Ran its full duration; completion effects were applied
+    Completed,
+    /// Ended early (another activity, a critical need, the object went away)
+    Interrupted,
+}
+
+/// Activity lifecycle event, broadcast to clients.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ActivityEvent {
+    Started {
+        player_id: String,
+        activity: TimedActivity,
+    },
+    Progress {
+        player_id: String,
+        activity: Activity,
+        elapsed_minutes: i64,
+        duration_minutes: i64,
+    },
+    Finished {
+        player_id: String,
+        activity: Activity,
+        outcome: ActivityOutcome,
+        reason: Option<String>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn critical_needs_interrupt_unless_satisfied() {
+        let needs = Needs {
+            hunger: 90.0,
+            sleepiness: 90.0,
+            ..Needs::default()
+        };
+        assert_eq!(Activity::Sleeping.interrupting_need(&needs), Some("hunger"));
+        assert_eq!(
+            Activity::Eating.interrupting_need(&needs),
+            Some("sleepiness")
+        );
+        assert_eq!(Activity::Reading.interrupting_need(&needs), Some("hunger"));
+        assert_eq!(
+            Activity::Sleeping.interrupting_need(&Needs {
+                sleepiness: 100.0,
+                exhaustion: 100.0,
+                ..Needs::default()
+            }),
+            None
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Game calendar module.
+//!
+//! Game time is counted in game minutes since the Unix epoch (1 real second =
+//! 1 game minute). A game day has 24 hours of 60 minutes, and a game year has
+//! 360 days for the sake of "divide-by-60" symmetry.
+
+use serde::{Deserialize, Serialize};
+
+/// Game minutes in a game hour.
+pub const MINUTES_PER_HOUR: i64 = 60;
+/// Game minutes in a game day.
+pub const MINUTES_PER_DAY: i64 = 24 * MINUTES_PER_HOUR;
+/// Game days in a game year.
+pub const DAYS_PER_YEAR: i64 = 360;
+
+/// A point in game time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct GameTime {
+    /// Game minutes since the epoch
+    pub minutes: i64,
+}
+
+impl GameTime {
+    /// Game time for a number of game minutes since the epoch.
+    pub fn from_minutes(minutes: i64) -> Self {
+        Self { minutes }
+    }
+
+    /// Current game time.
+    pub fn now() -> Self {
+        Self::from_minutes(crate::game::GameState::get_game_time_minutes())
+    }
+
+    /// Minutes since midnight (0..1440).
+    pub fn minute_of_day(&self) -> i64 {
+        self.minutes.rem_euclid(MINUTES_PER_DAY)
+    }
+
+    /// Hour of the day (0..24).
+    pub fn hour(&self) -> i64 {
+        self.minute_of_day() / MINUTES_PER_HOUR
+    }
+
+    /// Whole game days since the epoch.
+    pub fn day(&self) -> i64 {
+        self.minutes.div_euclid(MINUTES_PER_DAY)
+    }
+
+    /// Day of the year (0..360).
+    pub fn day_of_year(&self) -> i64 {
+        self.day().rem_euclid(DAYS_PER_YEAR)
+    }
+
+    /// Game years since the epoch.
+    pub fn year(&self) -> i64 {
+        self.day().div_euclid(DAYS_PER_YEAR)
+    }
+}
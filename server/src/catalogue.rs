@@ -0,0 +1,340 @@
+//! Object catalogue module.
+//!
+//! Describes every kind of entity (humans, balls, furniture, appliances, utensils)
+//! as data. Object types are JSON files under `data/objects/`; each file holds an
+//! array of types. Adding a new household object is a content change: no Rust code
+//! needs to know about it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::level::data_dir;
+use crate::properties::Properties;
+
+/// Collision shape of an object type, in meters.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ShapeDef {
+    /// Sphere
+    Ball { radius: f32 },
+    /// Box given by its half-extents (x, y, z)
+    Cuboid { half_extents: [f32; 3] },
+    /// Vertical capsule
+    Capsule { half_height: f32, radius: f32 },
+    /// Vertical cylinder
+    Cylinder { half_height: f32, radius: f32 },
+}
+
+impl ShapeDef {
+    /// Distance from the shape's center to its bottom (meters).
+    pub fn half_height(&self) -> f32 {
+        match self {
+            ShapeDef::Ball { radius } => *radius,
+            ShapeDef::Cuboid { half_extents } => half_extents[1],
+            ShapeDef::Capsule {
+                half_height,
+                radius,
+            } => half_height + radius,
+            ShapeDef::Cylinder { half_height, .. } => *half_height,
+        }
+    }
+
+    /// Largest horizontal distance from the shape's center to its side (meters).
+    pub fn half_width(&self) -> f32 {
+        match self {
+            ShapeDef::Ball { radius }
+            | ShapeDef::Capsule { radius, .. }
+            | ShapeDef::Cylinder { radius, .. } => *radius,
+            ShapeDef::Cuboid { half_extents } => half_extents[0].max(half_extents[2]),
+        }
+    }
+
+    /// Volume of the shape (cubic meters).
+    pub fn volume(&self) -> f32 {
+        use std::f32::consts::PI;
+        match self {
+            ShapeDef::Ball { radius } => 4.0 / 3.0 * PI * radius.powi(3),
+            ShapeDef::Cuboid { half_extents } => {
+                8.0 * half_extents[0] * half_extents[1] * half_extents[2]
+            }
+            ShapeDef::Capsule {
+                half_height,
+                radius,
+            } => PI * radius.powi(2) * (2.0 * half_height + 4.0 / 3.0 * radius),
+            ShapeDef::Cylinder {
+                half_height,
+                radius,
+            } => PI * radius.powi(2) * 2.0 * half_height,
+        }
+    }
+}
+
+/// How the physics world moves objects of a type.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BodyKind {
+    /// Simulated by physics (falls, bounces, can be pushed)
+    #[default]
+    Dynamic,
+    /// Never moves (built-in furniture, heavy appliances)
+    Fixed,
+    /// Moved by the server only, pushes dynamic objects
+    Kinematic,
+    /// Moved by the character controller; the position is at the feet
+    Character,
+}
+
+/// Storage space of a container object type (fridge, cupboard).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ContainerDef {
+    /// Number of inventory slots
+    pub slots: usize,
+    /// Maximum total weight of the contents (kilograms)
+    pub max_weight: f32,
+}
+
+/// What a serving of a food object type does for a character.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct FoodDef {
+    /// Hunger satisfied by one serving (need points)
+    pub nutrition: f32,
+    /// Thirst satisfied by one serving (need points)
+    #[serde(default)]
+    pub hydration: f32,
+    /// Game days fresh food keeps at room temperature; None for food that keeps
+    #[serde(default)]
+    pub shelf_life_days: Option<f32>,
+}
+
+/// Definition of one kind of object.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ObjectType {
+    /// Unique type name (e.g. "human", "ball", "bed_double"); stored in `entity_types`
+    pub name: String,
+    /// Client-side mesh asset (e.g. "assets/bedDouble.glb")
+    #[serde(default)]
+    pub mesh: Option<String>,
+    /// Collision shape
+    pub shape: ShapeDef,
+    /// Physics body kind
+    #[serde(default)]
+    pub body: BodyKind,
+    /// Mass in kilograms (derived from the shape with unit density if omitted)
+    #[serde(default)]
+    pub mass: Option<f32>,
+    /// Friction coefficient
+    #[serde(default = "default_friction")]
+    pub friction: f32,
+    /// Restitution (bounciness, 1.0 = perfectly elastic)
+    #[serde(default)]
+    pub restitution: f32,
+    /// Enable continuous collision detection (small, fast objects)
+    #[serde(default)]
+    pub ccd: bool,
+    /// Maximum random horizontal speed given on spawn (m/game-second)
+    #[serde(default)]
+    pub random_velocity: f32,
+    /// Interaction verbs the object supports (e.g. "sit", "sleep", "open")
+    #[serde(default)]
+    pub verbs: Vec<String>,
+    /// Free-form tags for rules and queries (e.g. "furniture", "kitchen_appliance")
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// How many of this item fit in one inventory slot
+    #[serde(default = "default_max_stack")]
+    pub max_stack: u32,
+    /// Storage space, for containers that hold items
+    #[serde(default)]
+    pub container: Option<ContainerDef>,
+    /// Shop price in cents; types without a price are not for sale
+    #[serde(default)]
+    pub price: Option<i64>,
+    /// Properties of the type's entities with their default values, e.g. {"on": false}
+    #[serde(default)]
+    pub properties: Properties,
+    /// Nutrition and shelf life, for food
+    #[serde(default)]
+    pub food: Option<FoodDef>,
+}
+
+fn default_friction() -> f32 {
+    0.5
+}
+
+fn default_max_stack() -> u32 {
+    1
+}
+
+impl ObjectType {
+    /// Whether the type carries the given tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Whether the type supports the given interaction verb.
+    pub fn has_verb(&self, verb: &str) -> bool {
+        self.verbs.iter().any(|v| v == verb)
+    }
+
+    /// Whether objects of this type can be carried in an inventory.
+    pub fn is_item(&self) -> bool {
+        self.has_verb("pick_up") && self.body == BodyKind::Dynamic
+    }
+
+    /// Weight of one object in kilograms, for inventory limits.
+    ///
+    /// Types without a mass weigh as much as their volume of water.
+    pub fn weight(&self) -> f32 {
+        self.mass.unwrap_or_else(|| 1000.0 * self.shape.volume())
+    }
+}
+
+/// Registry of all object types, keyed by name.
+#[derive(Clone, Debug, Default)]
+pub struct Catalogue {
+    types: HashMap<String, ObjectType>,
+}
+
+impl Catalogue {
+    /// Built-in catalogue with the core types (human, ball).
+    ///
+    /// Used when no catalogue files can be found. Matches `data/objects/core.json`.
+    pub fn core() -> Self {
+        let mut catalogue = Self::default();
+        catalogue.insert(ObjectType {
+            name: "human".to_string(),
+            mesh: None,
+            shape: ShapeDef::Capsule {
+                half_height: 0.5,
+                radius: 0.3,
+            },
+            body: BodyKind::Character,
+            mass: Some(70.0),
+            friction: 0.5,
+            restitution: 0.0,
+            ccd: false,
+            random_velocity: 0.0,
+            verbs: vec!["talk".to_string()],
+            tags: vec!["character".to_string()],
+            max_stack: 1,
+            container: None,
+            price: None,
+            properties: Properties::new(),
+            food: None,
+        });
+        catalogue.insert(ObjectType {
+            name: "ball".to_string(),
+            mesh: None,
+            // Ball radius is 0.5m to match visual representation
+            shape: ShapeDef::Ball { radius: 0.5 },
+            body: BodyKind::Dynamic,
+            mass: Some(0.1), // 100g ball
+            friction: 0.0,
+            restitution: 1.0, // Perfect elasticity
+            ccd: true,
+            random_velocity: 1.0,
+            verbs: vec!["pick_up".to_string(), "throw".to_string()],
+            tags: vec!["toy".to_string()],
+            max_stack: 1,
+            container: None,
+            price: None,
+            properties: Properties::new(),
+            food: None,
+        });
+        catalogue
+    }
+
+    /// Add or replace an object type.
+    pub fn insert(&mut self, object_type: ObjectType) {
+        self.types.insert(object_type.name.clone(), object_type);
+    }
+
+    /// Look up an object type by name.
+    pub fn get(&self, name: &str) -> Option<&ObjectType> {
+        self.types.get(name)
+    }
+
+    /// All object types, sorted by name.
+    pub fn types(&self) -> Vec<&ObjectType> {
+        let mut types: Vec<_> = self.types.values().collect();
+        types.sort_by(|a, b| a.name.cmp(&b.name));
+        types
+    }
+
+    /// All object types carrying the given tag.
+    pub fn with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a ObjectType> + 'a {
+        self.types.values().filter(move |t| t.has_tag(tag))
+    }
+
+    /// Load every `*.json` file in a directory. Later files override earlier ones
+    /// (files are read in name order).
+    pub fn load_dir(dir: &Path) -> anyhow::Result<Self> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let mut catalogue = Self::default();
+        for path in paths {
+            let text = std::fs::read_to_string(&path)?;
+            let types: Vec<ObjectType> = serde_json::from_str(&text)
+                .map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+            for object_type in types {
+                catalogue.insert(object_type);
+            }
+        }
+        Ok(catalogue)
+    }
+
+    /// Load the catalogue from `data/objects/`.
+    ///
+    /// Falls back to the built-in core catalogue if the directory is missing or
+    /// invalid. Core types missing from the files are always added.
+    pub fn load_configured() -> Self {
+        let dir = data_dir().join("objects");
+        let mut catalogue = match Self::load_dir(&dir) {
+            Ok(catalogue) => {
+                tracing::info!(
+                    "Loaded {} object types from {}",
+                    catalogue.types.len(),
+                    dir.display()
+                );
+                catalogue
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to load object catalogue from {}: {e}; using core types",
+                    dir.display()
+                );
+                Self::default()
+            }
+        };
+        for core_type in Self::core().types.into_values() {
+            catalogue
+                .types
+                .entry(core_type.name.clone())
+                .or_insert(core_type);
+        }
+        catalogue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_files_include_core_types() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/objects");
+        let catalogue = Catalogue::load_dir(&dir).expect("object files should parse");
+        for core_type in Catalogue::core().types() {
+            let from_file = catalogue.get(&core_type.name).expect("core type in files");
+            assert_eq!(
+                serde_json::to_value(from_file).unwrap(),
+                serde_json::to_value(core_type).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,391 @@
+//! In-game chat module.
+//!
+//! Characters talk on three kinds of channel: `say` reaches everyone within
+//! earshot of the speaker's position, `whisper` reaches one character anywhere in
+//! the world, and `group` reaches the members of a named group channel. The server
+//! limits how long a message may be and how many messages a character may send in
+//! a short (real) time, and passes every message through a [`Moderator`] that can
+//! let it through, mask parts of it or block it. Characters can also block others
+//! whose messages they no longer want to receive.
+//!
+//! The default moderator is a [`WordFilter`] loaded from `CHAT_FILTER_PATH` or
+//! `data/chat/filter.json`. With `PERSIST_CHAT` enabled, delivered messages are
+//! kept for saving to the `chat_messages` table.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::level::data_dir;
+
+/// Longest message (characters).
+pub const MAX_MESSAGE_CHARS: usize = 280;
+/// Distance (m) within which `say` can be heard.
+pub const SAY_DISTANCE: f32 = 20.0;
+/// Most messages a character may send within [`RATE_LIMIT_WINDOW`].
+const RATE_LIMIT_MESSAGES: usize = 5;
+/// Real time over which messages count towards the rate limit.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(10);
+/// Longest group channel name (characters).
+const MAX_GROUP_NAME_CHARS: usize = 32;
+
+/// Where a message goes.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChatChannel {
+    /// Everyone within [`SAY_DISTANCE`] of the speaker
+    Say,
+    /// One character, wherever they are
+    Whisper { to: String },
+    /// Members of a group channel
+    Group { name: String },
+}
+
+/// A message as delivered to its recipients.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChatMessage {
+    /// Unique ID (UUID)
+    pub id: String,
+    /// ID of the sender
+    pub from: String,
+    /// Display name of the sender
+    pub from_name: String,
+    pub channel: ChatChannel,
+    /// Text after moderation
+    pub text: String,
+    /// Game minute the message was sent
+    pub game_minute: i64,
+}
+
+/// Verdict of a [`Moderator`] on a message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Moderation {
+    /// Deliver the message as written
+    Allow,
+    /// Deliver this text instead
+    Replace(String),
+    /// Do not deliver the message, for this reason
+    Block(String),
+}
+
+/// Hook that reviews every message before it is delivered.
+pub trait Moderator: Send + Sync {
+    fn review(&self, from: &str, channel: &ChatChannel, text: &str) -> Moderation;
+}
+
+/// Moderator that masks some words and blocks messages containing others.
+///
+/// Words match case-insensitively, as whole words.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct WordFilter {
+    /// Words replaced by asterisks
+    #[serde(default)]
+    pub masked: Vec<String>,
+    /// Words that stop the message
+    #[serde(default)]
+    pub blocked: Vec<String>,
+}
+
+impl WordFilter {
+    /// Load a filter from a JSON file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let mut filter: WordFilter = serde_json::from_str(&text)?;
+        for word in filter.masked.iter_mut().chain(filter.blocked.iter_mut()) {
+            *word = word.to_lowercase();
+        }
+        Ok(filter)
+    }
+
+    /// Load the filter from `CHAT_FILTER_PATH` or `data/chat/filter.json`.
+    ///
+    /// Nothing is filtered if the file is missing or invalid.
+    pub fn load_configured() -> Self {
+        let path = std::env::var("CHAT_FILTER_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| data_dir().join("chat").join("filter.json"));
+        match Self::load(&path) {
+            Ok(filter) => {
+                tracing::info!(
+                    "Loaded chat filter ({} masked, {} blocked words) from {}",
+                    filter.masked.len(),
+                    filter.blocked.len(),
+                    path.display()
+                );
+                filter
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load chat filter from {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+}
+
+impl Moderator for WordFilter {
+    fn review(&self, _from: &str, _channel: &ChatChannel, text: &str) -> Moderation {
+        let mut masked = false;
+        let words: Vec<String> = text
+            .split(' ')
+            .map(|word| {
+                let bare = word
+                    .trim_matches(|c: char| !c.is_alphanumeric())
+                    .to_lowercase();
+                if self.masked.contains(&bare) {
+                    masked = true;
+                    "*".repeat(word.chars().count())
+                } else {
+                    word.to_string()
+                }
+            })
+            .collect();
+        let blocked = text
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| self.blocked.contains(&word.to_lowercase()));
+        if blocked {
+            Moderation::Block("message not allowed".to_string())
+        } else if masked {
+            Moderation::Replace(words.join(" "))
+        } else {
+            Moderation::Allow
+        }
+    }
+}
+
+/// Chat state: rate limits, group channels, block lists and unsaved history.
+pub struct Chat {
+    moderator: Box<dyn Moderator>,
+    /// Send times of recent messages, by player ID
+    recent: HashMap<String, VecDeque<Instant>>,
+    /// Members of each group channel
+    groups: HashMap<String, HashSet<String>>,
+    /// Players each player does not want to hear from
+    blocked: HashMap<String, HashSet<String>>,
+    /// Whether delivered messages are kept for persisting
+    persist: bool,
+    unsaved: Vec<ChatMessage>,
+}
+
+impl Default for Chat {
+    fn default() -> Self {
+        Self::new(Box::new(WordFilter::default()), false)
+    }
+}
+
+impl Chat {
+    pub fn new(moderator: Box<dyn Moderator>, persist: bool) -> Self {
+        Self {
+            moderator,
+            recent: HashMap::new(),
+            groups: HashMap::new(),
+            blocked: HashMap::new(),
+            persist,
+            unsaved: Vec::new(),
+        }
+    }
+
+    /// Chat with the configured word filter, persisting history if `PERSIST_CHAT`
+    /// is enabled.
+    pub fn configured() -> Self {
+        let persist = std::env::var("PERSIST_CHAT")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        Self::new(Box::new(WordFilter::load_configured()), persist)
+    }
+
+    /// Check a message against the limits and the moderator.
+    ///
+    /// Returns the text to deliver. Counts towards the sender's rate limit.
+    pub fn review(
+        &mut self,
+        from: &str,
+        channel: &ChatChannel,
+        text: &str,
+        now: Instant,
+    ) -> Result<String, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("empty message".to_string());
+        }
+        if text.chars().count() > MAX_MESSAGE_CHARS {
+            return Err(format!("longer than {MAX_MESSAGE_CHARS} characters"));
+        }
+        match channel {
+            ChatChannel::Say => {}
+            ChatChannel::Whisper { to } if to == from => {
+                return Err("whispering to yourself".to_string());
+            }
+            ChatChannel::Whisper { .. } => {}
+            ChatChannel::Group { name } => {
+                if !self.members(name).contains(from) {
+                    return Err(format!("not in group {name}"));
+                }
+            }
+        }
+
+        let recent = self.recent.entry(from.to_string()).or_default();
+        while recent
+            .front()
+            .is_some_and(|sent| now.duration_since(*sent) >= RATE_LIMIT_WINDOW)
+        {
+            recent.pop_front();
+        }
+        if recent.len() >= RATE_LIMIT_MESSAGES {
+            return Err("sending too fast".to_string());
+        }
+        recent.push_back(now);
+
+        match self.moderator.review(from, channel, text) {
+            Moderation::Allow => Ok(text.to_string()),
+            Moderation::Replace(text) => Ok(text),
+            Moderation::Block(reason) => Err(reason),
+        }
+    }
+
+    /// Keep only the recipients who did not block the sender.
+    pub fn unblocked(&self, from: &str, recipients: Vec<String>) -> Vec<String> {
+        recipients
+            .into_iter()
+            .filter(|id| !self.blocked.get(id).is_some_and(|b| b.contains(from)))
+            .collect()
+    }
+
+    /// Record a delivered message for persisting, if enabled.
+    pub fn record(&mut self, message: &ChatMessage) {
+        if self.persist {
+            self.unsaved.push(message.clone());
+        }
+    }
+
+    /// Members of a group channel.
+    pub fn members(&self, group: &str) -> HashSet<String> {
+        self.groups.get(group).cloned().unwrap_or_default()
+    }
+
+    /// Add a player to a group channel, creating it if needed.
+    pub fn join_group(&mut self, player_id: &str, group: &str) -> Result<(), String> {
+        let valid = !group.is_empty()
+            && group.chars().count() <= MAX_GROUP_NAME_CHARS
+            && group
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+        if !valid {
+            return Err(format!("invalid group name {group:?}"));
+        }
+        self.groups
+            .entry(group.to_string())
+            .or_default()
+            .insert(player_id.to_string());
+        Ok(())
+    }
+
+    /// Remove a player from a group channel; empty channels disappear.
+    pub fn leave_group(&mut self, player_id: &str, group: &str) {
+        if let Some(members) = self.groups.get_mut(group) {
+            members.remove(player_id);
+            if members.is_empty() {
+                self.groups.remove(group);
+            }
+        }
+    }
+
+    /// Group channels a player is in, sorted by name.
+    pub fn groups_of(&self, player_id: &str) -> Vec<String> {
+        let mut groups: Vec<_> = self
+            .groups
+            .iter()
+            .filter(|(_, members)| members.contains(player_id))
+            .map(|(name, _)| name.clone())
+            .collect();
+        groups.sort();
+        groups
+    }
+
+    /// Block or unblock messages from another player.
+    pub fn set_blocked(&mut self, player_id: &str, other_id: &str, blocked: bool) {
+        let list = self.blocked.entry(player_id.to_string()).or_default();
+        if blocked {
+            list.insert(other_id.to_string());
+        } else {
+            list.remove(other_id);
+        }
+    }
+
+    /// Players a player blocked, sorted by ID.
+    pub fn blocked_by(&self, player_id: &str) -> Vec<String> {
+        let mut blocked: Vec<_> = self
+            .blocked
+            .get(player_id)
+            .map(|b| b.iter().cloned().collect())
+            .unwrap_or_default();
+        blocked.sort();
+        blocked
+    }
+
+    /// Take the messages recorded since the last call, for persisting.
+    pub fn take_unsaved(&mut self) -> Vec<ChatMessage> {
+        std::mem::take(&mut self.unsaved)
+    }
+
+    /// Put back messages that failed to persist, ahead of newer ones.
+    pub fn return_unsaved(&mut self, mut messages: Vec<ChatMessage>) {
+        messages.append(&mut self.unsaved);
+        self.unsaved = messages;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_limited_and_moderated() {
+        let filter = WordFilter {
+            masked: vec!["darn".to_string()],
+            blocked: vec!["spam".to_string()],
+        };
+        let mut chat = Chat::new(Box::new(filter), false);
+        let start = Instant::now();
+        let say = ChatChannel::Say;
+        assert_eq!(
+            chat.review("ada", &say, " Darn it! ", start),
+            Ok("**** it!".to_string())
+        );
+        assert!(chat.review("ada", &say, "buy SPAM now", start).is_err());
+        assert!(chat.review("ada", &say, "   ", start).is_err());
+        let long = "a".repeat(MAX_MESSAGE_CHARS + 1);
+        assert!(chat.review("ada", &say, &long, start).is_err());
+
+        // Two messages reviewed above count towards the limit
+        for _ in 0..RATE_LIMIT_MESSAGES - 2 {
+            assert!(chat.review("ada", &say, "hi", start).is_ok());
+        }
+        assert_eq!(
+            chat.review("ada", &say, "hi", start),
+            Err("sending too fast".to_string())
+        );
+        assert!(chat
+            .review("ada", &say, "hi", start + RATE_LIMIT_WINDOW)
+            .is_ok());
+    }
+
+    #[test]
+    fn groups_and_blocks_decide_who_hears() {
+        let mut chat = Chat::default();
+        let group = ChatChannel::Group {
+            name: "band".to_string(),
+        };
+        assert!(chat.review("ada", &group, "hi", Instant::now()).is_err());
+        chat.join_group("ada", "band").unwrap();
+        assert!(chat.join_group("ada", "no spaces").is_err());
+        assert!(chat.review("ada", &group, "hi", Instant::now()).is_ok());
+        assert_eq!(chat.groups_of("ada"), vec!["band"]);
+
+        chat.set_blocked("bruno", "ada", true);
+        let everyone = vec!["bruno".to_string(), "chen".to_string()];
+        assert_eq!(chat.unblocked("ada", everyone), vec!["chen"]);
+        chat.leave_group("ada", "band");
+        assert!(chat.groups_of("ada").is_empty());
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! Handles PostgreSQL connection pooling and entity persistence.
 
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use sqlx::{postgres::PgPoolOptions, PgConnection, PgExecutor, PgPool};
+use std::collections::HashMap;
 use std::time::Duration;
 use uuid::Uuid;
 
@@ -43,19 +44,48 @@ pub async fn set_game_time_minutes(pool: &PgPool, game_time_minutes: i64) -> any
 /// Get entity type ID by name from the database.
 ///
 /// # Arguments
-/// * `pool` - Database connection pool
+/// * `executor` - Database connection pool or transaction
 /// * `name` - Entity type name (e.g., "human", "ball")
 ///
 /// # Returns
 /// Entity type ID from the `entity_types` table
-pub async fn get_entity_type_id(pool: &PgPool, name: &str) -> anyhow::Result<i32> {
+pub async fn get_entity_type_id(executor: impl PgExecutor<'_>, name: &str) -> anyhow::Result<i32> {
     let id: (i32,) = sqlx::query_as("SELECT id FROM entity_types WHERE name = $1")
         .bind(name)
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
     Ok(id.0)
 }
 
+/// Seed the `entity_types` table from the object catalogue.
+///
+/// Inserts any missing type names and refreshes the stored JSON definition of
+/// existing ones, so the database always reflects the loaded catalogue.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+/// * `catalogue` - Loaded object catalogue
+pub async fn seed_entity_types(
+    pool: &PgPool,
+    catalogue: &crate::catalogue::Catalogue,
+) -> anyhow::Result<()> {
+    for object_type in catalogue.types() {
+        let definition = serde_json::to_string(object_type)?;
+        sqlx::query(
+            r#"
+            INSERT INTO entity_types (name, definition)
+            VALUES ($1, $2::jsonb)
+            ON CONFLICT (name) DO UPDATE SET definition = EXCLUDED.definition
+            "#,
+        )
+        .bind(&object_type.name)
+        .bind(definition)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
 /// Entity data structure for database operations.
 ///
 /// Contains entity information in a format suitable for database storage.
@@ -66,6 +96,8 @@ pub async fn get_entity_type_id(pool: &PgPool, name: &str) -> anyhow::Result<i32
 pub struct EntityData {
     /// Entity identifier (can be UUID string or any string)
     pub id: String,
+    /// Zone the entity is in
+    pub zone: String,
     /// Entity type name (e.g., "human", "ball")
     pub entity_type_name: String,
     /// X position in centimeters
@@ -80,6 +112,64 @@ pub struct EntityData {
     pub rotation_y: i32,
     /// Z rotation in radians (stored as integer)
     pub rotation_z: i32,
+    /// Player who owns the entity, if anyone
+    pub owner_id: Option<String>,
+    /// Properties set on the entity, as a JSON object
+    pub properties: String,
+}
+
+impl EntityData {
+    /// Database form of an entity in a zone.
+    pub fn from_entity(zone: &str, entity: &crate::game::Entity) -> anyhow::Result<Self> {
+        // Simulation is in meters; DB storage is centimeters as integers.
+        let to_db_cm = |meters: f32| (meters * 100.0).round() as i32;
+        Ok(Self {
+            id: entity.id.clone(),
+            zone: zone.to_string(),
+            entity_type_name: entity.entity_type.as_str().to_string(),
+            position_x: to_db_cm(entity.position.x),
+            position_y: to_db_cm(entity.position.y),
+            position_z: to_db_cm(entity.position.z),
+            rotation_x: entity.rotation.x as i32,
+            rotation_y: entity.rotation.y as i32,
+            rotation_z: entity.rotation.z as i32,
+            owner_id: entity.owner.clone(),
+            properties: serde_json::to_string(&entity.properties)?,
+        })
+    }
+
+    /// The entity stored in this row.
+    pub fn into_entity(self) -> anyhow::Result<crate::game::Entity> {
+        let from_db_cm = |centimeters: i32| centimeters as f32 / 100.0;
+        Ok(crate::game::Entity {
+            id: self.id,
+            entity_type: crate::game::EntityType::new(self.entity_type_name),
+            position: crate::game::Position {
+                x: from_db_cm(self.position_x),
+                y: from_db_cm(self.position_y),
+                z: from_db_cm(self.position_z),
+            },
+            rotation: crate::game::Rotation {
+                x: self.rotation_x as f32,
+                y: self.rotation_y as f32,
+                z: self.rotation_z as f32,
+            },
+            owner: self.owner_id,
+            properties: serde_json::from_str(&self.properties)?,
+        })
+    }
+}
+
+/// Database ID of an entity.
+///
+/// Entity IDs can be UUID strings or any string identifier; non-UUID strings
+/// are converted to a deterministic UUID v5.
+pub fn entity_uuid(id: &str) -> Uuid {
+    Uuid::parse_str(id).unwrap_or_else(|_| {
+        // Use a fixed namespace UUID for entity IDs
+        let namespace = Uuid::parse_str("6ba7b810-9dad-11d1-80b4-00c04fd430c8").unwrap();
+        Uuid::new_v5(&namespace, id.as_bytes())
+    })
 }
 
 /// Upsert (insert or update) an entity in the database.
@@ -91,21 +181,16 @@ pub struct EntityData {
 /// Non-UUID strings are converted to deterministic UUID v5 for storage.
 ///
 /// # Arguments
-/// * `pool` - Database connection pool
+/// * `conn` - Database connection or transaction
 /// * `data` - Entity data to save
-pub async fn upsert_entity(pool: &PgPool, data: &EntityData) -> anyhow::Result<()> {
-    let type_id = get_entity_type_id(pool, &data.entity_type_name).await?;
-    // Try to parse as UUID, if it fails, generate a deterministic UUID v5 from the string
-    let uuid_id = Uuid::parse_str(&data.id).unwrap_or_else(|_| {
-        // Use a fixed namespace UUID for entity IDs
-        let namespace = Uuid::parse_str("6ba7b810-9dad-11d1-80b4-00c04fd430c8").unwrap();
-        Uuid::new_v5(&namespace, data.id.as_bytes())
-    });
+pub async fn upsert_entity(conn: &mut PgConnection, data: &EntityData) -> anyhow::Result<()> {
+    let type_id = get_entity_type_id(&mut *conn, &data.entity_type_name).await?;
+    let uuid_id = entity_uuid(&data.id);
 
     sqlx::query(
         r#"
-        INSERT INTO entities (id, entity_type_id, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        INSERT INTO entities (id, entity_type_id, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, owner_id, properties, entity_key, zone)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10::jsonb, $11, $12)
         ON CONFLICT (id) DO UPDATE SET
             entity_type_id = EXCLUDED.entity_type_id,
             position_x = EXCLUDED.position_x,
@@ -114,6 +199,10 @@ pub async fn upsert_entity(pool: &PgPool, data: &EntityData) -> anyhow::Result<(
             rotation_x = EXCLUDED.rotation_x,
             rotation_y = EXCLUDED.rotation_y,
             rotation_z = EXCLUDED.rotation_z,
+            owner_id = EXCLUDED.owner_id,
+            properties = EXCLUDED.properties,
+            entity_key = EXCLUDED.entity_key,
+            zone = EXCLUDED.zone,
             updated_at = NOW()
         "#,
     )
@@ -125,38 +214,472 @@ pub async fn upsert_entity(pool: &PgPool, data: &EntityData) -> anyhow::Result<(
     .bind(data.rotation_x)
     .bind(data.rotation_y)
     .bind(data.rotation_z)
-    .execute(pool)
+    .bind(&data.owner_id)
+    .bind(&data.properties)
+    .bind(&data.id)
+    .bind(&data.zone)
+    .execute(conn)
     .await?;
 
     Ok(())
 }
 
-/// Save all entities to the database.
+/// Save a zone's objects and the inventories of its characters and containers.
+///
+/// Objects are upserted and each owner's rows in `inventory_items` are replaced
+/// by its current contents. Removed objects are deleted together with anything
+/// stored in them. Everything is written in one transaction, so an item is never
+/// stored both in the world and in an inventory.
 ///
-/// Converts game entities to database format and upserts them.
 /// Called periodically (every 60 seconds) to persist game state.
 ///
 /// # Arguments
 /// * `pool` - Database connection pool
-/// * `entities` - Slice of game entities to save
-pub async fn save_all_entities(
+/// * `zone` - Zone the objects are in
+/// * `entities` - Objects in the zone
+/// * `removed` - IDs of the objects removed from the zone since the last save
+/// * `inventories` - (player or container entity ID, inventory) pairs
+pub async fn save_objects(
     pool: &PgPool,
+    zone: &str,
     entities: &[crate::game::Entity],
+    removed: &[String],
+    inventories: &[(String, crate::inventory::Inventory)],
 ) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    let uuids: Vec<Uuid> = removed.iter().map(|id| entity_uuid(id)).collect();
+    sqlx::query("DELETE FROM entities WHERE id = ANY($1)")
+        .bind(&uuids)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM inventory_items WHERE owner_id = ANY($1)")
+        .bind(removed)
+        .execute(&mut *tx)
+        .await?;
     for entity in entities {
-        // Simulation is in meters; DB storage is centimeters as integers.
-        let to_db_cm = |meters: f32| (meters * 100.0).round() as i32;
-        let data = EntityData {
-            id: entity.id.clone(),
-            entity_type_name: entity.entity_type.as_str().to_string(),
-            position_x: to_db_cm(entity.position.x),
-            position_y: to_db_cm(entity.position.y),
-            position_z: to_db_cm(entity.position.z),
-            rotation_x: entity.rotation.x as i32,
-            rotation_y: entity.rotation.y as i32,
-            rotation_z: entity.rotation.z as i32,
+        upsert_entity(&mut tx, &EntityData::from_entity(zone, entity)?).await?;
+    }
+    for (owner_id, inventory) in inventories {
+        sqlx::query("DELETE FROM inventory_items WHERE owner_id = $1")
+            .bind(owner_id)
+            .execute(&mut *tx)
+            .await?;
+        for (slot, stack) in inventory.stacks() {
+            sqlx::query(
+                r#"
+                INSERT INTO inventory_items (owner_id, slot, item_type, count, item_owner, properties)
+                VALUES ($1, $2, $3, $4, $5, $6::jsonb)
+                "#,
+            )
+            .bind(owner_id)
+            .bind(slot as i32)
+            .bind(&stack.item_type)
+            .bind(stack.count as i32)
+            .bind(&stack.owner)
+            .bind(serde_json::to_string(&stack.properties)?)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Load the entities stored for a zone.
+///
+/// Rows saved before entities were stored with their zone are left out.
+pub async fn load_entities(pool: &PgPool, zone: &str) -> anyhow::Result<Vec<crate::game::Entity>> {
+    type Row = (
+        String,
+        String,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        Option<String>,
+        String,
+    );
+    let rows: Vec<Row> = sqlx::query_as(
+        r#"
+        SELECT e.entity_key, t.name, e.position_x, e.position_y, e.position_z,
+               e.rotation_x, e.rotation_y, e.rotation_z, e.owner_id, e.properties::text
+        FROM entities e
+        JOIN entity_types t ON t.id = e.entity_type_id
+        WHERE e.zone = $1 AND e.entity_key IS NOT NULL
+        "#,
+    )
+    .bind(zone)
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter()
+        .map(
+            |(id, entity_type_name, x, y, z, rx, ry, rz, owner_id, properties)| {
+                EntityData {
+                    id,
+                    zone: zone.to_string(),
+                    entity_type_name,
+                    position_x: x,
+                    position_y: y,
+                    position_z: z,
+                    rotation_x: rx,
+                    rotation_y: ry,
+                    rotation_z: rz,
+                    owner_id,
+                    properties,
+                }
+                .into_entity()
+            },
+        )
+        .collect()
+}
+
+/// Load the stored properties of the given entities.
+///
+/// # Returns
+/// (entity ID, properties) for each of the entities found in the database
+pub async fn load_entity_properties(
+    pool: &PgPool,
+    entity_ids: &[String],
+) -> anyhow::Result<Vec<(String, crate::properties::Properties)>> {
+    let by_uuid: std::collections::HashMap<Uuid, &String> =
+        entity_ids.iter().map(|id| (entity_uuid(id), id)).collect();
+    let uuids: Vec<Uuid> = by_uuid.keys().copied().collect();
+    let rows: Vec<(Uuid, String)> =
+        sqlx::query_as("SELECT id, properties::text FROM entities WHERE id = ANY($1)")
+            .bind(&uuids)
+            .fetch_all(pool)
+            .await?;
+    rows.into_iter()
+        .filter_map(|(uuid, properties)| Some((by_uuid.get(&uuid)?.to_string(), properties)))
+        .map(|(id, properties)| Ok((id, serde_json::from_str(&properties)?)))
+        .collect()
+}
+
+/// Load every stored inventory slot.
+///
+/// # Returns
+/// (player or container entity ID, slot, stack) for each occupied slot
+pub async fn load_inventories(
+    pool: &PgPool,
+) -> anyhow::Result<Vec<(String, usize, crate::inventory::ItemStack)>> {
+    let rows: Vec<(String, i32, String, i32, Option<String>, String)> = sqlx::query_as(
+        "SELECT owner_id, slot, item_type, count, item_owner, properties::text FROM inventory_items ORDER BY owner_id, slot",
+    )
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter()
+        .map(|(owner_id, slot, item_type, count, owner, properties)| {
+            let stack = crate::inventory::ItemStack {
+                item_type,
+                count: count.max(0) as u32,
+                owner,
+                properties: serde_json::from_str(&properties)?,
+            };
+            Ok((owner_id, slot.max(0) as usize, stack))
+        })
+        .collect()
+}
+
+/// Save relationships that changed since the last save.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+/// * `relationships` - (character ID, character ID, relationship) triples
+pub async fn save_relationships(
+    pool: &PgPool,
+    relationships: &[(String, String, crate::relationship::Relationship)],
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    for (a, b, relationship) in relationships {
+        sqlx::query(
+            r#"
+            INSERT INTO relationships (player_a, player_b, familiarity, affection, trust, last_interaction_minute)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (player_a, player_b) DO UPDATE SET
+                familiarity = EXCLUDED.familiarity,
+                affection = EXCLUDED.affection,
+                trust = EXCLUDED.trust,
+                last_interaction_minute = EXCLUDED.last_interaction_minute
+            "#,
+        )
+        .bind(a)
+        .bind(b)
+        .bind(relationship.familiarity)
+        .bind(relationship.affection)
+        .bind(relationship.trust)
+        .bind(relationship.last_interaction)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Load the relationship graph.
+///
+/// # Returns
+/// (character ID, character ID, relationship) for each pair that has met
+pub async fn load_relationships(
+    pool: &PgPool,
+) -> anyhow::Result<Vec<(String, String, crate::relationship::Relationship)>> {
+    let rows: Vec<(String, String, f32, f32, f32, i64)> = sqlx::query_as(
+        "SELECT player_a, player_b, familiarity, affection, trust, last_interaction_minute FROM relationships",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(a, b, familiarity, affection, trust, last_interaction)| {
+            let relationship = crate::relationship::Relationship {
+                familiarity,
+                affection,
+                trust,
+                last_interaction,
+            };
+            (a, b, relationship)
+        })
+        .collect())
+}
+
+/// Save accounts and their characters.
+///
+/// Characters are only ever added; existing rows are left alone.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+/// * `accounts` - Accounts changed since the last save
+pub async fn save_accounts(
+    pool: &PgPool,
+    accounts: &[crate::account::Account],
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    for account in accounts {
+        sqlx::query(
+            r#"
+            INSERT INTO user_accounts (id, username)
+            VALUES ($1, $2)
+            ON CONFLICT (id) DO UPDATE SET username = EXCLUDED.username
+            "#,
+        )
+        .bind(&account.id)
+        .bind(&account.username)
+        .execute(&mut *tx)
+        .await?;
+        for (position, character) in account.characters.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO characters (id, account_id, name, position, genome, appearance)
+                VALUES ($1, $2, $3, $4, $5::jsonb, $6::jsonb)
+                ON CONFLICT (id) DO NOTHING
+                "#,
+            )
+            .bind(&character.id)
+            .bind(&account.id)
+            .bind(&character.name)
+            .bind(position as i32)
+            .bind(serde_json::to_string(&character.genome)?)
+            .bind(serde_json::to_string(&character.appearance)?)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Load every account with its characters.
+pub async fn load_accounts(pool: &PgPool) -> anyhow::Result<Vec<crate::account::Account>> {
+    let accounts: Vec<(String, String)> =
+        sqlx::query_as("SELECT id, username FROM user_accounts ORDER BY id")
+            .fetch_all(pool)
+            .await?;
+    let rows: Vec<(String, String, String, String, String)> = sqlx::query_as(
+        r#"
+        SELECT id, account_id, name, genome::text, appearance::text
+        FROM characters
+        ORDER BY account_id, position
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut characters: HashMap<String, Vec<crate::account::Character>> = HashMap::new();
+    for (id, account_id, name, genome, appearance) in rows {
+        characters
+            .entry(account_id)
+            .or_default()
+            .push(crate::account::Character {
+                id,
+                name,
+                genome: serde_json::from_str(&genome)?,
+                appearance: serde_json::from_str(&appearance)?,
+            });
+    }
+    Ok(accounts
+        .into_iter()
+        .map(|(id, username)| crate::account::Account {
+            characters: characters.remove(&id).unwrap_or_default(),
+            id,
+            username,
+        })
+        .collect())
+}
+
+/// Append chat messages to the chat history.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+/// * `messages` - Delivered messages, oldest first
+pub async fn save_chat_messages(
+    pool: &PgPool,
+    messages: &[crate::chat::ChatMessage],
+) -> anyhow::Result<()> {
+    use crate::chat::ChatChannel;
+    let mut tx = pool.begin().await?;
+    for message in messages {
+        let (kind, target) = match &message.channel {
+            ChatChannel::Say => ("say", None),
+            ChatChannel::Whisper { to } => ("whisper", Some(to)),
+            ChatChannel::Group { name } => ("group", Some(name)),
         };
-        upsert_entity(pool, &data).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO chat_messages (id, sender_id, channel, target, text, game_minute)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(Uuid::parse_str(&message.id)?)
+        .bind(&message.from)
+        .bind(kind)
+        .bind(target)
+        .bind(&message.text)
+        .bind(message.game_minute)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Changes to the economy waiting to be written to the database.
+pub struct EconomyChanges {
+    /// Every account balance (player ID, cents)
+    pub balances: Vec<(String, i64)>,
+    /// Orders placed since the last save
+    pub orders: Vec<crate::economy::Order>,
+    /// Orders delivered since the last save (order ID, game minute)
+    pub deliveries: Vec<(String, i64)>,
+    /// Transactions recorded since the last save
+    pub transactions: Vec<crate::economy::Transaction>,
+}
+
+/// Save account balances, new orders, deliveries and transactions in one
+/// database transaction. Transactions are only ever appended.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+/// * `changes` - Changes since the last save
+pub async fn save_economy(pool: &PgPool, changes: &EconomyChanges) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    // Orders first: transactions reference them
+    for order in &changes.orders {
+        sqlx::query(
+            r#"
+            INSERT INTO orders (id, player_id, lines, total_cents, placed_at_minute, deliver_at_minute)
+            VALUES ($1, $2, $3::jsonb, $4, $5, $6)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(Uuid::parse_str(&order.id)?)
+        .bind(&order.player_id)
+        .bind(serde_json::to_string(&order.lines)?)
+        .bind(order.total)
+        .bind(order.placed_at)
+        .bind(order.deliver_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for transaction in &changes.transactions {
+        let order_id = transaction
+            .order_id
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()?;
+        sqlx::query(
+            r#"
+            INSERT INTO transactions (id, player_id, amount_cents, balance_after_cents, kind, description, order_id, game_minute)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(Uuid::parse_str(&transaction.id)?)
+        .bind(&transaction.player_id)
+        .bind(transaction.amount)
+        .bind(transaction.balance_after)
+        .bind(transaction.kind.as_str())
+        .bind(&transaction.description)
+        .bind(order_id)
+        .bind(transaction.game_minute)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for (order_id, minute) in &changes.deliveries {
+        sqlx::query("UPDATE orders SET delivered_at_minute = $2 WHERE id = $1")
+            .bind(Uuid::parse_str(order_id)?)
+            .bind(minute)
+            .execute(&mut *tx)
+            .await?;
     }
+    for (player_id, balance) in &changes.balances {
+        sqlx::query(
+            r#"
+            INSERT INTO accounts (player_id, balance_cents)
+            VALUES ($1, $2)
+            ON CONFLICT (player_id) DO UPDATE SET balance_cents = EXCLUDED.balance_cents
+            "#,
+        )
+        .bind(player_id)
+        .bind(balance)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
     Ok(())
 }
+
+/// Load account balances and orders not yet delivered.
+///
+/// # Returns
+/// (player ID, balance in cents) pairs and pending orders
+pub async fn load_economy(
+    pool: &PgPool,
+) -> anyhow::Result<(Vec<(String, i64)>, Vec<crate::economy::Order>)> {
+    let balances: Vec<(String, i64)> =
+        sqlx::query_as("SELECT player_id, balance_cents FROM accounts")
+            .fetch_all(pool)
+            .await?;
+    let rows: Vec<(Uuid, String, String, i64, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT id, player_id, lines::text, total_cents, placed_at_minute, deliver_at_minute
+        FROM orders
+        WHERE delivered_at_minute IS NULL
+        ORDER BY deliver_at_minute
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut orders = Vec::new();
+    for (id, player_id, lines, total, placed_at, deliver_at) in rows {
+        orders.push(crate::economy::Order {
+            id: id.to_string(),
+            player_id,
+            lines: serde_json::from_str(&lines)?,
+            total,
+            placed_at,
+            deliver_at,
+        });
+    }
+    Ok((balances, orders))
+}
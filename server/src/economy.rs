@@ -0,0 +1,427 @@
+//! Household economy module.
+//!
+//! Every character has an account in a currency ledger (amounts in cents). The
+//! shop sells catalogue object types that have a price; a purchase is paid at once
+//! and delivered to the level's delivery point after a game-time delay, where the
+//! goods appear as entities owned by the buyer. Every change to a balance is a
+//! transaction, appended to a log that is persisted and never rewritten.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::catalogue::Catalogue;
+
+/// Balance of a newly opened account (cents).
+pub const STARTING_BALANCE: i64 = 200_000; // 2000.00
+/// Game minutes between a purchase and its delivery.
+pub const DELIVERY_MINUTES: i64 = 2 * 60;
+/// Most objects of one type in a single order.
+const MAX_ORDER_COUNT: u32 = 20;
+
+/// Why a balance changed.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+    /// Money given to a new account
+    Grant,
+    /// Payment for a shop order
+    Purchase,
+    /// Money returned for an order that could not be delivered
+    Refund,
+}
+
+impl TransactionKind {
+    /// Name stored in the transaction log.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionKind::Grant => "grant",
+            TransactionKind::Purchase => "purchase",
+            TransactionKind::Refund => "refund",
+        }
+    }
+}
+
+/// One change to a character's balance.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Transaction {
+    /// Unique ID (UUID)
+    pub id: String,
+    pub player_id: String,
+    /// Signed amount in cents (negative for payments)
+    pub amount: i64,
+    /// Balance after the transaction (cents)
+    pub balance_after: i64,
+    pub kind: TransactionKind,
+    /// What the money was for
+    pub description: String,
+    /// Order the transaction belongs to, if any
+    pub order_id: Option<String>,
+    /// Game minute of the transaction
+    pub game_minute: i64,
+}
+
+/// Balances of every account and the transactions not yet written to the database.
+#[derive(Clone, Debug, Default)]
+pub struct Ledger {
+    balances: HashMap<String, i64>,
+    unsaved: Vec<Transaction>,
+}
+
+impl Ledger {
+    /// Balance of an account (cents), or None if it has not been opened.
+    pub fn balance(&self, player_id: &str) -> Option<i64> {
+        self.balances.get(player_id).copied()
+    }
+
+    /// Open an account with the starting balance unless it already exists.
+    pub fn open(&mut self, player_id: &str, now: i64) {
+        if !self.balances.contains_key(player_id) {
+            self.balances.insert(player_id.to_string(), 0);
+            let _ = self.record(
+                player_id,
+                STARTING_BALANCE,
+                TransactionKind::Grant,
+                "starting money".to_string(),
+                None,
+                now,
+            );
+        }
+    }
+
+    /// Pay an amount (cents) out of an account, refusing to overdraw it.
+    pub fn pay(
+        &mut self,
+        player_id: &str,
+        amount: i64,
+        description: String,
+        order_id: Option<String>,
+        now: i64,
+    ) -> Result<Transaction, String> {
+        let balance = self
+            .balance(player_id)
+            .ok_or_else(|| "no account".to_string())?;
+        if amount > balance {
+            return Err(format!(
+                "not enough money: costs {}, balance is {}",
+                format_cents(amount),
+                format_cents(balance)
+            ));
+        }
+        self.record(
+            player_id,
+            -amount,
+            TransactionKind::Purchase,
+            description,
+            order_id,
+            now,
+        )
+        .ok_or_else(|| "no account".to_string())
+    }
+
+    /// Give money back for an order; None if the account is not kept here (closed
+    /// when its character moved elsewhere).
+    pub fn refund(
+        &mut self,
+        player_id: &str,
+        amount: i64,
+        description: String,
+        order_id: Option<String>,
+        now: i64,
+    ) -> Option<Transaction> {
+        self.record(
+            player_id,
+            amount,
+            TransactionKind::Refund,
+            description,
+            order_id,
+            now,
+        )
+    }
+
+    /// Change the balance of an open account and log the transaction; never
+    /// opens one.
+    fn record(
+        &mut self,
+        player_id: &str,
+        amount: i64,
+        kind: TransactionKind,
+        description: String,
+        order_id: Option<String>,
+        now: i64,
+    ) -> Option<Transaction> {
+        let balance = self.balances.get_mut(player_id)?;
+        *balance += amount;
+        let transaction = Transaction {
+            id: uuid::Uuid::new_v4().to_string(),
+            player_id: player_id.to_string(),
+            amount,
+            balance_after: *balance,
+            kind,
+            description,
+            order_id,
+            game_minute: now,
+        };
+        self.unsaved.push(transaction.clone());
+        Some(transaction)
+    }
+
+    /// Set balances loaded from the database.
+    pub fn restore(&mut self, balances: Vec<(String, i64)>) {
+        self.balances.extend(balances);
+    }
+
+    /// Close an account whose character moves elsewhere, returning its balance.
+    pub fn close(&mut self, player_id: &str) -> Option<i64> {
+        self.balances.remove(player_id)
+    }
+
+    /// Take the transactions recorded since the last call, for persisting.
+    pub fn take_unsaved(&mut self) -> Vec<Transaction> {
+        std::mem::take(&mut self.unsaved)
+    }
+
+    /// Put back transactions that failed to persist, ahead of newer ones.
+    pub fn return_unsaved(&mut self, mut transactions: Vec<Transaction>) {
+        transactions.append(&mut self.unsaved);
+        self.unsaved = transactions;
+    }
+
+    /// Every account balance.
+    pub fn balances(&self) -> Vec<(String, i64)> {
+        self.balances
+            .iter()
+            .map(|(player_id, balance)| (player_id.clone(), *balance))
+            .collect()
+    }
+}
+
+/// Object type on sale.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ShopItem {
+    pub object_type: String,
+    /// Price in cents
+    pub price: i64,
+}
+
+/// Number of objects of one type in an order.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct OrderLine {
+    pub object_type: String,
+    pub count: u32,
+    /// Price paid per object (cents), set by the shop
+    #[serde(default)]
+    pub unit_price: i64,
+}
+
+/// Paid purchase waiting for (or done with) delivery.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Order {
+    /// Unique ID (UUID)
+    pub id: String,
+    pub player_id: String,
+    pub lines: Vec<OrderLine>,
+    /// Total paid (cents)
+    pub total: i64,
+    /// Game minute the order was placed
+    pub placed_at: i64,
+    /// Game minute the goods arrive
+    pub deliver_at: i64,
+}
+
+/// Goods of an order that arrived.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Delivery {
+    pub order: Order,
+    /// Entities spawned at the delivery point
+    pub entity_ids: Vec<String>,
+}
+
+/// Orders waiting for delivery, and order changes not yet written to the database.
+#[derive(Clone, Debug, Default)]
+pub struct OrderBook {
+    pending: Vec<Order>,
+    unsaved_orders: Vec<Order>,
+    /// (order ID, game minute delivered)
+    unsaved_deliveries: Vec<(String, i64)>,
+}
+
+impl OrderBook {
+    /// Add a paid order.
+    pub fn place(&mut self, order: Order) {
+        self.unsaved_orders.push(order.clone());
+        self.pending.push(order);
+    }
+
+    /// Orders waiting for delivery.
+    pub fn pending(&self) -> &[Order] {
+        &self.pending
+    }
+
+    /// Remove and return the orders due at a game minute.
+    pub fn take_due(&mut self, now: i64) -> Vec<Order> {
+        let (due, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|order| order.deliver_at <= now);
+        self.pending = pending;
+        due
+    }
+
+    /// Record that an order has been delivered.
+    pub fn mark_delivered(&mut self, order_id: &str, now: i64) {
+        self.unsaved_deliveries.push((order_id.to_string(), now));
+    }
+
+    /// Put back pending orders loaded from the database.
+    pub fn restore(&mut self, pending: Vec<Order>) {
+        self.pending.extend(pending);
+    }
+
+    /// Add pending orders brought along by a character arriving from another zone.
+    ///
+    /// They are saved again from here: the zone they come from may not have
+    /// written them yet, and their delivery or refund refers to them.
+    pub fn take_over(&mut self, orders: Vec<Order>) {
+        self.unsaved_orders.extend(orders.iter().cloned());
+        self.pending.extend(orders);
+    }
+
+    /// Remove and return the orders a character is waiting for.
+    pub fn take_of(&mut self, player_id: &str) -> Vec<Order> {
+        let (theirs, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|order| order.player_id == player_id);
+        self.pending = pending;
+        theirs
+    }
+
+    /// Take new orders and deliveries since the last call, for persisting.
+    pub fn take_unsaved(&mut self) -> (Vec<Order>, Vec<(String, i64)>) {
+        (
+            std::mem::take(&mut self.unsaved_orders),
+            std::mem::take(&mut self.unsaved_deliveries),
+        )
+    }
+
+    /// Put back changes that failed to persist, ahead of newer ones.
+    pub fn return_unsaved(&mut self, mut orders: Vec<Order>, mut deliveries: Vec<(String, i64)>) {
+        orders.append(&mut self.unsaved_orders);
+        deliveries.append(&mut self.unsaved_deliveries);
+        self.unsaved_orders = orders;
+        self.unsaved_deliveries = deliveries;
+    }
+}
+
+/// Everything the shop sells, sorted by name.
+pub fn shop_items(catalogue: &Catalogue) -> Vec<ShopItem> {
+    catalogue
+        .types()
+        .into_iter()
+        .filter_map(|t| {
+            t.price.map(|price| ShopItem {
+                object_type: t.name.clone(),
+                price,
+            })
+        })
+        .collect()
+}
+
+/// Check an order and work out its total price (cents).
+pub fn quote(catalogue: &Catalogue, lines: &[OrderLine]) -> Result<i64, String> {
+    if lines.is_empty() {
+        return Err("empty order".to_string());
+    }
+    let mut total = 0;
+    for line in lines {
+        let price = catalogue
+            .get(&line.object_type)
+            .and_then(|t| t.price)
+            .ok_or_else(|| format!("{} is not for sale", line.object_type))?;
+        if line.count == 0 || line.count > MAX_ORDER_COUNT {
+            return Err(format!(
+                "{} {}: order between 1 and {MAX_ORDER_COUNT}",
+                line.count, line.object_type
+            ));
+        }
+        total += price * line.count as i64;
+    }
+    Ok(total)
+}
+
+/// Amount in cents as money (e.g. "12.50").
+pub fn format_cents(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{sign}{}.{:02}", cents.abs() / 100, cents.abs() % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn ledger_refuses_to_overdraw() {
+        let mut ledger = Ledger::default();
+        ledger.open("p1", 0);
+        ledger.open("p1", 5);
+        assert_eq!(ledger.balance("p1"), Some(STARTING_BALANCE));
+
+        let payment = ledger
+            .pay("p1", 15_000, "sofa".to_string(), None, 10)
+            .unwrap();
+        assert_eq!(payment.amount, -15_000);
+        assert_eq!(payment.balance_after, STARTING_BALANCE - 15_000);
+        assert!(ledger
+            .pay("p1", STARTING_BALANCE, "house".to_string(), None, 11)
+            .is_err());
+        assert!(ledger.pay("p2", 1, "gum".to_string(), None, 11).is_err());
+
+        let log = ledger.take_unsaved();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].kind, TransactionKind::Grant);
+        assert!(ledger.take_unsaved().is_empty());
+
+        // A closed account is not opened again by a late refund
+        ledger.close("p1");
+        assert!(ledger
+            .refund("p1", 15_000, "sofa".to_string(), None, 12)
+            .is_none());
+        assert_eq!(ledger.balance("p1"), None);
+    }
+
+    #[test]
+    fn quotes_only_what_is_for_sale() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/objects");
+        let catalogue = Catalogue::load_dir(&dir).expect("object files should parse");
+        let line = |object_type: &str, count| OrderLine {
+            object_type: object_type.to_string(),
+            count,
+            unit_price: 0,
+        };
+        let plate = catalogue.get("plate").and_then(|t| t.price).unwrap();
+        assert_eq!(quote(&catalogue, &[line("plate", 3)]), Ok(3 * plate));
+        assert!(quote(&catalogue, &[line("human", 1)]).is_err());
+        assert!(quote(&catalogue, &[line("plate", 0)]).is_err());
+        assert!(quote(&catalogue, &[]).is_err());
+        assert_eq!(format_cents(-1205), "-12.05");
+    }
+
+    #[test]
+    fn orders_brought_along_are_saved_again() {
+        let order = Order {
+            id: uuid::Uuid::new_v4().to_string(),
+            player_id: "p1".to_string(),
+            lines: Vec::new(),
+            total: 4_500,
+            placed_at: 0,
+            deliver_at: DELIVERY_MINUTES,
+        };
+        let mut book = OrderBook::default();
+        book.restore(vec![order.clone()]);
+        assert!(book.take_unsaved().0.is_empty());
+
+        let mut book = OrderBook::default();
+        book.take_over(vec![order.clone()]);
+        assert_eq!(book.pending(), std::slice::from_ref(&order));
+        assert_eq!(book.take_unsaved().0, vec![order]);
+    }
+}
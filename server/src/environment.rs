@@ -0,0 +1,329 @@
+//! Environment module: day/night and weather.
+//!
+//! The sun's position follows the game calendar: the year starts at the spring
+//! equinox, days are longer in summer, and the world sits at a mid latitude.
+//! Daylight fades in and out over twilight around sunrise and sunset.
+//!
+//! Weather is simulated minute by minute. Temperature follows the season and the
+//! time of day plus a slowly wandering offset for warm and cold spells; humidity
+//! wanders around a seasonal level and brings clouds and then rain (snow below
+//! freezing); wind speed and direction drift, stronger in winter.
+//!
+//! Characters outside the level's sheltered areas get cold and wet. Cold makes
+//! them hungry and tired and can give them a cold; being wet makes them chill
+//! faster. Wind pushes balls around.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+use crate::calendar::{GameTime, DAYS_PER_YEAR, MINUTES_PER_DAY, MINUTES_PER_HOUR};
+use crate::needs::NeedRates;
+
+/// Latitude of the world (degrees north).
+const LATITUDE_DEG: f32 = 45.0;
+/// Tilt of the world's axis (degrees), the sun's declination at the solstices.
+const AXIAL_TILT_DEG: f32 = 23.44;
+/// Sun elevation (degrees) at which twilight ends and full night begins.
+const TWILIGHT_DEG: f32 = -6.0;
+/// Sun elevation (degrees) from which it is full daylight.
+const FULL_DAYLIGHT_DEG: f32 = 6.0;
+/// Mean yearly temperature (°C).
+const MEAN_TEMPERATURE: f32 = 12.0;
+/// Difference between the warmest and the mean seasonal temperature (°C).
+const SEASONAL_SWING: f32 = 10.0;
+/// Difference between the warmest and the mean temperature of a clear day (°C).
+const DAILY_SWING: f32 = 5.0;
+/// Share of the wandering weather state that returns to normal each game minute.
+const REVERSION_PER_MINUTE: f32 = 0.002;
+/// Humidity above which it rains, and at which rain is heaviest.
+const RAIN_HUMIDITY: (f32, f32) = (0.7, 1.0);
+/// Game minutes between environment broadcasts.
+pub const BROADCAST_MINUTES: i64 = 5;
+/// Temperature (°C) below which characters outside start to chill.
+const CHILLING_TEMPERATURE: f32 = 10.0;
+/// Cold from which a character may catch a cold.
+pub const CHILLED: f32 = 60.0;
+
+/// Season of the game year, 90 days each starting with spring.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub fn of(time: GameTime) -> Self {
+        match time.day_of_year() * 4 / DAYS_PER_YEAR {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    /// Humidity the weather returns to.
+    fn humidity(&self) -> f32 {
+        match self {
+            Season::Spring | Season::Autumn => 0.6,
+            Season::Summer => 0.45,
+            Season::Winter => 0.55,
+        }
+    }
+
+    /// Wind speed (m/s) the weather returns to.
+    fn wind_speed(&self) -> f32 {
+        match self {
+            Season::Spring | Season::Autumn => 4.5,
+            Season::Summer => 3.0,
+            Season::Winter => 6.0,
+        }
+    }
+}
+
+/// Where the sun is and how light it is.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Sun {
+    /// Angle above the horizon (degrees, negative at night)
+    pub elevation_deg: f32,
+    /// Compass direction (degrees clockwise from north, +Z)
+    pub azimuth_deg: f32,
+    /// 0 (night) to 1 (full daylight)
+    pub daylight: f32,
+}
+
+impl Sun {
+    /// Sun position at a game time.
+    pub fn at(time: GameTime) -> Self {
+        let year_angle = 2.0 * PI * time.day_of_year() as f32 / DAYS_PER_YEAR as f32;
+        let declination = (AXIAL_TILT_DEG * year_angle.sin()).to_radians();
+        let hours = time.minute_of_day() as f32 / MINUTES_PER_HOUR as f32;
+        let hour_angle = (15.0 * (hours - 12.0)).to_radians();
+        let latitude = LATITUDE_DEG.to_radians();
+
+        let sin_elevation = latitude.sin() * declination.sin()
+            + latitude.cos() * declination.cos() * hour_angle.cos();
+        let elevation = sin_elevation.clamp(-1.0, 1.0).asin();
+        let cos_azimuth = (declination.sin() - elevation.sin() * latitude.sin())
+            / (elevation.cos() * latitude.cos()).max(f32::EPSILON);
+        let azimuth = cos_azimuth.clamp(-1.0, 1.0).acos().to_degrees();
+        // Morning sun is in the east, afternoon sun in the west
+        let azimuth_deg = if hour_angle > 0.0 {
+            360.0 - azimuth
+        } else {
+            azimuth
+        };
+
+        let elevation_deg = elevation.to_degrees();
+        let daylight =
+            ((elevation_deg - TWILIGHT_DEG) / (FULL_DAYLIGHT_DEG - TWILIGHT_DEG)).clamp(0.0, 1.0);
+        Self {
+            elevation_deg,
+            azimuth_deg,
+            daylight,
+        }
+    }
+}
+
+/// Weather at one moment.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Weather {
+    /// Air temperature (°C)
+    pub temperature_c: f32,
+    /// 0 (clear) to 1 (overcast)
+    pub cloud_cover: f32,
+    /// 0 (dry) to 1 (downpour)
+    pub rain: f32,
+    /// Whether the rain falls as snow
+    pub snow: bool,
+    /// Wind velocity in the X/Z plane (m/s)
+    pub wind: [f32; 2],
+}
+
+/// State of the weather simulation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeatherSim {
+    /// Warm or cold spell on top of the normal temperature (°C)
+    temperature_offset: f32,
+    /// 0 (dry air) to 1 (saturated)
+    humidity: f32,
+    wind_speed: f32,
+    /// Direction the wind blows towards (radians from +Z)
+    wind_direction: f32,
+    current: Weather,
+}
+
+impl WeatherSim {
+    /// Normal weather for the season.
+    pub fn new(time: GameTime) -> Self {
+        let season = Season::of(time);
+        let mut sim = Self {
+            temperature_offset: 0.0,
+            humidity: season.humidity(),
+            wind_speed: season.wind_speed(),
+            wind_direction: 0.0,
+            current: Weather {
+                temperature_c: MEAN_TEMPERATURE,
+                cloud_cover: 0.0,
+                rain: 0.0,
+                snow: false,
+                wind: [0.0, 0.0],
+            },
+        };
+        sim.update(time);
+        sim
+    }
+
+    pub fn current(&self) -> &Weather {
+        &self.current
+    }
+
+    /// Advance the weather by one game minute ending at `time`.
+    pub fn advance(&mut self, time: GameTime, rng: &mut impl Rng) {
+        let season = Season::of(time);
+        let mut wander = |value: f32, normal: f32, step: f32| {
+            value + (normal - value) * REVERSION_PER_MINUTE + rng.gen_range(-step..=step)
+        };
+        self.temperature_offset = wander(self.temperature_offset, 0.0, 0.15);
+        self.humidity = wander(self.humidity, season.humidity(), 0.01).clamp(0.0, 1.0);
+        self.wind_speed = wander(self.wind_speed, season.wind_speed(), 0.1).max(0.0);
+        self.wind_direction = wander(self.wind_direction, self.wind_direction, 0.02);
+        self.update(time);
+    }
+
+    /// Work out the weather from the simulation state.
+    fn update(&mut self, time: GameTime) {
+        let year_angle = 2.0 * PI * time.day_of_year() as f32 / DAYS_PER_YEAR as f32;
+        // Warmest a month and a half after the summer solstice
+        let seasonal = SEASONAL_SWING * (year_angle - PI / 4.0).sin();
+        let cloud_cover = ((self.humidity - 0.3) / 0.5).clamp(0.0, 1.0);
+        // Warmest at 15:00; clouds flatten the daily swing
+        let day_angle = 2.0 * PI * (time.minute_of_day() as f32 / MINUTES_PER_DAY as f32 - 0.375);
+        let daily = DAILY_SWING * (1.0 - 0.6 * cloud_cover) * day_angle.sin();
+        let temperature_c = MEAN_TEMPERATURE + seasonal + daily + self.temperature_offset;
+        let (wet, soaked) = RAIN_HUMIDITY;
+        let rain = ((self.humidity - wet) / (soaked - wet)).clamp(0.0, 1.0);
+        self.current = Weather {
+            temperature_c,
+            cloud_cover,
+            rain,
+            snow: rain > 0.0 && temperature_c <= 0.0,
+            wind: [
+                self.wind_speed * self.wind_direction.sin(),
+                self.wind_speed * self.wind_direction.cos(),
+            ],
+        };
+    }
+}
+
+/// Time of day, season and weather, as sent to clients.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EnvironmentState {
+    /// Game minute the state applies to
+    pub game_minute: i64,
+    pub season: Season,
+    pub sun: Sun,
+    pub weather: Weather,
+}
+
+/// How cold and wet the weather has made a character.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Exposure {
+    /// 0 (comfortable) to 100 (freezing)
+    pub cold: f32,
+    /// 0 (dry) to 100 (soaked)
+    pub wetness: f32,
+}
+
+impl Exposure {
+    /// Advance by a number of game minutes in some weather, sheltered or not.
+    pub fn tick(&mut self, weather: &Weather, sheltered: bool, minutes: f32) {
+        if sheltered {
+            self.cold -= 1.0 * minutes;
+            self.wetness -= 1.0 * minutes;
+        } else {
+            self.wetness += (weather.rain * 2.0 - 0.2) * minutes;
+            let chill = (CHILLING_TEMPERATURE - weather.temperature_c) / 10.0;
+            let wind = 1.0 + (weather.wind[0].hypot(weather.wind[1]) / 10.0);
+            let wet = 1.0 + self.wetness / 100.0;
+            self.cold += if chill > 0.0 {
+                chill * wind * wet * minutes
+            } else {
+                -0.5 * minutes
+            };
+        }
+        self.cold = self.cold.clamp(0.0, 100.0);
+        self.wetness = self.wetness.clamp(0.0, 100.0);
+    }
+
+    /// Per-game-minute effect on needs: the cold burns energy and tires.
+    pub fn need_effects(&self) -> NeedRates {
+        let cold = self.cold / 100.0;
+        NeedRates {
+            hunger: 0.1 * cold,
+            exhaustion: 0.1 * cold,
+            ..NeedRates::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn at(day: i64, hour: i64) -> GameTime {
+        GameTime::from_minutes(day * MINUTES_PER_DAY + hour * MINUTES_PER_HOUR)
+    }
+
+    #[test]
+    fn the_sun_follows_the_day_and_the_season() {
+        let summer_noon = Sun::at(at(90, 12));
+        let winter_noon = Sun::at(at(270, 12));
+        assert!((summer_noon.elevation_deg - 68.44).abs() < 0.1);
+        assert!(winter_noon.elevation_deg < summer_noon.elevation_deg);
+        assert_eq!(summer_noon.daylight, 1.0);
+        assert_eq!(Sun::at(at(90, 0)).daylight, 0.0);
+        // Morning sun in the east, evening sun in the west
+        assert!(Sun::at(at(0, 8)).azimuth_deg < 180.0);
+        assert!(Sun::at(at(0, 16)).azimuth_deg > 180.0);
+    }
+
+    #[test]
+    fn weather_follows_the_seasons() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut sim = WeatherSim::new(at(0, 0));
+        let mut mean = |sim: &mut WeatherSim, season: i64| {
+            let start = season * 90;
+            let mut total = 0.0;
+            for minute in 0..90 * MINUTES_PER_DAY {
+                let time = GameTime::from_minutes(start * MINUTES_PER_DAY + minute);
+                sim.advance(time, &mut rng);
+                let weather = sim.current();
+                assert!((0.0..=1.0).contains(&weather.rain));
+                total += weather.temperature_c;
+            }
+            total / (90 * MINUTES_PER_DAY) as f32
+        };
+        let summer = mean(&mut sim, 1);
+        let winter = mean(&mut sim, 3);
+        assert!(summer > winter + 10.0, "summer {summer} winter {winter}");
+
+        let mut exposure = Exposure::default();
+        let freezing_rain = Weather {
+            temperature_c: 2.0,
+            cloud_cover: 1.0,
+            rain: 1.0,
+            snow: false,
+            wind: [6.0, 0.0],
+        };
+        exposure.tick(&freezing_rain, false, 30.0);
+        assert!(exposure.cold > 30.0 && exposure.wetness > 30.0);
+        exposure.tick(&freezing_rain, true, 60.0);
+        assert_eq!(exposure, Exposure::default());
+    }
+}
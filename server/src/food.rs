@@ -0,0 +1,180 @@
+//! Food, cooking and spoilage module.
+//!
+//! Food is any catalogue object type with a `food` entry: how much hunger (and
+//! thirst) one serving satisfies and how many game days it keeps. Perishable food
+//! declares a `freshness` property that falls from 1.0 to 0.0 over its shelf life,
+//! four times slower in a cold container such as a fridge. Spoiled food still fills
+//! a stomach, but only half as well.
+//!
+//! Eating consumes one serving and lasts as long as it takes the Eating activity
+//! to relieve the serving's nutrition, so hunger drops by what was actually eaten.
+//!
+//! Recipes (`data/recipes/*.json`) turn ingredients from the cook's inventory into
+//! meals: they need an appliance (by catalogue tag) and take game time, and the
+//! meal only comes out if the Cooking activity runs to the end.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::catalogue::{Catalogue, FoodDef};
+use crate::game::Activity;
+use crate::level::data_dir;
+
+/// Property holding how fresh perishable food is (1.0 fresh, 0.0 spoiled).
+pub const FRESHNESS: &str = "freshness";
+/// Catalogue tag of containers that keep food cold.
+pub const COLD_TAG: &str = "cold";
+/// Game minutes between spoilage updates.
+pub const SPOILAGE_INTERVAL_MINUTES: i64 = 60;
+/// How fast food spoils in a cold container, relative to room temperature.
+const COLD_SPOILAGE_FACTOR: f64 = 0.25;
+/// Share of its nutrition that spoiled food still gives.
+const SPOILED_NUTRITION_FACTOR: f32 = 0.5;
+/// Shortest meal (game minutes).
+const MIN_EATING_MINUTES: i64 = 5;
+
+/// Freshness after some game minutes of spoiling.
+pub fn spoil(freshness: f64, food: &FoodDef, minutes: i64, cold: bool) -> f64 {
+    let Some(days) = food.shelf_life_days.filter(|days| *days > 0.0) else {
+        return freshness;
+    };
+    let factor = if cold { COLD_SPOILAGE_FACTOR } else { 1.0 };
+    let lost = minutes as f64 * factor / (days as f64 * 24.0 * 60.0);
+    (freshness - lost).max(0.0)
+}
+
+/// Whether food of this freshness has gone off.
+pub fn is_spoiled(freshness: f64) -> bool {
+    freshness <= 0.0
+}
+
+/// Hunger and thirst one serving relieves at a given freshness.
+pub fn serving(food: &FoodDef, freshness: f64) -> (f32, f32) {
+    if is_spoiled(freshness) {
+        (
+            food.nutrition * SPOILED_NUTRITION_FACTOR,
+            food.hydration * SPOILED_NUTRITION_FACTOR,
+        )
+    } else {
+        (food.nutrition, food.hydration)
+    }
+}
+
+/// Game minutes it takes the Eating activity to relieve this much hunger.
+pub fn eating_minutes(nutrition: f32) -> i64 {
+    let rate = -Activity::Eating.need_effects().hunger;
+    ((nutrition / rate).round() as i64).max(MIN_EATING_MINUTES)
+}
+
+/// A number of items of one type in a recipe.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RecipeItem {
+    /// Catalogue type name
+    pub item: String,
+    pub count: u32,
+}
+
+/// How to cook a meal.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Recipe {
+    /// Unique name, e.g. "omelette"
+    pub name: String,
+    /// Items taken from the cook's inventory
+    pub ingredients: Vec<RecipeItem>,
+    /// Catalogue tag of the appliance to cook on (e.g. "stove")
+    pub appliance: String,
+    /// Cooking time in game minutes
+    pub minutes: i64,
+    /// What comes out
+    pub output: RecipeItem,
+}
+
+impl Recipe {
+    /// Check that the recipe only uses items the catalogue knows.
+    pub fn check(&self, catalogue: &Catalogue) -> Result<(), String> {
+        if self.ingredients.is_empty() || self.minutes <= 0 || self.output.count == 0 {
+            return Err(format!("{}: needs ingredients, time and output", self.name));
+        }
+        for item in self.ingredients.iter().chain([&self.output]) {
+            if !catalogue.get(&item.item).is_some_and(|t| t.is_item()) {
+                return Err(format!("{}: {} is not an item", self.name, item.item));
+            }
+        }
+        let food = catalogue
+            .get(&self.output.item)
+            .and_then(|t| t.food.as_ref());
+        if food.is_none() {
+            return Err(format!("{}: {} is not food", self.name, self.output.item));
+        }
+        Ok(())
+    }
+}
+
+/// Load every `*.json` file (an array of recipes) in a directory.
+pub fn load_dir(dir: &Path) -> anyhow::Result<Vec<Recipe>> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut recipes = Vec::new();
+    for path in paths {
+        let text = std::fs::read_to_string(&path)?;
+        let parsed: Vec<Recipe> =
+            serde_json::from_str(&text).map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+        recipes.extend(parsed);
+    }
+    Ok(recipes)
+}
+
+/// Load recipes from `RECIPE_DIR` or `data/recipes/`.
+///
+/// Nothing can be cooked if the directory is missing or invalid.
+pub fn load_configured() -> Vec<Recipe> {
+    let dir = std::env::var("RECIPE_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|_| data_dir().join("recipes"));
+    match load_dir(&dir) {
+        Ok(recipes) => {
+            tracing::info!("Loaded {} recipes from {}", recipes.len(), dir.display());
+            recipes
+        }
+        Err(e) => {
+            tracing::warn!("Failed to load recipes from {}: {e}", dir.display());
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recipes_use_known_food() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data");
+        let catalogue = Catalogue::load_dir(&root.join("objects")).expect("objects should parse");
+        let recipes = load_dir(&root.join("recipes")).expect("recipes should parse");
+        assert!(!recipes.is_empty());
+        for recipe in &recipes {
+            assert_eq!(recipe.check(&catalogue), Ok(()));
+        }
+    }
+
+    #[test]
+    fn food_spoils_slower_in_the_cold() {
+        let milk = FoodDef {
+            nutrition: 8.0,
+            hydration: 20.0,
+            shelf_life_days: Some(2.0),
+        };
+        let day = 24 * 60;
+        assert!((spoil(1.0, &milk, day, false) - 0.5).abs() < 1e-9);
+        assert!((spoil(1.0, &milk, day, true) - 0.875).abs() < 1e-9);
+        assert!(is_spoiled(spoil(1.0, &milk, 3 * day, false)));
+        assert_eq!(serving(&milk, 0.0), (4.0, 10.0));
+        assert_eq!(eating_minutes(60.0), 20);
+        assert_eq!(eating_minutes(1.0), MIN_EATING_MINUTES);
+    }
+}
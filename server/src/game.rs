@@ -2,10 +2,33 @@
 //!
 //! Handles player and entity state, game time, and physics integration.
 
-use crate::physics::PhysicsWorld;
+use crate::account::{Account, Accounts, Character, CharacterSummary};
+use crate::activity::{
+    ActivityEvent, ActivityOutcome, TimedActivity, PROGRESS_INTERVAL_MINUTES, REACH_DISTANCE,
+};
+use crate::calendar::{GameTime, MINUTES_PER_DAY};
+use crate::catalogue::Catalogue;
+use crate::chat::{self, Chat, ChatChannel, ChatMessage};
+use crate::economy::{self, Delivery, Ledger, Order, OrderBook, OrderLine, DELIVERY_MINUTES};
+use crate::environment::{self, EnvironmentState, Exposure, Season, Sun, WeatherSim};
+use crate::food::{self, Recipe};
+use crate::genetics::{Appearance, AppearanceChoice, Genome};
+use crate::health::{self, Health, IllnessKind};
+use crate::interaction::{self, SpecialVerb, Verb};
+use crate::inventory::{Inventory, ItemStack};
+use crate::level::Level;
+use crate::mood::{Decision, Mood, MoodEvent};
+use crate::nav::{NavGrid, Waypoint};
+use crate::needs::{NeedRates, Needs};
+use crate::npc::{self, NpcDef};
+use crate::physics::{PhysicsEvent, PhysicsWorld};
+use crate::properties::{self, Properties, PropertyChange, PropertyValue};
+use crate::relationship::{self, Relationship, Relationships, SocialAction};
+use crate::schedule::{ActiveSchedule, Choice, Context, Schedule};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Instant;
 
 /// Daily routine activities that characters can be engaged in.
 ///
@@ -45,6 +68,26 @@ pub enum Activity {
     Commuting,
 }
 
+impl Activity {
+    /// Every activity, in declaration order.
+    pub const ALL: [Activity; 14] = [
+        Activity::Idle,
+        Activity::Sleeping,
+        Activity::Eating,
+        Activity::Cooking,
+        Activity::Working,
+        Activity::Exercising,
+        Activity::Socializing,
+        Activity::Shopping,
+        Activity::Cleaning,
+        Activity::Bathing,
+        Activity::Reading,
+        Activity::WatchingTv,
+        Activity::Gaming,
+        Activity::Commuting,
+    ];
+}
+
 /// Represents a player in the game world.
 ///
 /// Contains player identity, position, orientation, movement state, and current activity.
@@ -64,13 +107,45 @@ pub struct Player {
     /// Current activity the player is engaged in
     #[serde(default)]
     pub activity: Activity,
+    /// Need meters (hunger, thirst, sleepiness...), simulated by the server
+    #[serde(default)]
+    pub needs: Needs,
+    /// Emotional state, driven by needs and events; gates player commands
+    #[serde(default)]
+    pub mood: Mood,
+    /// Timing and object of the current activity (None while idle)
+    #[serde(default)]
+    pub timed_activity: Option<TimedActivity>,
+    /// Whether the owner is disconnected (the character keeps living on its
+    /// schedule or the needs autopilot)
+    #[serde(default)]
+    pub offline: bool,
+    /// Whether this is a non-player character driven by the server
+    #[serde(default)]
+    pub npc: bool,
+    /// Whether the character was brought by a client without an account; it is
+    /// removed from the world some time after its owner disconnects
+    #[serde(default)]
+    pub guest: bool,
+    /// Age, illnesses and injuries
+    #[serde(default)]
+    pub health: Health,
+    /// Heritable makeup, deciding traits such as height and metabolism
+    #[serde(default)]
+    pub genome: Genome,
+    /// How clients render the character's avatar
+    #[serde(default)]
+    pub appearance: Appearance,
+    /// How cold and wet the weather has made the character
+    #[serde(default)]
+    pub exposure: Exposure,
 }
 
 /// 3D position in the game world.
 ///
 /// Units are in meters (1 unit = 1 m).
 /// Y-axis is vertical (height).
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Position {
     /// X coordinate (horizontal, east-west)
     pub x: f32,
@@ -81,29 +156,40 @@ pub struct Position {
 }
 
 /// Type of entity in the game world.
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "lowercase")]
-pub enum EntityType {
-    /// Human entity (player character)
-    Human,
-    /// Ball entity (physics object)
-    Ball,
-}
+///
+/// Names an entry in the object catalogue (e.g. "human", "ball", "bed_double").
+/// Serialized as the bare type name.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct EntityType(String);
 
 impl EntityType {
+    /// Create an entity type from a catalogue type name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    /// Human entity (player character)
+    pub fn human() -> Self {
+        Self::new("human")
+    }
+
     /// Get the string representation of the entity type.
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            EntityType::Human => "human",
-            EntityType::Ball => "ball",
-        }
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether this is the given catalogue type.
+    pub fn is(&self, name: &str) -> bool {
+        self.0 == name
     }
 }
 
 /// Represents a game entity (non-player object).
 ///
-/// Entities can be physics objects like balls, or other interactive objects.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Entities can be physics objects like balls, or other interactive objects;
+/// their physical properties come from the object catalogue.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Entity {
     /// Unique entity identifier
     pub id: String,
@@ -113,12 +199,18 @@ pub struct Entity {
     pub position: Position,
     /// Rotation in Euler angles (radians)
     pub rotation: Rotation,
+    /// Player who owns the object, if anyone
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Properties set on the entity, e.g. "on" for a stove; others have the type's default
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub properties: Properties,
 }
 
 /// 3D rotation represented as Euler angles.
 ///
 /// Angles are in radians.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Rotation {
     /// Rotation around X-axis (pitch)
     pub x: f32,
@@ -137,21 +229,219 @@ pub struct GameState {
     pub players: HashMap<String, Player>,
     /// Map of entity ID to Entity data
     pub entities: HashMap<String, Entity>,
+    /// Stored objects removed from the world since the last save, by entity ID
+    removed_entities: Vec<String>,
     /// Physics simulation world
     pub physics: PhysicsWorld,
+    /// Static level the physics world was built from
+    pub level: Level,
+    /// Walkable grid baked from the level's static geometry
+    pub nav: NavGrid,
+    /// Object types available for entities
+    pub catalogue: Catalogue,
+    /// Game minute up to which needs and moods have been simulated
+    pub needs_tick_minute: i64,
+    /// Activities a reluctant character has agreed to start later: player ID to
+    /// (activity, game minute at which it starts)
+    pub delayed_activities: HashMap<String, (Activity, i64)>,
+    /// Schedules controlling characters, by player ID
+    pub schedules: HashMap<String, ActiveSchedule>,
+    /// Activity start/progress/finish events waiting to be broadcast
+    pub activity_events: Vec<ActivityEvent>,
+    /// What happened to characters while their owners were offline, by player ID
+    pub away_journals: HashMap<String, AwayJournal>,
+    /// Non-player characters, by player ID
+    pub npcs: HashMap<String, NpcBrain>,
+    /// Remaining waypoints of the routes characters are walking, by player ID
+    pub routes: HashMap<String, Vec<Waypoint>>,
+    /// Activities characters are walking to an object for, by player ID
+    pub errands: HashMap<String, Errand>,
+    /// Items held by characters and containers, by player or entity ID
+    pub inventories: HashMap<String, Inventory>,
+    /// Money of every character
+    pub ledger: Ledger,
+    /// Shop orders waiting for delivery
+    pub orders: OrderBook,
+    /// Deliveries made, waiting to be broadcast
+    pub deliveries: Vec<Delivery>,
+    /// Entity property changes, waiting to be broadcast
+    property_changes: Vec<PropertyChange>,
+    /// Recipes by name
+    recipes: HashMap<String, Recipe>,
+    /// Meals being cooked (or about to be), by player ID
+    cooking: HashMap<String, Cooking>,
+    /// How characters feel about each other
+    pub relationships: Relationships,
+    /// Chat limits, group channels, block lists and unsaved history
+    pub chat: Chat,
+    /// Accounts and the characters they own
+    pub accounts: Accounts,
+    /// Weather over the level
+    pub weather: WeatherSim,
+    /// Whether the environment changed enough to be broadcast again
+    environment_due: bool,
+    /// When each player's last position update was applied, by player ID
+    last_moves: HashMap<String, Instant>,
+}
+
+/// Walking speed of server-driven characters (m/game-second).
+const WALK_SPEED: f32 = 1.4;
+/// Fastest a player can make their character run (m/game-second).
+const MAX_RUN_SPEED: f32 = 6.0;
+/// Game seconds that pass per real second (one 1 s physics step every 1/60 s).
+const GAME_SECONDS_PER_SECOND: f32 = 60.0;
+/// Longest time (game seconds) one position update is simulated over.
+const MAX_MOVE_SECONDS: f32 = 60.0;
+/// Game minutes after which a character that has not reached its object gives up.
+const ERRAND_GIVE_UP_MINUTES: i64 = 30;
+/// Distance (m) at which a waypoint counts as reached.
+const WAYPOINT_REACHED: f32 = 0.15;
+/// Distance (m) ahead of a character at which dropped items land.
+const DROP_DISTANCE: f32 = 0.6;
+/// Length (m) of a row of delivered goods.
+const DELIVERY_ROW_LENGTH: f32 = 6.0;
+/// Gap (m) between delivered goods.
+const DELIVERY_SPACING: f32 = 0.3;
+
+/// State of a non-player character's decision making.
+#[derive(Clone, Debug)]
+pub struct NpcBrain {
+    pub def: NpcDef,
+}
+
+/// A meal a character is cooking, or about to cook.
+#[derive(Clone, Debug)]
+pub struct Cooking {
+    /// Name of the recipe
+    pub recipe: String,
+    /// Whether the ingredients have been used (the Cooking activity started)
+    pub started: bool,
+    /// Whether a spoiled ingredient went in
+    pub spoiled: bool,
+}
+
+/// Where a serving of food comes from.
+#[derive(Clone, Debug, PartialEq)]
+enum FoodSource {
+    /// A food entity in the world
+    Entity(String),
+    /// A slot in an inventory: (owner ID, slot)
+    Slot(String, usize),
+}
+
+/// A character on the way to an object to start an activity there.
+#[derive(Clone, Debug)]
+pub struct Errand {
+    pub activity: Activity,
+    pub object_id: String,
+    /// Game minute the character set off
+    pub since: i64,
+}
+
+/// Maximum number of game minutes simulated to catch up after the server fell
+/// behind (e.g. the machine was suspended): 30 game days.
+const MAX_CATCH_UP_MINUTES: i64 = 30 * MINUTES_PER_DAY;
+/// Maximum number of entries kept in an away journal.
+const MAX_JOURNAL_ENTRIES: usize = 200;
+/// Game minutes a character without an account stays in the world after its
+/// owner disconnects (half an hour).
+const GUEST_GRACE_MINUTES: i64 = 30 * 60;
+
+/// A character on the way from one zone to another, with everything that goes
+/// with it.
+///
+/// Serializable, so it can be handed to a zone on another server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Traveller {
+    pub player: Player,
+    pub inventory: Option<Inventory>,
+    /// Schedule uploaded by the owner, if any
+    pub schedule: Option<Schedule>,
+    /// Money (cents), if the character had an account
+    pub balance: Option<i64>,
+    /// Shop orders the character is waiting for, delivered where it goes
+    pub orders: Vec<Order>,
+    /// Relationships the character is part of, moved to the new zone
+    pub relationships: Vec<(String, String, Relationship)>,
+    pub chat_groups: Vec<String>,
+    /// Players the character does not want to hear from
+    pub blocked: Vec<String>,
+}
+
+/// Record of a character's life while their owner was offline.
+#[derive(Clone, Debug)]
+pub struct AwayJournal {
+    /// Game minute the owner disconnected
+    pub since: i64,
+    /// Notable events, oldest first, each prefixed with the game time
+    pub entries: Vec<String>,
+}
+
+/// Summary delivered to a player who reconnects to a character that kept living.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AwaySummary {
+    /// Game minutes the owner was away
+    pub away_minutes: i64,
+    /// Notable events while away, oldest first
+    pub events: Vec<String>,
+    /// Needs on return
+    pub needs: Needs,
+    /// Mood on return
+    pub mood: Mood,
+    /// Activity on return
+    pub activity: Activity,
 }
 
 impl GameState {
-    /// Create a new game state with initial entities.
+    /// Create a new game state in the built-in arena level with the core object types.
     pub fn new() -> Self {
-        let physics = PhysicsWorld::new();
+        Self::with_level(Level::arena(), Catalogue::core())
+    }
+
+    /// Create a new game state whose static world is built from a level description
+    /// and whose entities are built from an object catalogue.
+    pub fn with_level(level: Level, catalogue: Catalogue) -> Self {
+        let physics = PhysicsWorld::from_level(&level);
+        let nav = NavGrid::bake(&physics, &level);
         let entities = HashMap::new();
-        tracing::info!("GameState initialized with {} entities", entities.len());
-        Self {
+        let needs_tick_minute = Self::get_game_time_minutes();
+        tracing::info!(
+            "GameState initialized in level '{}' with {} entities",
+            level.name,
+            entities.len()
+        );
+        let mut state = Self {
             players: HashMap::new(),
             entities,
+            removed_entities: Vec::new(),
             physics,
-        }
+            level,
+            nav,
+            catalogue,
+            needs_tick_minute,
+            delayed_activities: HashMap::new(),
+            schedules: HashMap::new(),
+            activity_events: Vec::new(),
+            away_journals: HashMap::new(),
+            npcs: HashMap::new(),
+            routes: HashMap::new(),
+            errands: HashMap::new(),
+            inventories: HashMap::new(),
+            ledger: Ledger::default(),
+            orders: OrderBook::default(),
+            deliveries: Vec::new(),
+            property_changes: Vec::new(),
+            recipes: HashMap::new(),
+            cooking: HashMap::new(),
+            relationships: Relationships::default(),
+            chat: Chat::default(),
+            accounts: Accounts::default(),
+            weather: WeatherSim::new(GameTime::from_minutes(needs_tick_minute)),
+            environment_due: true,
+            last_moves: HashMap::new(),
+        };
+        state.spawn_placements();
+        state
     }
 
     /// Get the current game time in minutes, derived from Unix time.
@@ -167,8 +457,17 @@ impl GameState {
 
     /// Add a new player to the game state.
     ///
-    /// Also creates a corresponding human entity for physics simulation.
-    pub fn add_player(&mut self, player: Player) {
+    /// Also creates a corresponding human entity for physics simulation. Only
+    /// characters of an account get money.
+    pub fn add_player(&mut self, mut player: Player) {
+        // Needs and mood are server-authoritative; ignore whatever the client sent
+        player.needs = Needs::default();
+        player.mood = Mood::default();
+        player.activity = Activity::Idle;
+        player.timed_activity = None;
+        if !player.npc && !player.guest {
+            self.ledger.open(&player.id, self.needs_tick_minute);
+        }
         // Create corresponding entity for player (for physics simulation)
         let entity = self.player_to_entity(&player);
         self.add_entity(entity);
@@ -180,178 +479,3001 @@ impl GameState {
     /// Removes player data, associated entity, and physics body.
     pub fn remove_player(&mut self, player_id: &str) {
         let entity_id = format!("human_{}", player_id);
-        self.entities.remove(&entity_id);
-        self.physics.remove_entity(&entity_id);
+        self.remove_entity(&entity_id);
+        self.delayed_activities.remove(player_id);
+        self.schedules.remove(player_id);
+        self.away_journals.remove(player_id);
+        self.npcs.remove(player_id);
+        self.routes.remove(player_id);
+        self.errands.remove(player_id);
+        self.inventories.remove(player_id);
+        self.cooking.remove(player_id);
+        self.last_moves.remove(player_id);
         self.players.remove(player_id);
     }
 
-    /// Update a player's position, rotation, and movement state.
+    /// Create a character for an account and put it in the world.
     ///
-    /// Also updates the corresponding entity and physics body position.
-    pub fn update_player_position(
+    /// The character appears at the level's spawn point with no one controlling
+    /// it, living on the needs autopilot until its owner selects it.
+    ///
+    /// With two parents (two different characters of the same account, wherever
+    /// they are) the genome is inherited from them, otherwise it is random.
+    pub fn create_character(
         &mut self,
-        player_id: &str,
-        position: Position,
-        rotation: f32,
-        is_moving: bool,
-    ) {
-        if let Some(player) = self.players.get_mut(player_id) {
-            player.position = position.clone();
-            player.rotation = rotation;
-            player.is_moving = is_moving;
-
-            // Update corresponding entity and physics body
-            let entity_id = format!("human_{}", player_id);
-            if let Some(entity) = self.entities.get_mut(&entity_id) {
-                entity.position = position;
-                entity.rotation = Rotation {
-                    x: 0.0,
-                    y: rotation,
-                    z: 0.0,
+        account_id: &str,
+        name: &str,
+        choice: &AppearanceChoice,
+        parents: Option<[String; 2]>,
+    ) -> Result<Character, String> {
+        let mut rng = rand::thread_rng();
+        let genome = match parents {
+            Some([mother, father]) => {
+                if mother == father {
+                    return Err("parents must be two different characters".to_string());
+                }
+                let genome_of = |id: &str| {
+                    self.accounts
+                        .get(account_id)
+                        .and_then(|account| account.characters.iter().find(|c| c.id == id))
+                        .map(|c| c.genome.clone())
+                        .ok_or_else(|| format!("no character {id} of yours"))
                 };
-                // Update physics body position (for collision detection)
-                self.physics.update_human_position(
-                    &entity_id,
-                    entity.position.x,
-                    entity.position.y,
-                    entity.position.z,
-                );
+                Genome::inherit(&genome_of(&mother)?, &genome_of(&father)?, &mut rng)
             }
+            None => Genome::random(&mut rng),
+        };
+        let appearance = Appearance::new(&genome, choice)?;
+        let character = self
+            .accounts
+            .create_character(account_id, name, genome, appearance)?;
+        self.spawn_character(&character);
+        tracing::info!("Account {account_id} created character {}", character.name);
+        Ok(character)
+    }
+
+    /// Add an account's character to the world, offline.
+    fn spawn_character(&mut self, character: &Character) {
+        if self.players.contains_key(&character.id) {
+            return;
         }
+        let [x, y, z] = self.level.spawn_point();
+        let player = Player {
+            id: character.id.clone(),
+            username: character.name.clone(),
+            position: Position { x, y, z },
+            rotation: 0.0,
+            is_moving: false,
+            activity: Activity::Idle,
+            needs: Needs::default(),
+            mood: Mood::default(),
+            timed_activity: None,
+            offline: false,
+            npc: false,
+            guest: false,
+            health: Health::default(),
+            genome: character.genome.clone(),
+            appearance: character.appearance.clone(),
+            exposure: Exposure::default(),
+        };
+        self.add_player(player);
+        self.disconnect_player(&character.id);
     }
 
-    /// Update a player's current activity.
-    pub fn update_player_activity(&mut self, player_id: &str, activity: Activity) {
-        if let Some(player) = self.players.get_mut(player_id) {
-            player.activity = activity;
+    /// An account's character as listed for selection, if it is in this zone.
+    pub fn character_summary(&self, character: &Character, zone: &str) -> Option<CharacterSummary> {
+        let player = self.players.get(&character.id)?;
+        Some(CharacterSummary {
+            id: character.id.clone(),
+            name: character.name.clone(),
+            activity: player.activity.clone(),
+            offline: player.offline,
+            appearance: player.appearance.clone(),
+            zone: zone.to_string(),
+        })
+    }
+
+    /// Take a character out of this zone to move it to another.
+    ///
+    /// Whatever the character was doing is interrupted. Its inventory, money,
+    /// pending orders, schedule, relationships and chat groups and block list go
+    /// with it.
+    pub fn depart(&mut self, player_id: &str) -> Result<Traveller, String> {
+        match self.players.get(player_id) {
+            None => return Err(format!("no character {player_id}")),
+            Some(player) if player.npc => {
+                return Err("non-player characters stay in their zone".to_string())
+            }
+            Some(_) => {}
+        }
+        self.finish_activity(
+            player_id,
+            ActivityOutcome::Interrupted,
+            Some("left the zone".to_string()),
+        );
+        let mut player = self.players.get(player_id).cloned().expect("checked above");
+        player.activity = Activity::Idle;
+        player.is_moving = false;
+        let chat_groups = self.chat.groups_of(player_id);
+        for group in &chat_groups {
+            self.chat.leave_group(player_id, group);
+        }
+        let blocked = self.chat.blocked_by(player_id);
+        for other_id in &blocked {
+            self.chat.set_blocked(player_id, other_id, false);
         }
+        let traveller = Traveller {
+            inventory: self.inventories.get(player_id).cloned(),
+            schedule: self
+                .schedules
+                .get(player_id)
+                .filter(|active| !active.autopilot)
+                .map(|active| active.schedule.source.clone()),
+            balance: self.ledger.close(player_id),
+            orders: self.orders.take_of(player_id),
+            relationships: self.relationships.take_of(player_id),
+            chat_groups,
+            blocked,
+            player,
+        };
+        self.remove_player(player_id);
+        tracing::info!("Player {player_id} left level '{}'", self.level.name);
+        Ok(traveller)
     }
 
-    /// Get a copy of all players in the game.
-    pub fn get_all_players(&self) -> Vec<Player> {
-        self.players.values().cloned().collect()
+    /// Bring in a character that left another zone, at the level's spawn point.
+    pub fn arrive(&mut self, traveller: Traveller) {
+        let Traveller {
+            mut player,
+            inventory,
+            schedule,
+            balance,
+            orders,
+            relationships,
+            chat_groups,
+            blocked,
+        } = traveller;
+        let player_id = player.id.clone();
+        let [x, y, z] = self.level.spawn_point();
+        player.position = Position { x, y, z };
+        if let Some(balance) = balance {
+            self.ledger.restore(vec![(player_id.clone(), balance)]);
+        }
+        self.orders.take_over(orders);
+        if let Some(inventory) = inventory {
+            self.inventories.insert(player_id.clone(), inventory);
+        }
+        if let Some(schedule) = schedule {
+            match schedule.compile() {
+                Ok(schedule) => {
+                    self.schedules.insert(
+                        player_id.clone(),
+                        ActiveSchedule {
+                            schedule,
+                            last_choice: None,
+                            autopilot: false,
+                        },
+                    );
+                }
+                Err(errors) => tracing::warn!("Dropped schedule of {player_id}: {errors:?}"),
+            }
+        }
+        // Saved by this zone from now on
+        self.relationships.restore(relationships.clone());
+        self.relationships.return_unsaved(relationships);
+        for group in &chat_groups {
+            if let Err(e) = self.chat.join_group(&player_id, group) {
+                tracing::debug!("Player {player_id} could not rejoin chat group {group}: {e}");
+            }
+        }
+        for other_id in &blocked {
+            self.chat.set_blocked(&player_id, other_id, true);
+        }
+        let entity = self.player_to_entity(&player);
+        self.add_entity(entity);
+        self.players.insert(player_id.clone(), player);
+        tracing::info!("Player {player_id} arrived in level '{}'", self.level.name);
     }
 
-    /// Add a new entity to the game state.
+    /// Set accounts loaded from the database and put their characters in the world.
+    pub fn restore_accounts(&mut self, accounts: Vec<Account>) {
+        for character in accounts.iter().flat_map(|a| a.characters.iter()) {
+            self.spawn_character(character);
+        }
+        self.accounts.restore(accounts);
+    }
+
+    /// Spawn a non-player character from its definition.
+    pub fn spawn_npc(&mut self, def: NpcDef) {
+        let player = Player {
+            id: def.id.clone(),
+            username: def.name.clone(),
+            position: def.position.clone(),
+            rotation: 0.0,
+            is_moving: false,
+            activity: Activity::Idle,
+            needs: Needs::default(),
+            mood: Mood::default(),
+            timed_activity: None,
+            offline: false,
+            npc: true,
+            guest: false,
+            health: match def.age_years {
+                Some(age) => Health::aged(age, self.needs_tick_minute),
+                None => Health::default(),
+            },
+            genome: Genome::seeded(&def.id),
+            appearance: Appearance::default(),
+            exposure: Exposure::default(),
+        };
+        let player = Player {
+            appearance: Appearance::new(&player.genome, &def.appearance).unwrap_or_default(),
+            ..player
+        };
+        self.add_player(player);
+        tracing::info!("Spawned NPC {} ({})", def.name, def.id);
+        self.npcs.insert(def.id.clone(), NpcBrain { def });
+    }
+
+    /// Send a character walking to a position along a path through the level.
     ///
-    /// Creates the corresponding physics body based on entity type.
-    pub fn add_entity(&mut self, entity: Entity) {
-        match entity.entity_type {
-            EntityType::Human => {
-                // Create kinematic physics body for human (position-controlled)
-                self.physics.create_human(
-                    entity.id.clone(),
-                    entity.position.x,
-                    entity.position.y,
-                    entity.position.z,
+    /// Returns false (and leaves the character where it is) when no path exists.
+    pub fn walk_to(&mut self, player_id: &str, target: &Position) -> bool {
+        let Some(player) = self.players.get(player_id) else {
+            return false;
+        };
+        match self.nav.find_path(&player.position, target) {
+            Some(route) if !route.is_empty() => {
+                self.routes.insert(player_id.to_string(), route);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Stop a walking character and drop any errand it was walking for.
+    pub fn stop_walking(&mut self, player_id: &str) {
+        self.errands.remove(player_id);
+        if self.routes.remove(player_id).is_some() {
+            let rotation = self.players.get(player_id).map_or(0.0, |p| p.rotation);
+            self.set_player_move_intent(player_id, (0.0, 0.0), 0.0, rotation);
+        }
+    }
+
+    /// Steer walking characters along their routes; called every physics step.
+    ///
+    /// Characters head for their next waypoint, slowing down so they stop on it,
+    /// and are moved directly to waypoints reached through a level link.
+    fn follow_routes(&mut self) {
+        let step_seconds = self.physics.integration_parameters.dt;
+        let mut steering = Vec::new();
+        let mut arrived = Vec::new();
+        for (player_id, route) in self.routes.iter_mut() {
+            let Some(player) = self.players.get(player_id) else {
+                continue;
+            };
+            while let Some(waypoint) = route.first() {
+                let (dx, dz) = (
+                    waypoint.position.x - player.position.x,
+                    waypoint.position.z - player.position.z,
                 );
+                let horizontal = (dx * dx + dz * dz).sqrt();
+                if waypoint.via_link {
+                    steering.push((
+                        player_id.clone(),
+                        Some(waypoint.position.clone()),
+                        0.0,
+                        0.0,
+                        0.0,
+                    ));
+                    route.remove(0);
+                    break;
+                }
+                if horizontal <= WAYPOINT_REACHED {
+                    route.remove(0);
+                    continue;
+                }
+                let speed = WALK_SPEED.min(horizontal / step_seconds);
+                steering.push((player_id.clone(), None, dx, dz, speed));
+                break;
             }
-            EntityType::Ball => {
-                // Create dynamic physics body for ball (physics-controlled)
-                self.physics.create_bouncy_ball(
-                    entity.id.clone(),
-                    entity.position.x,
-                    entity.position.z,
+            if route.is_empty() {
+                arrived.push(player_id.clone());
+            }
+        }
+        for (player_id, teleport, dx, dz, speed) in steering {
+            if let Some(target) = teleport {
+                self.physics.update_human_position(
+                    &format!("human_{player_id}"),
+                    target.x,
+                    target.y,
+                    target.z,
                 );
+                let rotation = self.players.get(&player_id).map_or(0.0, |p| p.rotation);
+                self.set_player_move_intent(&player_id, (0.0, 0.0), 0.0, rotation);
+            } else {
+                self.set_player_move_intent(&player_id, (dx, dz), speed, dx.atan2(dz));
+            }
+        }
+        for player_id in arrived {
+            if self.routes.remove(&player_id).is_some() {
+                let rotation = self.players.get(&player_id).map_or(0.0, |p| p.rotation);
+                self.set_player_move_intent(&player_id, (0.0, 0.0), 0.0, rotation);
             }
         }
-        self.entities.insert(entity.id.clone(), entity);
     }
 
-    /// Update an entity's position.
+    /// Walk a character to an object and start an activity once it is in reach.
     ///
-    /// Also updates physics body if the entity is a human.
-    pub fn update_entity_position(&mut self, entity_id: &str, position: Position) {
-        if let Some(entity) = self.entities.get_mut(entity_id) {
-            entity.position = position.clone();
-            // Update physics body for human entities
-            if matches!(entity.entity_type, EntityType::Human) {
-                self.physics
-                    .update_human_position(entity_id, position.x, position.y, position.z);
+    /// Returns false when the object does not exist or cannot be reached.
+    pub fn go_do(
+        &mut self,
+        player_id: &str,
+        activity: Activity,
+        object_id: &str,
+        now: i64,
+    ) -> bool {
+        let Some(target) = self.entities.get(object_id).map(|o| o.position.clone()) else {
+            return false;
+        };
+        self.stop_walking(player_id);
+        if !self.walk_to(player_id, &target) {
+            return false;
+        }
+        self.errands.insert(
+            player_id.to_string(),
+            Errand {
+                activity,
+                object_id: object_id.to_string(),
+                since: now,
+            },
+        );
+        true
+    }
+
+    /// Start the activities of characters whose errand object is in reach, and
+    /// give up on errands that take too long or whose route ran out.
+    fn run_errands(&mut self, now: i64) {
+        let ids: Vec<String> = self.errands.keys().cloned().collect();
+        for id in ids {
+            let (Some(errand), Some(player)) = (self.errands.get(&id), self.players.get(&id))
+            else {
+                self.errands.remove(&id);
+                continue;
+            };
+            let object = self.entities.get(&errand.object_id);
+            let in_reach =
+                object.is_some_and(|o| distance(&o.position, &player.position) <= REACH_DISTANCE);
+            let walking =
+                self.routes.contains_key(&id) && now - errand.since < ERRAND_GIVE_UP_MINUTES;
+            if in_reach || !walking || object.is_none() {
+                let activity = errand.activity.clone();
+                let object_id = errand.object_id.clone();
+                self.stop_walking(&id);
+                if !in_reach {
+                    tracing::debug!("{id} gave up walking to {activity:?}");
+                    continue;
+                }
+                let decision = self.request_activity_with(&id, activity.clone(), Some(object_id));
+                // A schedule whose activity was refused on arrival asks again
+                if matches!(decision, Decision::Refuse { .. }) {
+                    if let Some(active) = self.schedules.get_mut(&id) {
+                        if active.last_choice.as_ref() == Some(&activity) {
+                            active.last_choice = None;
+                        }
+                    }
+                }
             }
         }
     }
 
-    /// Update an entity's rotation.
-    pub fn update_entity_rotation(&mut self, entity_id: &str, rotation: Rotation) {
-        if let Some(entity) = self.entities.get_mut(entity_id) {
-            entity.rotation = rotation;
+    /// Let every idle NPC pick something to do, walk to it, and start it on arrival.
+    ///
+    /// NPCs with an uploaded schedule follow the schedule instead.
+    fn run_npcs(&mut self, now: i64) {
+        let ids: Vec<String> = self.npcs.keys().cloned().collect();
+        for id in ids {
+            if self.schedules.contains_key(&id)
+                || self.delayed_activities.contains_key(&id)
+                || self.errands.contains_key(&id)
+            {
+                continue;
+            }
+            let Some(player) = self.players.get(&id) else {
+                continue;
+            };
+            if player.timed_activity.is_some() {
+                continue;
+            }
+            let from = player.position.clone();
+            let Some(intention) = npc::choose(&self.npcs[&id].def, &player.needs, |tag| {
+                self.nearest_tagged(&from, tag, npc::SEARCH_RADIUS)
+                    .map(|(entity, d)| (entity.id.clone(), d))
+            }) else {
+                continue;
+            };
+            // Hungry NPCs without food at hand go to the fridge
+            let object_id = match intention.object_id {
+                None if intention.activity == Activity::Eating
+                    && self.find_food(&id, None).is_none() =>
+                {
+                    self.food_to_walk_to(&id)
+                }
+                object_id => object_id,
+            };
+            let far_object = object_id.filter(|object_id| {
+                self.entities
+                    .get(object_id)
+                    .is_some_and(|o| distance(&o.position, &from) > REACH_DISTANCE)
+            });
+            match far_object {
+                Some(object_id) => {
+                    if !self.go_do(&id, intention.activity, &object_id, now) {
+                        tracing::debug!("NPC {id} has no path to {object_id}");
+                    }
+                }
+                None => {
+                    self.request_activity(&id, intention.activity);
+                }
+            }
         }
     }
 
-    /// Get a copy of all entities in the game.
-    pub fn get_all_entities(&self) -> Vec<Entity> {
-        self.entities.values().cloned().collect()
+    /// Connect a player to their character.
+    ///
+    /// A character left behind by an earlier connection is taken over as it is
+    /// now (position, needs, activity), and a summary of what happened while the
+    /// owner was away is returned. Otherwise the player is added as new.
+    pub fn connect_player(&mut self, player: Player) -> Option<AwaySummary> {
+        let Some(existing) = self.players.get_mut(&player.id) else {
+            // Characters without an account get a genome of their own; the hair
+            // style and outfit the client asked for are kept if valid
+            let mut player = player;
+            player.guest = true;
+            player.genome = Genome::seeded(&player.id);
+            let choice = AppearanceChoice {
+                hair_style: player.appearance.hair_style.clone(),
+                outfit_color: player.appearance.outfit_color.clone(),
+            };
+            player.appearance = Appearance::new(&player.genome, &choice)
+                .or_else(|_| Appearance::new(&player.genome, &AppearanceChoice::default()))
+                .unwrap_or_default();
+            self.add_player(player);
+            return None;
+        };
+        existing.username = player.username;
+        existing.offline = false;
+        let summary = AwaySummary {
+            away_minutes: 0,
+            events: Vec::new(),
+            needs: existing.needs.clone(),
+            mood: existing.mood.clone(),
+            activity: existing.activity.clone(),
+        };
+        if self
+            .schedules
+            .get(&player.id)
+            .is_some_and(|active| active.autopilot)
+        {
+            self.schedules.remove(&player.id);
+        }
+        let journal = self.away_journals.remove(&player.id)?;
+        Some(AwaySummary {
+            away_minutes: Self::get_game_time_minutes() - journal.since,
+            events: journal.entries,
+            ..summary
+        })
     }
 
-    /// Step the physics simulation and sync entity positions/rotations from physics.
+    /// Leave a player's character in the world after its owner disconnected.
     ///
-    /// This should be called every frame (60 FPS) to update physics simulation.
-    /// After stepping physics, entity positions and rotations are updated from
-    /// the physics world state.
+    /// The character stops walking and from now on follows its schedule, or
+    /// the needs autopilot if it has none. Events are journaled for the owner.
+    pub fn disconnect_player(&mut self, player_id: &str) {
+        let Some(player) = self.players.get_mut(player_id) else {
+            return;
+        };
+        player.offline = true;
+        player.is_moving = false;
+        let rotation = player.rotation;
+        self.set_player_move_intent(player_id, (0.0, 0.0), 0.0, rotation);
+        self.schedules
+            .entry(player_id.to_string())
+            .or_insert_with(|| ActiveSchedule {
+                schedule: Schedule::autopilot()
+                    .compile()
+                    .expect("autopilot schedule is valid"),
+                last_choice: None,
+                autopilot: true,
+            });
+        self.away_journals.insert(
+            player_id.to_string(),
+            AwayJournal {
+                since: Self::get_game_time_minutes(),
+                entries: Vec::new(),
+            },
+        );
+        tracing::info!("Player {player_id} went offline; character keeps living");
+    }
+
+    /// Add an entry to an offline player's away journal.
+    fn journal(&mut self, player_id: &str, now: i64, entry: String) {
+        let Some(journal) = self.away_journals.get_mut(player_id) else {
+            return;
+        };
+        if journal.entries.len() >= MAX_JOURNAL_ENTRIES {
+            journal.entries.remove(0);
+        }
+        let time = GameTime::from_minutes(now);
+        journal.entries.push(format!(
+            "day {} {:02}:{:02} {entry}",
+            time.day_of_year() + 1,
+            time.hour(),
+            time.minute_of_day() % 60
+        ));
+    }
+
+    /// Update a player's position, rotation, and movement state.
     ///
-    /// # Arguments
-    /// * `dt` - Delta time in seconds (typically 1/60.0 for 60 FPS)
-    pub fn step_physics(&mut self, dt: f64) {
-        // Step physics simulation
-        self.physics.step(dt);
+    /// The requested position is treated as a movement intent: the human entity is
+    /// moved towards it horizontally by the character controller, which applies
+    /// gravity and stops at walls and other bodies. Player and entity are updated
+    /// with the resulting position.
+    ///
+    /// The move is simulated over the real time since the player's previous update,
+    /// and no further than the character could run in that time.
+    ///
+    /// # Returns
+    /// The corrected position, or None if the player does not exist
+    pub fn update_player_position(
+        &mut self,
+        player_id: &str,
+        position: Position,
+        rotation: f32,
+        is_moving: bool,
+    ) -> Option<Position> {
+        let player = self.players.get_mut(player_id)?;
+        let entity_id = format!("human_{}", player_id);
+        let entity = self.entities.get_mut(&entity_id)?;
 
-        // Sync entity positions and rotations from physics world
-        for entity in self.entities.values_mut() {
-            // Update position from physics
-            if let Some((x, y, z)) = self.physics.get_entity_position(&entity.id) {
-                // Reset balls that have fallen too far below ground (y < -10m)
-                // This prevents balls from falling through the world indefinitely
-                if matches!(entity.entity_type, EntityType::Ball) && y < -10.0 {
-                    tracing::warn!(
-                        "Ball {} fell below ground (y={}), resetting to y=5",
-                        entity.id,
-                        y
-                    );
-                    // Reset ball position to above ground with random x/z
-                    let mut rng = rand::thread_rng();
-                    let new_x = rng.gen_range(-50.0..50.0);
-                    let new_z = rng.gen_range(-50.0..50.0);
-                    let new_y = 5.0;
+        let now = Instant::now();
+        let dt = match self.last_moves.insert(player_id.to_string(), now) {
+            Some(then) => (now.duration_since(then).as_secs_f32() * GAME_SECONDS_PER_SECOND)
+                .min(MAX_MOVE_SECONDS),
+            None => self.physics.integration_parameters.dt,
+        };
+        let mut dx = position.x - entity.position.x;
+        let mut dz = position.z - entity.position.z;
+        let reach = MAX_RUN_SPEED * player.health.speed_factor(self.needs_tick_minute) * dt;
+        let distance = (dx * dx + dz * dz).sqrt();
+        if !distance.is_finite() {
+            (dx, dz) = (0.0, 0.0);
+        } else if distance > reach {
+            dx *= reach / distance;
+            dz *= reach / distance;
+        }
+        let corrected = match self.physics.move_character(&entity_id, dx, dz, dt) {
+            Some((x, y, z)) => Position { x, y, z },
+            None => position,
+        };
 
-                    // Remove old physics body and create new one
-                    self.physics.remove_entity(&entity.id);
-                    self.physics
-                        .create_bouncy_ball(entity.id.clone(), new_x, new_z);
+        player.position = corrected.clone();
+        player.rotation = rotation;
+        player.is_moving = is_moving;
+        entity.position = corrected.clone();
+        entity.rotation = Rotation {
+            x: 0.0,
+            y: rotation,
+            z: 0.0,
+        };
+        Some(corrected)
+    }
 
-                    entity.position = Position {
-                        x: new_x,
-                        y: new_y,
-                        z: new_z,
+    /// Set a player's walking intent.
+    ///
+    /// The human entity keeps walking in `direction` (X/Z, normalized) at `speed`,
+    /// at most [`MAX_RUN_SPEED`], on every physics step until a zero direction is sent.
+    pub fn set_player_move_intent(
+        &mut self,
+        player_id: &str,
+        direction: (f32, f32),
+        speed: f32,
+        rotation: f32,
+    ) {
+        let Some(player) = self.players.get_mut(player_id) else {
+            return;
+        };
+        let speed = if speed.is_finite() {
+            speed.clamp(0.0, MAX_RUN_SPEED)
+        } else {
+            0.0
+        };
+        let speed = speed * player.health.speed_factor(self.needs_tick_minute);
+        let (dx, dz) = direction;
+        let length = (dx * dx + dz * dz).sqrt();
+        let (vx, vz) = if length > f32::EPSILON {
+            (dx / length * speed, dz / length * speed)
+        } else {
+            (0.0, 0.0)
+        };
+        player.rotation = rotation;
+        player.is_moving = length > f32::EPSILON;
+        self.physics
+            .set_character_intent(&format!("human_{player_id}"), vx, vz);
+    }
+
+    /// Ask a player's character to start an activity.
+    ///
+    /// Refused while the current activity cannot be abandoned yet or when a
+    /// required object (e.g. a bed for Sleeping) is out of reach. Otherwise the
+    /// character's mood decides: the activity starts now, is scheduled to start
+    /// after a delay, or is refused. A new request replaces any delayed one.
+    pub fn request_activity(&mut self, player_id: &str, activity: Activity) -> Decision {
+        self.request_activity_with(player_id, activity, None)
+    }
+
+    /// Ask a player's character to start an activity with a given object, or with
+    /// the nearest suitable one when `object_id` is None.
+    fn request_activity_with(
+        &mut self,
+        player_id: &str,
+        activity: Activity,
+        object_id: Option<String>,
+    ) -> Decision {
+        // Simulated time, which lags behind the clock while catching up
+        let now = self.needs_tick_minute;
+        let Some(player) = self.players.get(player_id) else {
+            return Decision::Refuse {
+                reason: "unknown player".to_string(),
+            };
+        };
+        if let Some(current) = &player.timed_activity {
+            if current.activity == activity {
+                return Decision::Allow;
+            }
+            let min_minutes = current.activity.spec().min_minutes;
+            let elapsed = current.elapsed(now);
+            if elapsed < min_minutes {
+                return Decision::Refuse {
+                    reason: format!(
+                        "busy {:?} for another {} minutes",
+                        current.activity,
+                        min_minutes - elapsed
+                    ),
+                };
+            }
+        }
+        if let Some(reason) = player.health.forbids(&activity, now) {
+            return Decision::Refuse { reason };
+        }
+        let object_id = match object_id {
+            Some(object_id) => {
+                let in_reach = self.entities.get(&object_id).is_some_and(|object| {
+                    distance(&object.position, &player.position) <= REACH_DISTANCE
+                });
+                if !in_reach {
+                    return Decision::Refuse {
+                        reason: format!("{object_id} is out of reach"),
                     };
-                } else {
-                    entity.position = Position { x, y, z };
                 }
+                Some(object_id)
             }
-            // Update rotation from physics
-            if let Some((rx, ry, rz)) = self.physics.get_entity_rotation(&entity.id) {
-                entity.rotation = Rotation {
-                    x: rx,
-                    y: ry,
-                    z: rz,
-                };
+            None => match self.find_required_object(player, &activity) {
+                Ok(object_id) => object_id,
+                Err(reason) => return Decision::Refuse { reason },
+            },
+        };
+
+        if activity == Activity::Eating && self.find_food(player_id, object_id.as_deref()).is_none()
+        {
+            return Decision::Refuse {
+                reason: "nothing to eat".to_string(),
+            };
+        }
+
+        let decision = player.mood.decide_activity(&player.needs, &activity);
+        match &decision {
+            Decision::Allow => {
+                self.delayed_activities.remove(player_id);
+                self.start_activity(player_id, activity, object_id, now);
             }
+            Decision::Delay { minutes, .. } => {
+                self.delayed_activities
+                    .insert(player_id.to_string(), (activity, now + minutes));
+            }
+            Decision::Refuse { .. } => {}
         }
+        decision
     }
 
-    /// Convert a player to an entity representation.
+    /// Find the nearest object within reach that an activity needs.
     ///
-    /// Used when adding a player to create the corresponding physics entity.
-    pub fn player_to_entity(&self, player: &Player) -> Entity {
-        Entity {
-            id: format!("human_{}", player.id),
-            entity_type: EntityType::Human,
-            position: player.position.clone(),
-            rotation: Rotation {
-                x: 0.0,
-                y: player.rotation,
-                z: 0.0,
-            },
+    /// Returns `Ok(None)` when the activity needs no object.
+    fn find_required_object(
+        &self,
+        player: &Player,
+        activity: &Activity,
+    ) -> Result<Option<String>, String> {
+        let Some(tag) = activity.spec().required_tag else {
+            return Ok(None);
+        };
+        self.nearest_tagged(&player.position, tag, REACH_DISTANCE)
+            .map(|(entity, _)| Some(entity.id.clone()))
+            .ok_or_else(|| format!("{activity:?} needs a {tag} within reach"))
+    }
+
+    /// Nearest entity whose type carries a catalogue tag, within a distance.
+    fn nearest_tagged(
+        &self,
+        from: &Position,
+        tag: &str,
+        max_distance: f32,
+    ) -> Option<(&Entity, f32)> {
+        self.entities
+            .values()
+            .filter(|entity| {
+                self.catalogue
+                    .get(entity.entity_type.as_str())
+                    .is_some_and(|t| t.has_tag(tag))
+            })
+            .map(|entity| (entity, distance(&entity.position, from)))
+            .filter(|(_, d)| *d <= max_distance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Start an activity now, interrupting the current one.
+    fn start_activity(
+        &mut self,
+        player_id: &str,
+        activity: Activity,
+        mut object_id: Option<String>,
+        now: i64,
+    ) {
+        if let Some(current) = self
+            .players
+            .get(player_id)
+            .and_then(|p| p.timed_activity.as_ref())
+        {
+            let reason = format!("switched to {activity:?}");
+            if current.activity != activity {
+                self.finish_activity(player_id, ActivityOutcome::Interrupted, Some(reason));
+            }
+        }
+        let mut duration_minutes = activity.spec().duration_minutes;
+        match activity {
+            Activity::Eating => {
+                let Some(source) = self.find_food(player_id, object_id.as_deref()) else {
+                    tracing::debug!("{player_id} has nothing to eat");
+                    return;
+                };
+                // The food, or the fridge it came from, is not needed while eating
+                let holder = match &source {
+                    FoodSource::Entity(id) | FoodSource::Slot(id, _) => id,
+                };
+                if object_id.as_ref() == Some(holder) {
+                    object_id = None;
+                }
+                match self.serve_food(player_id, &source) {
+                    Some(minutes) => duration_minutes = minutes,
+                    None => return,
+                }
+            }
+            Activity::Cooking => {
+                if let Some(minutes) = self.start_recipe(player_id) {
+                    duration_minutes = minutes;
+                }
+            }
+            _ => {
+                self.cooking.remove(player_id);
+            }
+        }
+        // Get the object ready, e.g. turn the stove on to cook
+        if let Some(object_id) = &object_id {
+            while let Some((name, value)) =
+                self.unmet_flag(object_id, interaction::required_state(&activity))
+            {
+                if let Err(e) = self.set_property(object_id, name, PropertyValue::Bool(value)) {
+                    tracing::warn!("Could not prepare {object_id} for {activity:?}: {e}");
+                    break;
+                }
+            }
         }
+        let Some(player) = self.players.get_mut(player_id) else {
+            return;
+        };
+        let timed = TimedActivity {
+            duration_minutes,
+            activity: activity.clone(),
+            started_at: now,
+            object_id,
+        };
+        player.activity = activity;
+        player.timed_activity = Some(timed.clone());
+        self.journal(player_id, now, format!("started {:?}", timed.activity));
+        self.activity_events.push(ActivityEvent::Started {
+            player_id: player_id.to_string(),
+            activity: timed,
+        });
+    }
+
+    /// End the current activity and return the character to idle.
+    ///
+    /// Completion effects are applied only when the activity completed.
+    fn finish_activity(
+        &mut self,
+        player_id: &str,
+        outcome: ActivityOutcome,
+        reason: Option<String>,
+    ) {
+        let Some(player) = self.players.get_mut(player_id) else {
+            return;
+        };
+        let Some(timed) = player.timed_activity.take() else {
+            return;
+        };
+        if outcome == ActivityOutcome::Completed {
+            player.needs.adjust(timed.activity.spec().completion);
+        }
+        player.activity = Activity::Idle;
+        let entry = match (&outcome, &reason) {
+            (ActivityOutcome::Completed, _) => format!("finished {:?}", timed.activity),
+            (ActivityOutcome::Interrupted, Some(reason)) => {
+                format!("stopped {:?}: {reason}", timed.activity)
+            }
+            (ActivityOutcome::Interrupted, None) => format!("stopped {:?}", timed.activity),
+        };
+        self.journal(player_id, self.needs_tick_minute, entry);
+        if timed.activity == Activity::Cooking {
+            let completed = outcome == ActivityOutcome::Completed;
+            self.finish_recipe(player_id, completed, timed.object_id.clone());
+        }
+        self.activity_events.push(ActivityEvent::Finished {
+            player_id: player_id.to_string(),
+            activity: timed.activity,
+            outcome,
+            reason,
+        });
+    }
+
+    /// Complete, interrupt or report progress of every running activity.
+    fn advance_activities(&mut self, previous_minute: i64, now: i64) {
+        let mut finished = Vec::new();
+        for player in self.players.values() {
+            let Some(timed) = &player.timed_activity else {
+                continue;
+            };
+            if timed.is_complete(now) {
+                finished.push((player.id.clone(), ActivityOutcome::Completed, None));
+                continue;
+            }
+            if let Some(reason) = player.health.forbids(&timed.activity, now) {
+                finished.push((
+                    player.id.clone(),
+                    ActivityOutcome::Interrupted,
+                    Some(reason),
+                ));
+                continue;
+            }
+            if let Some(need) = timed.activity.interrupting_need(&player.needs) {
+                let reason = format!("{need} is critical");
+                finished.push((
+                    player.id.clone(),
+                    ActivityOutcome::Interrupted,
+                    Some(reason),
+                ));
+                continue;
+            }
+            if let Some(object_id) = &timed.object_id {
+                let object = self.entities.get(object_id);
+                let in_reach = object.is_some_and(|object| {
+                    distance(&object.position, &player.position) <= REACH_DISTANCE
+                });
+                let unready =
+                    self.unmet_flag(object_id, interaction::required_state(&timed.activity));
+                if let Some((name, _)) = unready {
+                    let reason = format!("{object_id} is no longer {name}");
+                    finished.push((
+                        player.id.clone(),
+                        ActivityOutcome::Interrupted,
+                        Some(reason),
+                    ));
+                    continue;
+                }
+                if !in_reach {
+                    let reason = format!("{object_id} is out of reach");
+                    finished.push((
+                        player.id.clone(),
+                        ActivityOutcome::Interrupted,
+                        Some(reason),
+                    ));
+                    continue;
+                }
+            }
+            let (before, after) = (timed.elapsed(previous_minute), timed.elapsed(now));
+            if after / PROGRESS_INTERVAL_MINUTES > before / PROGRESS_INTERVAL_MINUTES {
+                self.activity_events.push(ActivityEvent::Progress {
+                    player_id: player.id.clone(),
+                    activity: timed.activity.clone(),
+                    elapsed_minutes: after,
+                    duration_minutes: timed.duration_minutes,
+                });
+            }
+        }
+        for (player_id, outcome, reason) in finished {
+            self.finish_activity(&player_id, outcome, reason);
+        }
+    }
+
+    /// Time of day, season and weather right now.
+    pub fn environment(&self) -> EnvironmentState {
+        let time = GameTime::from_minutes(self.needs_tick_minute);
+        EnvironmentState {
+            game_minute: self.needs_tick_minute,
+            season: Season::of(time),
+            sun: Sun::at(time),
+            weather: self.weather.current().clone(),
+        }
+    }
+
+    /// Take the environment if it is due to be broadcast again.
+    pub fn take_environment(&mut self) -> Option<EnvironmentState> {
+        std::mem::take(&mut self.environment_due).then(|| self.environment())
+    }
+
+    /// Take the activity events produced since the last call, for broadcasting.
+    pub fn take_activity_events(&mut self) -> Vec<ActivityEvent> {
+        std::mem::take(&mut self.activity_events)
+    }
+
+    /// Evaluate every schedule and request the activities whose choice changed.
+    fn run_schedules(&mut self, time: GameTime) {
+        let mut requests = Vec::new();
+        for (player_id, active) in &self.schedules {
+            let Some(player) = self.players.get(player_id) else {
+                continue;
+            };
+            let Some(choice) = active.schedule.choose(&Context {
+                player,
+                time,
+                entities: &self.entities,
+                catalogue: &self.catalogue,
+            }) else {
+                continue;
+            };
+            let already_pending = self
+                .delayed_activities
+                .get(player_id)
+                .is_some_and(|(activity, _)| *activity == choice.activity)
+                || self
+                    .errands
+                    .get(player_id)
+                    .is_some_and(|errand| errand.activity == choice.activity);
+            if active.last_choice.as_ref() != Some(&choice.activity) && !already_pending {
+                requests.push((player_id.clone(), choice));
+            }
+        }
+
+        for (player_id, choice) in requests {
+            // Walk to the object the activity needs when none is in reach
+            if let Some(object_id) = self.object_to_walk_to(&player_id, &choice.activity) {
+                if self.go_do(
+                    &player_id,
+                    choice.activity.clone(),
+                    &object_id,
+                    time.minutes,
+                ) {
+                    tracing::debug!(
+                        "Schedule of {player_id} walks to {object_id} for {:?} ({})",
+                        choice.activity,
+                        choice.reason
+                    );
+                    if let Some(active) = self.schedules.get_mut(&player_id) {
+                        active.last_choice = Some(choice.activity);
+                    }
+                    continue;
+                }
+            }
+            let decision = self.request_activity(&player_id, choice.activity.clone());
+            tracing::debug!(
+                "Schedule of {player_id} asks for {:?} ({}): {decision:?}",
+                choice.activity,
+                choice.reason
+            );
+            // A refused activity is asked for again next minute
+            if matches!(decision, Decision::Refuse { .. }) {
+                continue;
+            }
+            if let Some(active) = self.schedules.get_mut(&player_id) {
+                active.last_choice = Some(choice.activity);
+            }
+        }
+    }
+
+    /// Nearest object an activity needs that is out of reach but within walking
+    /// distance, or None when one is in reach or the activity needs none.
+    fn object_to_walk_to(&self, player_id: &str, activity: &Activity) -> Option<String> {
+        let player = self.players.get(player_id)?;
+        if *activity == Activity::Eating && self.find_food(player_id, None).is_none() {
+            return self.food_to_walk_to(player_id);
+        }
+        let tag = activity.spec().required_tag?;
+        if self
+            .nearest_tagged(&player.position, tag, REACH_DISTANCE)
+            .is_some()
+        {
+            return None;
+        }
+        self.nearest_tagged(&player.position, tag, npc::SEARCH_RADIUS)
+            .map(|(entity, _)| entity.id.clone())
+    }
+
+    /// Ask whether a player's character is willing to walk right now.
+    pub fn check_movement(&self, player_id: &str) -> Decision {
+        match self.players.get(player_id) {
+            Some(player) => player.mood.decide_movement(&player.needs),
+            None => Decision::Refuse {
+                reason: "unknown player".to_string(),
+            },
+        }
+    }
+
+    /// Validate a schedule and put it in control of a player's character.
+    ///
+    /// An empty schedule removes the current one. On failure the previous
+    /// schedule stays in force and every validation error is returned.
+    pub fn set_schedule(&mut self, player_id: &str, schedule: Schedule) -> Result<(), Vec<String>> {
+        if !self.players.contains_key(player_id) {
+            return Err(vec!["unknown player".to_string()]);
+        }
+        if schedule == Schedule::default() {
+            self.schedules.remove(player_id);
+            return Ok(());
+        }
+        let schedule = schedule.compile()?;
+        self.schedules.insert(
+            player_id.to_string(),
+            ActiveSchedule {
+                schedule,
+                last_choice: None,
+                autopilot: false,
+            },
+        );
+        Ok(())
+    }
+
+    /// A player's schedule and what it currently asks for.
+    pub fn schedule_status(&self, player_id: &str) -> (Option<Schedule>, Option<Choice>) {
+        let (Some(active), Some(player)) =
+            (self.schedules.get(player_id), self.players.get(player_id))
+        else {
+            return (None, None);
+        };
+        let choice = active.schedule.choose(&Context {
+            player,
+            time: GameTime::now(),
+            entities: &self.entities,
+            catalogue: &self.catalogue,
+        });
+        (Some(active.schedule.source.clone()), choice)
+    }
+
+    /// Advance every character (online or not) up to the given game minute:
+    /// needs and mood, running activities, schedules and delayed activities.
+    ///
+    /// Each elapsed game minute applies the base decay plus the effects of the
+    /// character's current activity. After a pause (e.g. a suspended machine)
+    /// the missed minutes are simulated one by one, up to 30 game days.
+    /// Activity events are queued for [`GameState::take_activity_events`].
+    pub fn tick_characters(&mut self, now_minutes: i64) {
+        let behind = now_minutes - self.needs_tick_minute;
+        if behind > MAX_CATCH_UP_MINUTES {
+            tracing::warn!(
+                "Skipping {} game minutes of character simulation",
+                behind - MAX_CATCH_UP_MINUTES
+            );
+            self.needs_tick_minute = now_minutes - MAX_CATCH_UP_MINUTES;
+        }
+        if behind > 1 {
+            tracing::info!(
+                "Catching up {} game minutes of character simulation",
+                behind.min(MAX_CATCH_UP_MINUTES)
+            );
+        }
+        // Minute by minute, so schedules, activities and delays fire at the
+        // right game time even when catching up after a long pause
+        while self.needs_tick_minute < now_minutes {
+            self.tick_minute(self.needs_tick_minute + 1);
+        }
+        self.evict_guests(now_minutes);
+    }
+
+    /// Remove the characters without an account whose owner has been gone for
+    /// longer than the grace period.
+    fn evict_guests(&mut self, now: i64) {
+        let expired: Vec<String> = self
+            .players
+            .values()
+            .filter(|player| player.guest && player.offline)
+            .filter(|player| {
+                self.away_journals
+                    .get(&player.id)
+                    .is_some_and(|journal| now - journal.since >= GUEST_GRACE_MINUTES)
+            })
+            .map(|player| player.id.clone())
+            .collect();
+        for id in expired {
+            for group in self.chat.groups_of(&id) {
+                self.chat.leave_group(&id, &group);
+            }
+            for other_id in self.chat.blocked_by(&id) {
+                self.chat.set_blocked(&id, &other_id, false);
+            }
+            self.ledger.close(&id);
+            self.relationships.take_of(&id);
+            self.remove_player(&id);
+            tracing::info!("Removed character {id}, which has no account, after its owner left");
+        }
+    }
+
+    /// Simulate one game minute ending at `now`.
+    fn tick_minute(&mut self, now: i64) {
+        let previous_minute = self.needs_tick_minute;
+        self.needs_tick_minute = now;
+        self.weather
+            .advance(GameTime::from_minutes(now), &mut rand::thread_rng());
+        let [wind_x, wind_z] = self.weather.current().wind;
+        self.physics.set_wind(wind_x, wind_z);
+        if now.rem_euclid(environment::BROADCAST_MINUTES) == 0 {
+            self.environment_due = true;
+        }
+        let mut journal = Vec::new();
+        for player in self.players.values_mut() {
+            let was_critical = player.needs.critical();
+            let sheltered = self
+                .level
+                .is_sheltered(player.position.x, player.position.z);
+            player.exposure.tick(self.weather.current(), sheltered, 1.0);
+            player.needs.tick(&player.activity, 1.0);
+            player.needs.adjust(player.health.need_effects(now));
+            player.needs.adjust(player.genome.need_effects());
+            player.needs.adjust(player.exposure.need_effects());
+            for event in player.health.tick(now, 1) {
+                tracing::info!("Player {} {event}", player.id);
+                journal.push((player.id.clone(), event));
+            }
+            player.mood.tick(&player.needs, &player.activity, 1.0);
+            player.mood.temper(player.genome.traits().temperament, 1.0);
+            for need in player.needs.critical() {
+                if !was_critical.contains(&need) {
+                    tracing::info!("Player {} {} is critical", player.id, need);
+                    journal.push((player.id.clone(), format!("{need} became critical")));
+                }
+            }
+        }
+        for (player_id, entry) in journal {
+            self.journal(&player_id, now, entry);
+        }
+        self.spread_illness(now);
+        if now.rem_euclid(relationship::CHAT_INTERVAL_MINUTES) == 0 {
+            self.chat_while_socializing(now);
+        }
+
+        self.advance_activities(previous_minute, now);
+        self.run_errands(now);
+        self.run_schedules(GameTime::from_minutes(now));
+        self.run_npcs(now);
+        self.deliver_orders(now);
+        if now.rem_euclid(food::SPOILAGE_INTERVAL_MINUTES) == 0 {
+            self.spoil_food(food::SPOILAGE_INTERVAL_MINUTES);
+        }
+
+        let due: Vec<String> = self
+            .delayed_activities
+            .iter()
+            .filter(|(_, (_, start))| *start <= now)
+            .map(|(player_id, _)| player_id.clone())
+            .collect();
+        for player_id in due {
+            let Some((activity, _)) = self.delayed_activities.remove(&player_id) else {
+                continue;
+            };
+            let Some(player) = self.players.get(&player_id) else {
+                continue;
+            };
+            match self.find_required_object(player, &activity) {
+                Ok(object_id) => self.start_activity(&player_id, activity, object_id, now),
+                Err(reason) => tracing::debug!("Delayed activity of {player_id} dropped: {reason}"),
+            }
+        }
+    }
+
+    /// Pass contagious illnesses on to characters nearby, and let characters who
+    /// are run down or chilled catch a cold.
+    fn spread_illness(&mut self, now: i64) {
+        let carriers: Vec<(Position, Vec<IllnessKind>)> = self
+            .players
+            .values()
+            .map(|p| (p.position.clone(), p.health.contagious(now)))
+            .filter(|(_, kinds)| !kinds.is_empty())
+            .collect();
+        let mut rng = rand::thread_rng();
+        let mut caught = Vec::new();
+        for player in self.players.values_mut() {
+            for (position, kinds) in &carriers {
+                if distance(position, &player.position) > health::CONTAGION_DISTANCE {
+                    continue;
+                }
+                for kind in kinds {
+                    if rng.gen_bool(health::CONTAGION_CHANCE) && player.health.catch(*kind, now) {
+                        caught.push((player.id.clone(), *kind));
+                    }
+                }
+            }
+            let run_down = player.needs.critical().contains(&"exhaustion")
+                || player.exposure.cold >= environment::CHILLED;
+            if run_down
+                && rng.gen_bool(health::RUN_DOWN_CHANCE)
+                && player.health.catch(IllnessKind::Cold, now)
+            {
+                caught.push((player.id.clone(), IllnessKind::Cold));
+            }
+        }
+        for (player_id, kind) in caught {
+            tracing::debug!("Player {player_id} caught {kind:?}");
+        }
+    }
+
+    /// Get a copy of all players in the game.
+    pub fn get_all_players(&self) -> Vec<Player> {
+        self.players.values().cloned().collect()
+    }
+
+    /// Add a new entity to the game state.
+    ///
+    /// Creates the corresponding physics body from the entity type's catalogue
+    /// entry. Entities of unknown types are rejected.
+    pub fn add_entity(&mut self, entity: Entity) {
+        let Some(object_type) = self.catalogue.get(entity.entity_type.as_str()) else {
+            tracing::warn!(
+                "Unknown entity type '{}' for entity {}",
+                entity.entity_type.as_str(),
+                entity.id
+            );
+            return;
+        };
+        self.physics.create_object(
+            entity.id.clone(),
+            object_type,
+            entity.position.x,
+            entity.position.y,
+            entity.position.z,
+        );
+        self.entities.insert(entity.id.clone(), entity);
+    }
+
+    /// Remove an entity and its physics body from the world.
+    ///
+    /// Objects other than characters' human entities are stored in the database,
+    /// so their removal is recorded for the next save.
+    pub fn remove_entity(&mut self, entity_id: &str) -> Option<Entity> {
+        let entity = self.entities.remove(entity_id)?;
+        self.physics.remove_entity(entity_id);
+        if entity.entity_type != EntityType::human() {
+            self.removed_entities.push(entity_id.to_string());
+        }
+        Some(entity)
+    }
+
+    /// Objects stored in the database: every entity but characters' human entities.
+    pub fn stored_entities(&self) -> Vec<Entity> {
+        self.entities
+            .values()
+            .filter(|entity| entity.entity_type != EntityType::human())
+            .cloned()
+            .collect()
+    }
+
+    /// Inventories to store: those of containers and of characters other than guests.
+    ///
+    /// Guests are evicted and never come back, so nothing of theirs is stored.
+    pub fn stored_inventories(&self) -> Vec<(String, Inventory)> {
+        self.inventories
+            .iter()
+            .filter(|(owner_id, _)| !self.players.get(*owner_id).is_some_and(|p| p.guest))
+            .map(|(owner_id, inventory)| (owner_id.clone(), inventory.clone()))
+            .collect()
+    }
+
+    /// Take the relationships changed since the last call, for persisting,
+    /// leaving out those of guests.
+    pub fn take_unsaved_relationships(&mut self) -> Vec<(String, String, Relationship)> {
+        let guest = |id: &str| self.players.get(id).is_some_and(|p| p.guest);
+        self.relationships
+            .take_unsaved()
+            .into_iter()
+            .filter(|(a, b, _)| !guest(a) && !guest(b))
+            .collect()
+    }
+
+    /// Take the IDs of stored objects removed since the last call, for persisting.
+    pub fn take_removed_entities(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.removed_entities)
+    }
+
+    /// Mark removals that failed to persist as unsaved again.
+    pub fn return_removed_entities(&mut self, mut entity_ids: Vec<String>) {
+        entity_ids.append(&mut self.removed_entities);
+        self.removed_entities = entity_ids;
+    }
+
+    /// Put back objects loaded from the database that the level does not place
+    /// itself, such as deliveries and dropped items.
+    ///
+    /// Objects already in the world (placed by the level) are left alone; their
+    /// stored properties come back through [`GameState::restore_properties`].
+    pub fn restore_entities(&mut self, entities: Vec<Entity>) {
+        for entity in entities {
+            if self.entities.contains_key(&entity.id) || entity.entity_type == EntityType::human() {
+                continue;
+            }
+            self.add_entity(entity);
+        }
+    }
+
+    /// Spawn the objects placed in the level and fill containers with their contents.
+    fn spawn_placements(&mut self) {
+        for placement in self.level.objects.clone() {
+            let Some(object_type) = self.catalogue.get(&placement.object_type) else {
+                tracing::warn!(
+                    "Unknown object type '{}' for placed object {}",
+                    placement.object_type,
+                    placement.id
+                );
+                continue;
+            };
+            let [x, y, z] = placement.position;
+            let half_height = object_type.shape.half_height();
+            self.add_entity(Entity {
+                id: placement.id.clone(),
+                entity_type: EntityType::new(&placement.object_type),
+                position: Position {
+                    x,
+                    y: y + half_height,
+                    z,
+                },
+                rotation: Rotation {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+                owner: placement.owner.clone(),
+                properties: Properties::new(),
+            });
+            for (item_type, count) in placement.contents {
+                let stack = ItemStack {
+                    item_type,
+                    count,
+                    owner: placement.owner.clone(),
+                    properties: Properties::new(),
+                };
+                if let Err(e) = self.add_items(&placement.id, stack) {
+                    tracing::warn!("Contents of {} do not fit: {e}", placement.id);
+                }
+            }
+        }
+    }
+
+    /// Empty inventory for an owner: a character, or a container entity.
+    ///
+    /// IDs that are not entities are taken to be characters, so inventories of
+    /// characters that are not in the world right now can be restored.
+    fn empty_inventory(&self, owner_id: &str) -> Option<Inventory> {
+        let Some(entity) = self.entities.get(owner_id) else {
+            return Some(Inventory::character());
+        };
+        let container = self
+            .catalogue
+            .get(entity.entity_type.as_str())?
+            .container
+            .as_ref()?;
+        Some(Inventory::new(container.slots, container.max_weight))
+    }
+
+    /// Contents of a character's or container's inventory.
+    pub fn inventory(&self, owner_id: &str) -> Option<Inventory> {
+        match self.inventories.get(owner_id) {
+            Some(inventory) => Some(inventory.clone()),
+            None => self.empty_inventory(owner_id),
+        }
+    }
+
+    /// Add items to an inventory.
+    fn add_items(&mut self, owner_id: &str, stack: ItemStack) -> Result<(), String> {
+        let empty = self
+            .empty_inventory(owner_id)
+            .ok_or_else(|| format!("{owner_id} cannot hold items"))?;
+        self.inventories
+            .entry(owner_id.to_string())
+            .or_insert(empty)
+            .add(stack, &self.catalogue)
+    }
+
+    /// Put back inventories loaded from the database: (owner ID, slot, stack).
+    ///
+    /// Only inventories of characters and containers in this zone are taken.
+    pub fn restore_inventories(&mut self, rows: Vec<(String, usize, ItemStack)>) {
+        let mut by_owner: HashMap<String, Vec<(usize, ItemStack)>> = HashMap::new();
+        for (owner_id, slot, stack) in rows {
+            by_owner.entry(owner_id).or_default().push((slot, stack));
+        }
+        for (owner_id, stacks) in by_owner {
+            if !self.players.contains_key(&owner_id) && !self.entities.contains_key(&owner_id) {
+                // Kept by the zone the owner is in
+                tracing::debug!("Stored inventory of {owner_id} has no owner in this level");
+                continue;
+            }
+            let Some(mut inventory) = self.empty_inventory(&owner_id) else {
+                tracing::warn!("Dropped stored inventory of {owner_id}, which cannot hold items");
+                continue;
+            };
+            for (slot, stack) in inventory.restore(stacks) {
+                tracing::warn!("Dropped stored {stack:?} in slot {slot} of {owner_id}");
+            }
+            self.inventories.insert(owner_id, inventory);
+        }
+    }
+
+    /// Entity within reach of a player's character.
+    fn entity_in_reach(&self, player_id: &str, entity_id: &str) -> Result<&Entity, String> {
+        let player = self
+            .players
+            .get(player_id)
+            .ok_or_else(|| "unknown player".to_string())?;
+        let entity = self
+            .entities
+            .get(entity_id)
+            .ok_or_else(|| format!("no such object {entity_id}"))?;
+        if distance(&entity.position, &player.position) > REACH_DISTANCE {
+            return Err(format!("{entity_id} is out of reach"));
+        }
+        Ok(entity)
+    }
+
+    /// Move items between two inventories, all or nothing.
+    ///
+    /// Items owned by someone other than `player_id` cannot be moved; unowned
+    /// items taken into the player's own inventory become theirs.
+    fn transfer_items(
+        &mut self,
+        player_id: &str,
+        from: &str,
+        slot: usize,
+        count: u32,
+        to: &str,
+    ) -> Result<(), String> {
+        let mut source = self
+            .inventory(from)
+            .ok_or_else(|| format!("{from} cannot hold items"))?;
+        let mut target = self
+            .inventory(to)
+            .ok_or_else(|| format!("{to} cannot hold items"))?;
+        let mut stack = source.remove(slot, count)?;
+        check_item_owner(player_id, &stack)?;
+        if to == player_id {
+            stack.owner.get_or_insert_with(|| player_id.to_string());
+        }
+        target.add(stack, &self.catalogue)?;
+        self.inventories.insert(from.to_string(), source);
+        self.inventories.insert(to.to_string(), target);
+        Ok(())
+    }
+
+    /// Pick an item up from the world into the character's inventory.
+    pub fn pick_up(&mut self, player_id: &str, entity_id: &str) -> Result<(), String> {
+        let entity = self.entity_in_reach(player_id, entity_id)?;
+        if !self
+            .catalogue
+            .get(entity.entity_type.as_str())
+            .is_some_and(|t| t.is_item())
+        {
+            return Err(format!("{entity_id} cannot be picked up"));
+        }
+        let stack = ItemStack {
+            item_type: entity.entity_type.as_str().to_string(),
+            count: 1,
+            owner: Some(
+                entity
+                    .owner
+                    .clone()
+                    .unwrap_or_else(|| player_id.to_string()),
+            ),
+            properties: entity.properties.clone(),
+        };
+        check_item_owner(player_id, &stack)?;
+        self.add_items(player_id, stack)?;
+        self.remove_entity(entity_id);
+        Ok(())
+    }
+
+    /// Drop items from an inventory slot at the character's feet, just ahead.
+    pub fn drop_items(&mut self, player_id: &str, slot: usize, count: u32) -> Result<(), String> {
+        let player = self
+            .players
+            .get(player_id)
+            .ok_or_else(|| "unknown player".to_string())?;
+        let position = Position {
+            x: player.position.x + DROP_DISTANCE * player.rotation.sin(),
+            y: player.position.y,
+            z: player.position.z + DROP_DISTANCE * player.rotation.cos(),
+        };
+        self.place_items(player_id, slot, count, position)
+    }
+
+    /// Put items from an inventory slot down on a point within reach.
+    pub fn place_items(
+        &mut self,
+        player_id: &str,
+        slot: usize,
+        count: u32,
+        position: Position,
+    ) -> Result<(), String> {
+        let player = self
+            .players
+            .get(player_id)
+            .ok_or_else(|| "unknown player".to_string())?;
+        if distance(&player.position, &position) > REACH_DISTANCE {
+            return Err("that place is out of reach".to_string());
+        }
+        let bounds = &self.level.bounds;
+        if !(bounds.min[0]..=bounds.max[0]).contains(&position.x)
+            || !(bounds.min[1]..=bounds.max[1]).contains(&position.z)
+        {
+            return Err("that place is outside the level".to_string());
+        }
+        let mut inventory = self
+            .inventory(player_id)
+            .ok_or_else(|| "no inventory".to_string())?;
+        let stack = inventory.remove(slot, count)?;
+        self.inventories.insert(player_id.to_string(), inventory);
+        self.set_out(&stack, position);
+        Ok(())
+    }
+
+    /// Turn a stack into entities standing on a point; several items are piled
+    /// up and left to settle.
+    fn set_out(&mut self, stack: &ItemStack, position: Position) {
+        let height = self
+            .catalogue
+            .get(&stack.item_type)
+            .map_or(0.0, |t| 2.0 * t.shape.half_height());
+        for i in 0..stack.count {
+            let id = format!("{}_{}", stack.item_type, uuid::Uuid::new_v4().simple());
+            self.add_entity(Entity {
+                id,
+                entity_type: EntityType::new(&stack.item_type),
+                position: Position {
+                    x: position.x,
+                    y: position.y + height * (i as f32 + 0.5) + 0.01,
+                    z: position.z,
+                },
+                rotation: Rotation {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+                owner: stack.owner.clone(),
+                properties: stack.properties.clone(),
+            });
+        }
+    }
+
+    /// Move items from the character's inventory into a container within reach.
+    pub fn store_items(
+        &mut self,
+        player_id: &str,
+        slot: usize,
+        count: u32,
+        container_id: &str,
+    ) -> Result<(), String> {
+        self.container_in_reach(player_id, container_id)?;
+        self.transfer_items(player_id, player_id, slot, count, container_id)
+    }
+
+    /// Move items from a container within reach into the character's inventory.
+    pub fn take_items(
+        &mut self,
+        player_id: &str,
+        container_id: &str,
+        slot: usize,
+        count: u32,
+    ) -> Result<(), String> {
+        self.container_in_reach(player_id, container_id)?;
+        self.transfer_items(player_id, container_id, slot, count, player_id)
+    }
+
+    /// Inventory a player may look into: their own, or a container within reach.
+    pub fn view_inventory(&self, player_id: &str, owner_id: &str) -> Result<Inventory, String> {
+        if owner_id != player_id {
+            self.container_in_reach(player_id, owner_id)?;
+        } else if !self.players.contains_key(player_id) {
+            return Err("unknown player".to_string());
+        }
+        self.inventory(owner_id)
+            .ok_or_else(|| format!("{owner_id} cannot hold items"))
+    }
+
+    /// Check that a container is within reach and that the player may open it.
+    fn container_in_reach(&self, player_id: &str, container_id: &str) -> Result<(), String> {
+        let entity = self.entity_in_reach(player_id, container_id)?;
+        let container = self
+            .catalogue
+            .get(entity.entity_type.as_str())
+            .and_then(|t| t.container.as_ref());
+        if container.is_none() {
+            return Err(format!("{container_id} is not a container"));
+        }
+        match &entity.owner {
+            Some(owner) if owner != player_id => Err(format!("{container_id} belongs to {owner}")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Buy objects from the shop, paid now and delivered to the level's
+    /// delivery point after [`DELIVERY_MINUTES`].
+    pub fn purchase(
+        &mut self,
+        player_id: &str,
+        mut lines: Vec<OrderLine>,
+    ) -> Result<Order, String> {
+        if !self.players.contains_key(player_id) {
+            return Err("unknown player".to_string());
+        }
+        let total = economy::quote(&self.catalogue, &lines)?;
+        for line in &mut lines {
+            line.unit_price = self
+                .catalogue
+                .get(&line.object_type)
+                .and_then(|t| t.price)
+                .unwrap_or(0);
+        }
+        let now = self.needs_tick_minute;
+        let order = Order {
+            id: uuid::Uuid::new_v4().to_string(),
+            player_id: player_id.to_string(),
+            lines,
+            total,
+            placed_at: now,
+            deliver_at: now + DELIVERY_MINUTES,
+        };
+        let description = order
+            .lines
+            .iter()
+            .map(|line| format!("{} x{}", line.object_type, line.count))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.ledger
+            .pay(player_id, total, description, Some(order.id.clone()), now)?;
+        tracing::info!(
+            "Player {player_id} ordered {} for {}",
+            order.id,
+            economy::format_cents(total)
+        );
+        self.orders.place(order.clone());
+        Ok(order)
+    }
+
+    /// Spawn the goods of every order due by `now` at the delivery point.
+    ///
+    /// Object types that left the catalogue since the order are refunded.
+    fn deliver_orders(&mut self, now: i64) {
+        for order in self.orders.take_due(now) {
+            let [x0, y0, z0] = self.level.delivery_point.unwrap_or_else(|| {
+                let bounds = &self.level.bounds;
+                [
+                    (bounds.min[0] + bounds.max[0]) / 2.0,
+                    0.0,
+                    (bounds.min[1] + bounds.max[1]) / 2.0,
+                ]
+            });
+            // Goods are set out in rows along +X, rows stepping towards -Z
+            let (mut x, mut z, mut row_depth) = (0.0_f32, 0.0_f32, 0.0_f32);
+            let mut entity_ids = Vec::new();
+            for line in &order.lines {
+                let Some(object_type) = self.catalogue.get(&line.object_type).cloned() else {
+                    let refund = self.ledger.refund(
+                        &order.player_id,
+                        line.unit_price * line.count as i64,
+                        format!("{} no longer sold", line.object_type),
+                        Some(order.id.clone()),
+                        now,
+                    );
+                    if refund.is_none() {
+                        tracing::warn!(
+                            "No account here to refund order {} of {}",
+                            order.id,
+                            order.player_id
+                        );
+                    }
+                    continue;
+                };
+                let half_width = object_type.shape.half_width();
+                for _ in 0..line.count {
+                    if x > 0.0 && x + 2.0 * half_width > DELIVERY_ROW_LENGTH {
+                        (x, z, row_depth) = (0.0, z - row_depth - DELIVERY_SPACING, 0.0);
+                    }
+                    let id = format!("{}_{}", line.object_type, uuid::Uuid::new_v4().simple());
+                    self.add_entity(Entity {
+                        id: id.clone(),
+                        entity_type: EntityType::new(&line.object_type),
+                        position: Position {
+                            x: x0 + x + half_width,
+                            y: y0 + object_type.shape.half_height() + 0.01,
+                            z: z0 + z - half_width,
+                        },
+                        rotation: Rotation {
+                            x: 0.0,
+                            y: 0.0,
+                            z: 0.0,
+                        },
+                        owner: Some(order.player_id.clone()),
+                        properties: Properties::new(),
+                    });
+                    entity_ids.push(id);
+                    x += 2.0 * half_width + DELIVERY_SPACING;
+                    row_depth = row_depth.max(2.0 * half_width);
+                }
+            }
+            tracing::info!(
+                "Delivered order {} ({} objects)",
+                order.id,
+                entity_ids.len()
+            );
+            self.orders.mark_delivered(&order.id, now);
+            self.journal(
+                &order.player_id,
+                now,
+                format!("a delivery of {} objects arrived", entity_ids.len()),
+            );
+            self.deliveries.push(Delivery { order, entity_ids });
+        }
+    }
+
+    /// Take the deliveries made since the last call, for broadcasting.
+    pub fn take_deliveries(&mut self) -> Vec<Delivery> {
+        std::mem::take(&mut self.deliveries)
+    }
+
+    /// Add a recipe characters can cook, if it only uses known items.
+    pub fn add_recipe(&mut self, recipe: Recipe) {
+        match recipe.check(&self.catalogue) {
+            Ok(()) => {
+                self.recipes.insert(recipe.name.clone(), recipe);
+            }
+            Err(e) => tracing::warn!("Ignoring recipe: {e}"),
+        }
+    }
+
+    /// Every recipe, sorted by name.
+    pub fn recipes(&self) -> Vec<Recipe> {
+        let mut recipes: Vec<Recipe> = self.recipes.values().cloned().collect();
+        recipes.sort_by(|a, b| a.name.cmp(&b.name));
+        recipes
+    }
+
+    /// Cook a recipe on an appliance within reach with ingredients from the
+    /// character's inventory.
+    ///
+    /// The ingredients are used up when the Cooking activity starts, and the meal
+    /// lands in the cook's inventory if it runs to the end.
+    pub fn cook(&mut self, player_id: &str, appliance_id: &str, recipe_name: &str) -> Decision {
+        let refuse = |reason: String| Decision::Refuse { reason };
+        let Some(recipe) = self.recipes.get(recipe_name) else {
+            return refuse(format!("no recipe for {recipe_name}"));
+        };
+        let appliance = match self.entity_in_reach(player_id, appliance_id) {
+            Ok(appliance) => appliance,
+            Err(reason) => return refuse(reason),
+        };
+        if !self
+            .catalogue
+            .get(appliance.entity_type.as_str())
+            .is_some_and(|t| t.has_tag(&recipe.appliance))
+        {
+            return refuse(format!("{recipe_name} is cooked on a {}", recipe.appliance));
+        }
+        let cooking = self
+            .players
+            .get(player_id)
+            .and_then(|p| p.timed_activity.as_ref())
+            .is_some_and(|timed| timed.activity == Activity::Cooking);
+        if cooking {
+            return refuse("already cooking".to_string());
+        }
+        let inventory = self
+            .inventory(player_id)
+            .unwrap_or_else(Inventory::character);
+        if let Some(missing) = recipe
+            .ingredients
+            .iter()
+            .find(|ingredient| inventory.count(&ingredient.item) < ingredient.count)
+        {
+            return refuse(format!(
+                "{recipe_name} needs {} {}",
+                missing.count, missing.item
+            ));
+        }
+
+        self.cooking.insert(
+            player_id.to_string(),
+            Cooking {
+                recipe: recipe_name.to_string(),
+                started: false,
+                spoiled: false,
+            },
+        );
+        let decision = self.request_activity_with(
+            player_id,
+            Activity::Cooking,
+            Some(appliance_id.to_string()),
+        );
+        if matches!(decision, Decision::Refuse { .. }) {
+            self.cooking.remove(player_id);
+        }
+        decision
+    }
+
+    /// Take the ingredients of the meal a character is about to cook.
+    ///
+    /// Returns the cooking time, or None when there is no meal to cook.
+    fn start_recipe(&mut self, player_id: &str) -> Option<i64> {
+        let cooking = self.cooking.get(player_id)?;
+        let recipe = self.recipes.get(&cooking.recipe)?.clone();
+        let mut inventory = self.inventory(player_id)?;
+        let mut spoiled = false;
+        for ingredient in &recipe.ingredients {
+            match inventory.take(&ingredient.item, ingredient.count) {
+                Ok(stacks) => {
+                    spoiled |= stacks
+                        .iter()
+                        .any(|stack| food::is_spoiled(self.stack_freshness(stack)));
+                }
+                Err(reason) => {
+                    self.cooking.remove(player_id);
+                    let now = self.needs_tick_minute;
+                    self.journal(
+                        player_id,
+                        now,
+                        format!("could not cook {}: {reason}", recipe.name),
+                    );
+                    return None;
+                }
+            }
+        }
+        self.inventories.insert(player_id.to_string(), inventory);
+        self.cooking.insert(
+            player_id.to_string(),
+            Cooking {
+                recipe: recipe.name,
+                started: true,
+                spoiled,
+            },
+        );
+        Some(recipe.minutes)
+    }
+
+    /// Hand the cook their meal when cooking completed; an interrupted meal is lost.
+    ///
+    /// A meal that does not fit in the cook's inventory is set out on the appliance.
+    fn finish_recipe(&mut self, player_id: &str, completed: bool, appliance_id: Option<String>) {
+        let Some(cooking) = self.cooking.remove(player_id).filter(|c| c.started) else {
+            return;
+        };
+        let Some(recipe) = self.recipes.get(&cooking.recipe).cloned() else {
+            return;
+        };
+        let now = self.needs_tick_minute;
+        if !completed {
+            self.journal(player_id, now, format!("ruined the {}", recipe.name));
+            return;
+        }
+        let mut properties = Properties::new();
+        if cooking.spoiled {
+            properties.insert(food::FRESHNESS.to_string(), PropertyValue::Float(0.0));
+        }
+        let meal = ItemStack {
+            item_type: recipe.output.item,
+            count: recipe.output.count,
+            owner: Some(player_id.to_string()),
+            properties,
+        };
+        self.journal(player_id, now, format!("cooked {}", recipe.name));
+        if let Err(reason) = self.add_items(player_id, meal.clone()) {
+            tracing::debug!("{player_id} has no room for {}: {reason}", meal.item_type);
+            let on_appliance =
+                appliance_id
+                    .and_then(|id| self.entities.get(&id))
+                    .map(|appliance| {
+                        let half_height = self
+                            .catalogue
+                            .get(appliance.entity_type.as_str())
+                            .map_or(0.0, |t| t.shape.half_height());
+                        Position {
+                            y: appliance.position.y + half_height,
+                            ..appliance.position.clone()
+                        }
+                    });
+            let position =
+                on_appliance.or_else(|| self.players.get(player_id).map(|p| p.position.clone()));
+            if let Some(position) = position {
+                self.set_out(&meal, position);
+            }
+        }
+    }
+
+    /// Freshness of items in a stack (1.0 for food that keeps).
+    fn stack_freshness(&self, stack: &ItemStack) -> f64 {
+        stack
+            .properties
+            .get(food::FRESHNESS)
+            .or_else(|| {
+                self.catalogue
+                    .get(&stack.item_type)?
+                    .properties
+                    .get(food::FRESHNESS)
+            })
+            .and_then(|value| value.as_float())
+            .unwrap_or(1.0)
+    }
+
+    /// Food a character may eat: the object itself when it is food, otherwise the
+    /// best food in their inventory, then in containers within reach, then lying
+    /// within reach. Unspoiled and more nourishing food is preferred.
+    fn find_food(&self, player_id: &str, object_id: Option<&str>) -> Option<FoodSource> {
+        let player = self.players.get(player_id)?;
+        let is_food = |item_type: &str| {
+            self.catalogue
+                .get(item_type)
+                .and_then(|t| t.food.as_ref())
+                .map(|food| food.nutrition)
+        };
+        let may_eat = |owner: &Option<String>| owner.as_ref().is_none_or(|o| o == player_id);
+        let best = |candidates: Vec<(FoodSource, f64, f32)>| {
+            candidates
+                .into_iter()
+                .max_by(|a, b| {
+                    let key = |c: &(FoodSource, f64, f32)| (!food::is_spoiled(c.1), c.2);
+                    key(a)
+                        .partial_cmp(&key(b))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .map(|(source, _, _)| source)
+        };
+        let entity_freshness = |id: &str| {
+            self.property(id, food::FRESHNESS)
+                .and_then(|value| value.as_float())
+                .unwrap_or(1.0)
+        };
+
+        if let Some(object) = object_id.and_then(|id| self.entities.get(id)) {
+            if is_food(object.entity_type.as_str()).is_some() && may_eat(&object.owner) {
+                return Some(FoodSource::Entity(object.id.clone()));
+            }
+        }
+        let slots = |owner_id: &str| -> Vec<(FoodSource, f64, f32)> {
+            self.inventories
+                .get(owner_id)
+                .map_or(Vec::new(), |inventory| {
+                    inventory
+                        .stacks()
+                        .filter(|(_, stack)| may_eat(&stack.owner))
+                        .filter_map(|(slot, stack)| {
+                            let nutrition = is_food(&stack.item_type)?;
+                            let source = FoodSource::Slot(owner_id.to_string(), slot);
+                            Some((source, self.stack_freshness(stack), nutrition))
+                        })
+                        .collect()
+                })
+        };
+        if let Some(source) = best(slots(player_id)) {
+            return Some(source);
+        }
+        let in_reach: Vec<&Entity> = self
+            .entities
+            .values()
+            .filter(|e| distance(&e.position, &player.position) <= REACH_DISTANCE)
+            .collect();
+        let containers = in_reach
+            .iter()
+            .filter(|e| self.container_in_reach(player_id, &e.id).is_ok())
+            .flat_map(|e| slots(&e.id))
+            .collect();
+        if let Some(source) = best(containers) {
+            return Some(source);
+        }
+        let loose = in_reach
+            .iter()
+            .filter(|e| may_eat(&e.owner))
+            .filter_map(|e| {
+                let nutrition = is_food(e.entity_type.as_str())?;
+                Some((
+                    FoodSource::Entity(e.id.clone()),
+                    entity_freshness(&e.id),
+                    nutrition,
+                ))
+            })
+            .collect();
+        best(loose)
+    }
+
+    /// Nearest container holding food the character may eat, or food lying
+    /// around, within [`npc::SEARCH_RADIUS`].
+    fn food_to_walk_to(&self, player_id: &str) -> Option<String> {
+        let player = self.players.get(player_id)?;
+        let may_eat = |owner: &Option<String>| owner.as_ref().is_none_or(|o| o == player_id);
+        let is_food = |item_type: &str| {
+            self.catalogue
+                .get(item_type)
+                .is_some_and(|t| t.food.is_some())
+        };
+        self.entities
+            .values()
+            .filter(|e| may_eat(&e.owner))
+            .filter(|e| {
+                is_food(e.entity_type.as_str())
+                    || self.inventories.get(&e.id).is_some_and(|inventory| {
+                        inventory
+                            .stacks()
+                            .any(|(_, stack)| may_eat(&stack.owner) && is_food(&stack.item_type))
+                    })
+            })
+            .map(|e| (e, distance(&e.position, &player.position)))
+            .filter(|(_, d)| *d <= npc::SEARCH_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(e, _)| e.id.clone())
+    }
+
+    /// Eat one serving: the character drinks its hydration at once and eats for
+    /// as long as it takes to relieve its nutrition.
+    ///
+    /// Returns how long the meal takes, or None if the food is gone.
+    fn serve_food(&mut self, player_id: &str, source: &FoodSource) -> Option<i64> {
+        let (item_type, freshness) = match source {
+            FoodSource::Entity(entity_id) => {
+                let freshness = self
+                    .property(entity_id, food::FRESHNESS)
+                    .and_then(|value| value.as_float())
+                    .unwrap_or(1.0);
+                let entity = self.remove_entity(entity_id)?;
+                (entity.entity_type.as_str().to_string(), freshness)
+            }
+            FoodSource::Slot(owner_id, slot) => {
+                let mut inventory = self.inventory(owner_id)?;
+                let stack = inventory.remove(*slot, 1).ok()?;
+                self.inventories.insert(owner_id.clone(), inventory);
+                let freshness = self.stack_freshness(&stack);
+                (stack.item_type, freshness)
+            }
+        };
+        let food = self.catalogue.get(&item_type)?.food.clone()?;
+        let (nutrition, hydration) = food::serving(&food, freshness);
+        let player = self.players.get_mut(player_id)?;
+        player.needs.adjust(NeedRates {
+            thirst: -hydration,
+            ..NeedRates::default()
+        });
+        let spoiled = if food::is_spoiled(freshness) {
+            " (spoiled)"
+        } else {
+            ""
+        };
+        let now = self.needs_tick_minute;
+        self.journal(player_id, now, format!("ate {item_type}{spoiled}"));
+        if food::is_spoiled(freshness) && rand::thread_rng().gen_bool(health::FOOD_POISONING_CHANCE)
+        {
+            if let Some(player) = self.players.get_mut(player_id) {
+                player.health.catch(IllnessKind::FoodPoisoning, now);
+            }
+        }
+        Some(food::eating_minutes(nutrition))
+    }
+
+    /// Age perishable food by some game minutes, in the world and in inventories.
+    fn spoil_food(&mut self, minutes: i64) {
+        let mut changes = Vec::new();
+        for entity in self.entities.values() {
+            let Some(food) = self
+                .catalogue
+                .get(entity.entity_type.as_str())
+                .and_then(|t| t.food.as_ref())
+            else {
+                continue;
+            };
+            let Some(freshness) = self
+                .property(&entity.id, food::FRESHNESS)
+                .and_then(|value| value.as_float())
+            else {
+                continue;
+            };
+            let after = food::spoil(freshness, food, minutes, false);
+            if after != freshness {
+                changes.push((entity.id.clone(), after));
+            }
+        }
+        for (entity_id, freshness) in changes {
+            if let Err(e) =
+                self.set_property(&entity_id, food::FRESHNESS, PropertyValue::Float(freshness))
+            {
+                tracing::warn!("Could not spoil {entity_id}: {e}");
+            }
+        }
+
+        for (owner_id, inventory) in self.inventories.iter_mut() {
+            let cold = self
+                .entities
+                .get(owner_id)
+                .and_then(|e| self.catalogue.get(e.entity_type.as_str()))
+                .is_some_and(|t| t.has_tag(food::COLD_TAG));
+            for stack in inventory.slots.iter_mut().flatten() {
+                let Some(object_type) = self.catalogue.get(&stack.item_type) else {
+                    continue;
+                };
+                let Some(food) = &object_type.food else {
+                    continue;
+                };
+                let Some(freshness) = stack
+                    .properties
+                    .get(food::FRESHNESS)
+                    .or_else(|| object_type.properties.get(food::FRESHNESS))
+                    .and_then(|value| value.as_float())
+                else {
+                    continue;
+                };
+                let after = food::spoil(freshness, food, minutes, cold);
+                stack
+                    .properties
+                    .insert(food::FRESHNESS.to_string(), PropertyValue::Float(after));
+            }
+        }
+    }
+
+    /// Value of an entity's property, falling back to its type's default.
+    pub fn property(&self, entity_id: &str, name: &str) -> Option<PropertyValue> {
+        let entity = self.entities.get(entity_id)?;
+        entity.properties.get(name).cloned().or_else(|| {
+            self.catalogue
+                .get(entity.entity_type.as_str())?
+                .properties
+                .get(name)
+                .cloned()
+        })
+    }
+
+    /// First of the given flags an entity does not match, ignoring flags its type
+    /// does not declare (a console without an "on" switch is always ready).
+    fn unmet_flag(
+        &self,
+        entity_id: &str,
+        flags: &'static [(&'static str, bool)],
+    ) -> Option<(&'static str, bool)> {
+        flags.iter().copied().find(|(name, wanted)| {
+            self.property(entity_id, name)
+                .and_then(|value| value.as_bool())
+                .is_some_and(|value| value != *wanted)
+        })
+    }
+
+    /// Set a property of an entity after checking it against the entity type,
+    /// and record the change for clients.
+    fn set_property(
+        &mut self,
+        entity_id: &str,
+        name: &str,
+        value: PropertyValue,
+    ) -> Result<(), String> {
+        let entity = self
+            .entities
+            .get(entity_id)
+            .ok_or_else(|| format!("no entity {entity_id}"))?;
+        let object_type = self
+            .catalogue
+            .get(entity.entity_type.as_str())
+            .ok_or_else(|| format!("unknown type {}", entity.entity_type.as_str()))?;
+        properties::check(object_type, name, &value)?;
+        if self.property(entity_id, name).as_ref() == Some(&value) {
+            return Ok(());
+        }
+        if let Some(entity) = self.entities.get_mut(entity_id) {
+            entity.properties.insert(name.to_string(), value.clone());
+        }
+        self.property_changes.push(PropertyChange {
+            entity_id: entity_id.to_string(),
+            name: name.to_string(),
+            value,
+        });
+        Ok(())
+    }
+
+    /// Take the property changes made since the last call, for broadcasting.
+    pub fn take_property_changes(&mut self) -> Vec<PropertyChange> {
+        std::mem::take(&mut self.property_changes)
+    }
+
+    /// Put back entity properties loaded from the database, dropping any that
+    /// no longer match the entity's type.
+    pub fn restore_properties(&mut self, rows: Vec<(String, Properties)>) {
+        for (entity_id, properties) in rows {
+            let Some(entity) = self.entities.get_mut(&entity_id) else {
+                continue;
+            };
+            let Some(object_type) = self.catalogue.get(entity.entity_type.as_str()) else {
+                continue;
+            };
+            for (name, value) in properties {
+                match properties::check(object_type, &name, &value) {
+                    Ok(()) => {
+                        entity.properties.insert(name, value);
+                    }
+                    Err(e) => tracing::warn!("Dropping stored property of {entity_id}: {e}"),
+                }
+            }
+        }
+    }
+
+    /// Use an object within reach with one of the verbs its type affords.
+    ///
+    /// Checks the object's state (e.g. a stove must be on to cook), then changes
+    /// it, adjusts the character's needs and starts the verb's activity with the
+    /// object, subject to the character's mood as for [`GameState::request_activity`].
+    pub fn interact(&mut self, player_id: &str, entity_id: &str, verb_name: &str) -> Decision {
+        let refuse = |reason: String| Decision::Refuse { reason };
+        let entity = match self.entity_in_reach(player_id, entity_id) {
+            Ok(entity) => entity,
+            Err(reason) => return refuse(reason),
+        };
+        let affords = self
+            .catalogue
+            .get(entity.entity_type.as_str())
+            .is_some_and(|t| t.has_verb(verb_name));
+        let Some(verb) = interaction::verb(verb_name).filter(|_| affords) else {
+            return refuse(format!(
+                "cannot {verb_name} a {}",
+                entity.entity_type.as_str()
+            ));
+        };
+        // Objects someone else owns are theirs to use; characters are talked to
+        // whoever they are
+        let talk = matches!(verb, Verb::Special(SpecialVerb::Talk));
+        if let Some(owner) = entity.owner.as_ref().filter(|o| *o != player_id && !talk) {
+            return refuse(format!("{entity_id} belongs to {owner}"));
+        }
+
+        let affordance = match verb {
+            Verb::Affordance(affordance) => affordance,
+            Verb::Special(SpecialVerb::PickUp) => {
+                return match self.pick_up(player_id, entity_id) {
+                    Ok(()) => Decision::Allow,
+                    Err(reason) => refuse(reason),
+                };
+            }
+            Verb::Special(SpecialVerb::Talk) => return self.talk(player_id, entity_id),
+            Verb::Special(SpecialVerb::Throw) => return self.throw(player_id, entity_id),
+        };
+        if let Some((name, wanted)) = self.unmet_flag(entity_id, affordance.requires) {
+            let state = if wanted { "" } else { "not " };
+            return refuse(format!("{entity_id} must {state}be {name}"));
+        }
+        let object_type = self.catalogue.get(entity.entity_type.as_str());
+        for (name, value) in affordance.sets {
+            let checked = object_type
+                .ok_or_else(|| "unknown type".to_string())
+                .and_then(|t| properties::check(t, name, &PropertyValue::Bool(*value)));
+            if let Err(reason) = checked {
+                return refuse(reason);
+            }
+        }
+
+        let decision = match &affordance.activity {
+            Some(activity) => {
+                self.request_activity_with(player_id, activity.clone(), Some(entity_id.to_string()))
+            }
+            None => Decision::Allow,
+        };
+        if matches!(decision, Decision::Refuse { .. }) {
+            return decision;
+        }
+        for (name, value) in affordance.sets {
+            // Checked above
+            let _ = self.set_property(entity_id, name, PropertyValue::Bool(*value));
+        }
+        if let Some(player) = self.players.get_mut(player_id) {
+            player.needs.adjust(affordance.needs);
+        }
+        decision
+    }
+
+    /// Chat with the character whose human entity is `entity_id`.
+    fn talk(&mut self, player_id: &str, entity_id: &str) -> Decision {
+        let Some(other_id) = entity_id.strip_prefix("human_") else {
+            return Decision::Refuse {
+                reason: format!("{entity_id} cannot talk"),
+            };
+        };
+        if other_id == player_id {
+            return Decision::Refuse {
+                reason: "talking to yourself".to_string(),
+            };
+        }
+        self.socialize(player_id, other_id, SocialAction::Chat)
+    }
+
+    /// Chat with, argue with or hug another character standing close by.
+    ///
+    /// Changes the relationship of both characters and how they feel.
+    pub fn socialize(&mut self, player_id: &str, other_id: &str, action: SocialAction) -> Decision {
+        let refuse = |reason: String| Decision::Refuse { reason };
+        if player_id == other_id {
+            return refuse("no one to socialize with".to_string());
+        }
+        let (Some(player), Some(other)) = (self.players.get(player_id), self.players.get(other_id))
+        else {
+            return refuse(format!("{other_id} is not here"));
+        };
+        if distance(&player.position, &other.position) > relationship::SOCIAL_DISTANCE {
+            return refuse(format!("{} is too far away", other.username));
+        }
+        if other.activity == Activity::Sleeping {
+            return refuse(format!("{} is asleep", other.username));
+        }
+        let now = self.needs_tick_minute;
+        if let Err(reason) = self.relationships.apply(player_id, other_id, action, now) {
+            return refuse(reason);
+        }
+        let event = action.mood_event();
+        let mut journal = Vec::new();
+        for (id, with) in [(player_id, other_id), (other_id, player_id)] {
+            let name = self.players.get(with).map(|p| p.username.clone());
+            if let (Some(player), Some(name)) = (self.players.get_mut(id), name) {
+                player.mood.apply_event(&event);
+                journal.push((
+                    id.to_string(),
+                    format!("{} with {name}", action.past_tense()),
+                ));
+            }
+        }
+        for (id, entry) in journal {
+            self.journal(&id, now, entry);
+        }
+        Decision::Allow
+    }
+
+    /// Send a chat message, returning it with the IDs of the players who get it.
+    ///
+    /// The sender always gets their own message back; players who blocked the
+    /// sender do not get it.
+    pub fn send_chat(
+        &mut self,
+        player_id: &str,
+        channel: ChatChannel,
+        text: &str,
+    ) -> Result<(ChatMessage, Vec<String>), String> {
+        let Some(sender) = self.players.get(player_id) else {
+            return Err("unknown player".to_string());
+        };
+        let mut recipients: Vec<String> = match &channel {
+            ChatChannel::Say => self
+                .players
+                .values()
+                .filter(|p| distance(&p.position, &sender.position) <= chat::SAY_DISTANCE)
+                .map(|p| p.id.clone())
+                .collect(),
+            ChatChannel::Whisper { to } => {
+                if !self.players.contains_key(to) {
+                    return Err(format!("{to} is not here"));
+                }
+                vec![to.clone()]
+            }
+            ChatChannel::Group { name } => self.chat.members(name).into_iter().collect(),
+        };
+        let from_name = sender.username.clone();
+        let text = self
+            .chat
+            .review(player_id, &channel, text, std::time::Instant::now())?;
+
+        recipients.retain(|id| id != player_id);
+        let mut recipients = self.chat.unblocked(player_id, recipients);
+        recipients.push(player_id.to_string());
+        let message = ChatMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            from: player_id.to_string(),
+            from_name,
+            channel,
+            text,
+            game_minute: self.needs_tick_minute,
+        };
+        self.chat.record(&message);
+        Ok((message, recipients))
+    }
+
+    /// Let characters socializing close to each other chat, or argue when one of
+    /// them is angry.
+    fn chat_while_socializing(&mut self, now: i64) {
+        let socializing: Vec<(String, Position, f32)> = self
+            .players
+            .values()
+            .filter(|p| p.activity == Activity::Socializing)
+            .map(|p| (p.id.clone(), p.position.clone(), p.mood.anger))
+            .collect();
+        for (i, (a, a_position, a_anger)) in socializing.iter().enumerate() {
+            for (b, b_position, b_anger) in &socializing[i + 1..] {
+                if distance(a_position, b_position) > relationship::SOCIAL_DISTANCE {
+                    continue;
+                }
+                let action = if a_anger.max(*b_anger) >= relationship::ARGUE_ANGER {
+                    SocialAction::Argue
+                } else {
+                    SocialAction::Chat
+                };
+                tracing::debug!("{a} and {b} {} at {now}", action.past_tense());
+                self.socialize(a, b, action);
+            }
+        }
+    }
+
+    /// Throw an object within reach the way the character faces.
+    fn throw(&mut self, player_id: &str, entity_id: &str) -> Decision {
+        let rotation = self.players.get(player_id).map_or(0.0, |p| p.rotation);
+        let (forward, up) = interaction::THROW_SPEED;
+        self.physics.set_linear_velocity(
+            entity_id,
+            forward * rotation.sin(),
+            up,
+            forward * rotation.cos(),
+        );
+        Decision::Allow
+    }
+
+    /// Update an entity's position.
+    ///
+    /// Also updates physics body if the entity is a human.
+    pub fn update_entity_position(&mut self, entity_id: &str, position: Position) {
+        if let Some(entity) = self.entities.get_mut(entity_id) {
+            entity.position = position.clone();
+            // Update physics body for human entities
+            if entity.entity_type == EntityType::human() {
+                self.physics
+                    .update_human_position(entity_id, position.x, position.y, position.z);
+            }
+        }
+    }
+
+    /// Update an entity's rotation.
+    pub fn update_entity_rotation(&mut self, entity_id: &str, rotation: Rotation) {
+        if let Some(entity) = self.entities.get_mut(entity_id) {
+            entity.rotation = rotation;
+        }
+    }
+
+    /// Get a copy of all entities in the game.
+    pub fn get_all_entities(&self) -> Vec<Entity> {
+        self.entities.values().cloned().collect()
+    }
+
+    /// Step the physics simulation and sync entity positions/rotations from physics.
+    ///
+    /// This should be called every frame (60 FPS) to update physics simulation.
+    /// After stepping physics, entity positions and rotations are updated from
+    /// the physics world state.
+    ///
+    /// # Arguments
+    /// * `dt` - Delta time in seconds (typically 1/60.0 for 60 FPS)
+    ///
+    /// # Returns
+    /// Collision and contact events from this step, keyed by entity ID, so game
+    /// rules can react to interactions (e.g. a ball hitting a human).
+    pub fn step_physics(&mut self, dt: f64) -> Vec<PhysicsEvent> {
+        self.follow_routes();
+
+        // Step physics simulation
+        let events = self.physics.step(dt);
+
+        // Sync entity positions and rotations from physics world
+        for entity in self.entities.values_mut() {
+            // Update position from physics
+            if let Some((x, y, z)) = self.physics.get_entity_position(&entity.id) {
+                // Reset balls that have fallen too far below ground (y < -10m)
+                // This prevents balls from falling through the world indefinitely
+                if entity.entity_type.is("ball") && y < -10.0 {
+                    tracing::warn!(
+                        "Ball {} fell below ground (y={}), resetting to y=5",
+                        entity.id,
+                        y
+                    );
+                    // Reset ball position to above ground with random x/z inside the level bounds
+                    let mut rng = rand::thread_rng();
+                    let bounds = &self.level.bounds;
+                    let new_x = rng.gen_range(bounds.min[0]..bounds.max[0]);
+                    let new_z = rng.gen_range(bounds.min[1]..bounds.max[1]);
+                    let new_y = 5.0;
+
+                    // Remove old physics body and create new one
+                    self.physics.remove_entity(&entity.id);
+                    if let Some(object_type) = self.catalogue.get(entity.entity_type.as_str()) {
+                        self.physics.create_object(
+                            entity.id.clone(),
+                            object_type,
+                            new_x,
+                            new_y,
+                            new_z,
+                        );
+                    }
+
+                    entity.position = Position {
+                        x: new_x,
+                        y: new_y,
+                        z: new_z,
+                    };
+                } else {
+                    entity.position = Position { x, y, z };
+                }
+            }
+            // Update rotation from physics
+            if let Some((rx, ry, rz)) = self.physics.get_entity_rotation(&entity.id) {
+                entity.rotation = Rotation {
+                    x: rx,
+                    y: ry,
+                    z: rz,
+                };
+            }
+        }
+
+        // Hard impacts injure characters
+        let now = self.needs_tick_minute;
+        let dt = self.physics.integration_parameters.dt;
+        for event in &events {
+            let PhysicsEvent::ContactForce { a, b, magnitude } = event else {
+                continue;
+            };
+            let Some(severity) = health::injury_severity(magnitude * dt) else {
+                continue;
+            };
+            for (target, other) in [(a, b), (b, a)] {
+                let Some(player) = target
+                    .entity_id()
+                    .and_then(|id| id.strip_prefix("human_"))
+                    .and_then(|pid| self.players.get_mut(pid))
+                else {
+                    continue;
+                };
+                // One injury per game minute, however long the contact lasts
+                if player.health.injured_at(now) {
+                    continue;
+                }
+                let cause = other.entity_id().unwrap_or("the level").to_string();
+                tracing::info!("Player {} injured by {cause} ({severity:.2})", player.id);
+                player.health.injure(severity, cause, now);
+                player.mood.apply_event(&MoodEvent::Hit);
+            }
+        }
+
+        // Characters hit by a ball get upset
+        for event in &events {
+            if let PhysicsEvent::CollisionStarted { a, b } = event {
+                for (target, other) in [(a, b), (b, a)] {
+                    let (Some(target), Some(other)) = (target.entity_id(), other.entity_id())
+                    else {
+                        continue;
+                    };
+                    if !other.starts_with("ball_") {
+                        continue;
+                    }
+                    if let Some(player) = target
+                        .strip_prefix("human_")
+                        .and_then(|pid| self.players.get_mut(pid))
+                    {
+                        player.mood.apply_event(&MoodEvent::Hit);
+                    }
+                }
+            }
+        }
+
+        // Players follow their human entity (walking intent, falling, ground snapping)
+        for player in self.players.values_mut() {
+            if let Some(entity) = self.entities.get(&format!("human_{}", player.id)) {
+                player.position = entity.position.clone();
+            }
+        }
+
+        events
+    }
+
+    /// Convert a player to an entity representation.
+    ///
+    /// Used when adding a player to create the corresponding physics entity.
+    pub fn player_to_entity(&self, player: &Player) -> Entity {
+        Entity {
+            id: format!("human_{}", player.id),
+            entity_type: EntityType::human(),
+            position: player.position.clone(),
+            rotation: Rotation {
+                x: 0.0,
+                y: player.rotation,
+                z: 0.0,
+            },
+            owner: Some(player.id.clone()),
+            properties: Properties::new(),
+        }
+    }
+}
+
+/// Refuse to move items that belong to someone else.
+fn check_item_owner(player_id: &str, stack: &ItemStack) -> Result<(), String> {
+    match &stack.owner {
+        Some(owner) if owner != player_id => Err(format!("{} belongs to {owner}", stack.item_type)),
+        _ => Ok(()),
+    }
+}
+
+/// Straight-line distance between two positions (m).
+pub fn distance(a: &Position, b: &Position) -> f32 {
+    let (dx, dy, dz) = (a.x - b.x, a.y - b.y, a.z - b.z);
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::EntityData;
+    use std::path::Path;
+
+    fn game() -> GameState {
+        let catalogue =
+            Catalogue::load_dir(&Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/objects"))
+                .unwrap();
+        GameState::with_level(Level::arena(), catalogue)
+    }
+
+    fn player(id: &str, x: f32, z: f32) -> Player {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "username": id,
+            "position": { "x": x, "y": 1.0, "z": z },
+            "rotation": 0.0
+        }))
+        .unwrap()
+    }
+
+    fn object(id: &str, object_type: &str, x: f32, owner: Option<&str>) -> Entity {
+        Entity {
+            id: id.to_string(),
+            entity_type: EntityType::new(object_type),
+            position: Position { x, y: 0.5, z: 0.0 },
+            rotation: Rotation {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            owner: owner.map(str::to_string),
+            properties: Properties::new(),
+        }
+    }
+
+    fn stack(item_type: &str, count: u32, owner: Option<&str>) -> ItemStack {
+        ItemStack {
+            item_type: item_type.to_string(),
+            count,
+            owner: owner.map(str::to_string),
+            properties: Properties::new(),
+        }
+    }
+
+    fn order_of(object_type: &str, count: u32) -> Vec<OrderLine> {
+        vec![OrderLine {
+            object_type: object_type.to_string(),
+            count,
+            unit_price: 0,
+        }]
+    }
+
+    #[test]
+    fn purchases_are_paid_now_and_delivered_to_the_buyer() {
+        let mut game = game();
+        game.level.delivery_point = Some([2.0, 0.0, 3.0]);
+        game.add_player(player("ada", 0.0, 0.0));
+        let price = game.catalogue.get("chair").and_then(|t| t.price).unwrap();
+        let now = game.needs_tick_minute;
+
+        let order = game.purchase("ada", order_of("chair", 1)).unwrap();
+        assert_eq!(
+            order,
+            Order {
+                id: order.id.clone(),
+                player_id: "ada".to_string(),
+                lines: vec![OrderLine {
+                    object_type: "chair".to_string(),
+                    count: 1,
+                    unit_price: price,
+                }],
+                total: price,
+                placed_at: now,
+                deliver_at: now + DELIVERY_MINUTES,
+            }
+        );
+        assert_eq!(
+            game.ledger.balance("ada"),
+            Some(economy::STARTING_BALANCE - price)
+        );
+        assert_eq!(game.orders.pending(), std::slice::from_ref(&order));
+
+        game.tick_characters(order.deliver_at);
+        let deliveries = game.take_deliveries();
+        assert_eq!(deliveries.len(), 1);
+        let delivery = &deliveries[0];
+        assert_eq!(delivery.order, order);
+        assert_eq!(delivery.entity_ids.len(), 1);
+        assert!(game.orders.pending().is_empty());
+
+        let shape = &game.catalogue.get("chair").unwrap().shape;
+        let (half_width, half_height) = (shape.half_width(), shape.half_height());
+        assert_eq!(
+            game.entities[&delivery.entity_ids[0]],
+            Entity {
+                id: delivery.entity_ids[0].clone(),
+                entity_type: EntityType::new("chair"),
+                position: Position {
+                    x: 2.0 + half_width,
+                    y: half_height + 0.01,
+                    z: 3.0 - half_width,
+                },
+                rotation: Rotation {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+                owner: Some("ada".to_string()),
+                properties: Properties::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn purchases_over_the_balance_change_nothing() {
+        let mut game = game();
+        game.add_player(player("ada", 0.0, 0.0));
+        game.ledger.take_unsaved();
+        let entities = game.entities.clone();
+
+        assert!(game.purchase("ada", order_of("shower", 3)).is_err());
+        assert_eq!(game.ledger.balance("ada"), Some(economy::STARTING_BALANCE));
+        assert!(game.ledger.take_unsaved().is_empty());
+        assert!(game.orders.pending().is_empty());
+        assert_eq!(game.orders.take_unsaved(), (Vec::new(), Vec::new()));
+        assert_eq!(game.entities, entities);
+    }
+
+    #[test]
+    fn items_are_only_picked_up_within_reach() {
+        let mut game = game();
+        game.add_player(player("ada", 0.0, 0.0));
+        game.add_entity(object("far_egg", "egg", 10.0, None));
+        game.add_entity(object("egg", "egg", 1.0, None));
+        let far_egg = game.entities["far_egg"].clone();
+
+        assert_eq!(
+            game.pick_up("ada", "far_egg"),
+            Err("far_egg is out of reach".to_string())
+        );
+        assert_eq!(game.entities["far_egg"], far_egg);
+        assert_eq!(game.inventory("ada"), Some(Inventory::character()));
+
+        game.pick_up("ada", "egg").unwrap();
+        assert!(!game.entities.contains_key("egg"));
+        assert_eq!(game.take_removed_entities(), vec!["egg".to_string()]);
+        let mut expected = Inventory::character();
+        expected.slots[0] = Some(stack("egg", 1, Some("ada")));
+        assert_eq!(game.inventory("ada"), Some(expected));
+    }
+
+    #[test]
+    fn containers_of_others_are_refused() {
+        let mut game = game();
+        game.add_player(player("ada", 0.0, 0.0));
+        game.add_entity(object("cupboard", "cupboard", 1.0, Some("bruno")));
+        game.add_items("ada", stack("egg", 2, None)).unwrap();
+        let carried = game.inventory("ada");
+
+        let refusal = "cupboard belongs to bruno".to_string();
+        assert_eq!(game.view_inventory("ada", "cupboard"), Err(refusal.clone()));
+        assert_eq!(
+            game.store_items("ada", 0, 1, "cupboard"),
+            Err(refusal.clone())
+        );
+        assert_eq!(game.take_items("ada", "cupboard", 0, 1), Err(refusal));
+        assert_eq!(game.inventory("ada"), carried);
+        assert!(!game.inventories.contains_key("cupboard"));
+    }
+
+    #[test]
+    fn stacks_move_all_or_nothing() {
+        let mut game = game();
+        game.add_player(player("ada", 0.0, 0.0));
+        game.add_entity(object("cupboard", "cupboard", 1.0, None));
+        game.add_items("ada", stack("egg", 5, None)).unwrap();
+
+        game.store_items("ada", 0, 3, "cupboard").unwrap();
+        let mut carried = Inventory::character();
+        carried.slots[0] = Some(stack("egg", 2, None));
+        let mut stored = game.inventory("cupboard").unwrap();
+        assert_eq!(game.view_inventory("ada", "ada"), Ok(carried.clone()));
+        assert_eq!(stored.slots[0], Some(stack("egg", 3, None)));
+
+        // More than the slot holds: nothing moves
+        assert!(game.take_items("ada", "cupboard", 0, 4).is_err());
+        assert_eq!(game.view_inventory("ada", "cupboard"), Ok(stored.clone()));
+        assert_eq!(game.view_inventory("ada", "ada"), Ok(carried.clone()));
+
+        // Too heavy to carry: nothing moves
+        game.add_items("cupboard", stack("milk", 4, None)).unwrap();
+        game.add_items("ada", stack("milk", 4 * 6, None)).unwrap();
+        stored = game.inventory("cupboard").unwrap();
+        carried = game.inventory("ada").unwrap();
+        assert!(game.take_items("ada", "cupboard", 1, 4).is_err());
+        assert_eq!(game.view_inventory("ada", "cupboard"), Ok(stored));
+        assert_eq!(game.view_inventory("ada", "ada"), Ok(carried));
+
+        // Taken out, the eggs become the taker's
+        game.take_items("ada", "cupboard", 0, 3).unwrap();
+        assert_eq!(game.inventory("ada").unwrap().count("egg"), 5);
+        assert_eq!(game.inventory("cupboard").unwrap().count("egg"), 0);
+    }
+
+    #[test]
+    fn objects_are_only_used_by_their_owner() {
+        let mut game = game();
+        game.add_player(player("ada", 0.0, 0.0));
+        game.add_entity(object("stove", "stove", 1.0, Some("ada")));
+        game.add_entity(object("bruno_stove", "stove", -1.0, Some("bruno")));
+        let stove = game.entities["bruno_stove"].clone();
+
+        assert_eq!(
+            game.interact("ada", "bruno_stove", "turn_on"),
+            Decision::Refuse {
+                reason: "bruno_stove belongs to bruno".to_string()
+            }
+        );
+        assert_eq!(game.entities["bruno_stove"], stove);
+
+        assert_eq!(game.interact("ada", "stove", "turn_on"), Decision::Allow);
+        assert_eq!(
+            game.entities["stove"].properties.get("on"),
+            Some(&PropertyValue::Bool(true))
+        );
+        assert_eq!(
+            game.interact("ada", "stove", "turn_on"),
+            Decision::Refuse {
+                reason: "stove must not be on".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn cooking_uses_up_the_ingredients() {
+        let mut game = game();
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/recipes");
+        for recipe in food::load_dir(&dir).unwrap() {
+            game.add_recipe(recipe);
+        }
+        game.add_player(player("ada", 0.0, 0.0));
+        game.add_entity(object("stove", "stove", 1.0, None));
+        game.add_items("ada", stack("egg", 3, None)).unwrap();
+        let carried = game.inventory("ada");
+
+        assert_eq!(
+            game.cook("ada", "stove", "omelette"),
+            Decision::Refuse {
+                reason: "omelette needs 1 cheese".to_string()
+            }
+        );
+        assert_eq!(game.inventory("ada"), carried);
+
+        game.add_items("ada", stack("cheese", 1, None)).unwrap();
+        assert_eq!(game.cook("ada", "stove", "omelette"), Decision::Allow);
+        assert_eq!(game.players["ada"].activity, Activity::Cooking);
+        assert_eq!(game.inventory("ada"), Some(Inventory::character()));
+
+        game.tick_characters(game.needs_tick_minute + 15);
+        let mut expected = Inventory::character();
+        expected.slots[0] = Some(stack("omelette", 1, Some("ada")));
+        assert_eq!(game.inventory("ada"), Some(expected));
+    }
+
+    #[test]
+    fn only_characters_close_by_socialize() {
+        let mut game = game();
+        game.add_player(player("ada", 0.0, 0.0));
+        game.add_player(player("bruno", 1.0, 0.0));
+        game.add_player(player("carl", 10.0, 0.0));
+
+        assert_eq!(
+            game.socialize("ada", "carl", SocialAction::Chat),
+            Decision::Refuse {
+                reason: "carl is too far away".to_string()
+            }
+        );
+        assert_eq!(
+            game.relationships.get("ada", "carl"),
+            Relationship::default()
+        );
+
+        assert_eq!(
+            game.socialize("ada", "bruno", SocialAction::Chat),
+            Decision::Allow
+        );
+        let mut expected = Relationship::default();
+        expected
+            .apply(SocialAction::Chat, game.needs_tick_minute)
+            .unwrap();
+        assert_eq!(game.relationships.get("bruno", "ada"), expected);
+    }
+
+    #[test]
+    fn nothing_of_guests_is_stored() {
+        let mut game = game();
+        game.add_player(player("ada", 0.0, 0.0));
+        game.connect_player(player("gus", 1.0, 0.0));
+        assert!(game.players["gus"].guest);
+        game.add_items("ada", stack("egg", 1, None)).unwrap();
+        game.add_items("gus", stack("egg", 1, None)).unwrap();
+        assert_eq!(
+            game.socialize("gus", "ada", SocialAction::Chat),
+            Decision::Allow
+        );
+
+        let stored: Vec<String> = game
+            .stored_inventories()
+            .into_iter()
+            .map(|(owner_id, _)| owner_id)
+            .collect();
+        assert_eq!(stored, vec!["ada".to_string()]);
+        assert!(game.take_unsaved_relationships().is_empty());
+    }
+
+    #[test]
+    fn deliveries_are_restored_from_their_stored_form() {
+        let mut game = game();
+        game.add_player(player("ada", 0.0, 0.0));
+        let order = game.purchase("ada", order_of("chair", 1)).unwrap();
+        game.tick_characters(order.deliver_at);
+        let delivery = game.take_deliveries().pop().unwrap();
+        let delivered = game.entities[&delivery.entity_ids[0]].clone();
+
+        let rows: Vec<Entity> = game
+            .stored_entities()
+            .iter()
+            .map(|entity| {
+                EntityData::from_entity("arena", entity)
+                    .unwrap()
+                    .into_entity()
+                    .unwrap()
+            })
+            .collect();
+        let mut restored = self::game();
+        restored.restore_entities(rows);
+
+        let entity = &restored.entities[&delivered.id];
+        assert!(distance(&entity.position, &delivered.position) < 0.01);
+        assert_eq!(
+            Entity {
+                position: delivered.position.clone(),
+                ..entity.clone()
+            },
+            delivered
+        );
+        assert!(restored
+            .physics
+            .get_entity_position(&delivered.id)
+            .is_some());
+
+        restored.remove_entity(&delivered.id);
+        assert_eq!(restored.take_removed_entities(), vec![delivered.id]);
     }
 }
@@ -0,0 +1,272 @@
+//! Gateway module.
+//!
+//! With `GATEWAY` set the server hosts no zones: it serves the client files and
+//! relays every client WebSocket connection to the server hosting the home zone,
+//! as listed in the zone directory (`ZONE_DIRECTORY_PATH`). When that server hands
+//! the character off to a zone on another server (`ZoneHandoff`), the gateway
+//! connects there, presents the ticket (`ResumeHandoff`) and keeps relaying, so a
+//! client keeps one connection whichever server its character is on.
+//!
+//! To try it locally, run one server per port of the directory and a gateway:
+//!
+//! ```text
+//! ZONE_DIRECTORY_PATH=data/shards/local.json SHARD_SECRET=dev PORT=8081 cargo run
+//! ZONE_DIRECTORY_PATH=data/shards/local.json SHARD_SECRET=dev PORT=8082 cargo run
+//! ZONE_DIRECTORY_PATH=data/shards/local.json GATEWAY=true PORT=8080 cargo run
+//! ```
+
+use axum::extract::ws::{Message, WebSocket};
+use axum::extract::{State, WebSocketUpgrade};
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use futures_util::{SinkExt, StreamExt};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message as UpstreamMessage;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tower_http::{cors::CorsLayer, services::ServeDir};
+
+use crate::messages::GameMessage;
+use crate::shard::Directory;
+
+type Upstream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Whether this server runs as a gateway (`GATEWAY`).
+pub fn enabled() -> bool {
+    std::env::var("GATEWAY")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Serve the client files and relay WebSocket connections until shut down.
+pub async fn serve(port: u16, static_dir: PathBuf) -> anyhow::Result<()> {
+    let directory = Directory::load_configured()
+        .filter(|directory| directory.home().is_some())
+        .ok_or_else(|| anyhow::anyhow!("a gateway needs a zone directory (ZONE_DIRECTORY_PATH)"))?;
+    let app = Router::new()
+        .route("/ws", get(relay_handler))
+        .fallback_service(ServeDir::new(static_dir).append_index_html_on_directories(true))
+        .layer(CorsLayer::permissive())
+        .with_state(Arc::new(directory));
+
+    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{port}")).await?;
+    tracing::info!("Gateway listening on 0.0.0.0:{port}");
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn relay_handler(ws: WebSocketUpgrade, State(directory): State<Arc<Directory>>) -> Response {
+    ws.on_upgrade(|socket| relay(socket, directory))
+}
+
+/// WebSocket URL of a server's game endpoint.
+fn websocket_url(server: &str) -> String {
+    let server = server.trim_end_matches('/');
+    let server = match server.strip_prefix("https://") {
+        Some(rest) => format!("wss://{rest}"),
+        None => server.replacen("http://", "ws://", 1),
+    };
+    format!("{server}/ws")
+}
+
+/// Relay one client connection, following its character from server to server.
+async fn relay(socket: WebSocket, directory: Arc<Directory>) {
+    let (mut client_tx, mut client_rx) = socket.split();
+    let home = directory.home().expect("checked at startup").server.clone();
+    let mut server = home;
+    let mut resume: Option<String> = None;
+
+    loop {
+        let upstream: Upstream = match connect_async(websocket_url(&server)).await {
+            Ok((upstream, _)) => upstream,
+            Err(e) => {
+                tracing::error!("Gateway failed to connect to {server}: {e}");
+                break;
+            }
+        };
+        let (mut upstream_tx, mut upstream_rx) = upstream.split();
+        if let Some(ticket) = resume.take() {
+            let message = GameMessage::ResumeHandoff { ticket };
+            if let Ok(json) = serde_json::to_string(&message) {
+                if upstream_tx.send(UpstreamMessage::text(json)).await.is_err() {
+                    break;
+                }
+            }
+        }
+
+        // Relay until the client leaves, the server goes away or hands off
+        let handoff = loop {
+            tokio::select! {
+                msg = client_rx.next() => {
+                    let forwarded = match msg {
+                        Some(Ok(Message::Text(text))) => UpstreamMessage::text(text.as_str()),
+                        Some(Ok(Message::Binary(data))) => UpstreamMessage::binary(data),
+                        Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                        _ => {
+                            let _ = upstream_tx.close().await;
+                            return;
+                        }
+                    };
+                    if upstream_tx.send(forwarded).await.is_err() {
+                        break None;
+                    }
+                }
+                msg = upstream_rx.next() => {
+                    let forwarded = match msg {
+                        Some(Ok(UpstreamMessage::Text(text))) => {
+                            if let Some(handoff) = parse_handoff(text.as_str()) {
+                                break Some(handoff);
+                            }
+                            Message::Text(text.as_str().into())
+                        }
+                        Some(Ok(UpstreamMessage::Binary(data))) => Message::Binary(data),
+                        Some(Ok(UpstreamMessage::Ping(data))) => Message::Ping(data),
+                        Some(Ok(_)) => continue,
+                        _ => break None,
+                    };
+                    if client_tx.send(forwarded).await.is_err() {
+                        let _ = upstream_tx.close().await;
+                        return;
+                    }
+                }
+            }
+        };
+        let _ = upstream_tx.close().await;
+        let Some((next_server, ticket)) = handoff else {
+            break;
+        };
+        tracing::debug!("Gateway following handoff from {server} to {next_server}");
+        server = next_server;
+        resume = Some(ticket);
+    }
+    let _ = client_tx.close().await;
+}
+
+/// Server and ticket of a `ZoneHandoff` message.
+fn parse_handoff(text: &str) -> Option<(String, String)> {
+    // Cheap check first: almost every message is something else
+    if !text.contains("ZoneHandoff") {
+        return None;
+    }
+    match serde_json::from_str(text) {
+        Ok(GameMessage::ZoneHandoff { server, ticket, .. }) => Some((server, ticket)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shard::DirectoryEntry;
+    use std::time::Duration;
+
+    #[test]
+    fn handoffs_are_told_apart_from_other_messages() {
+        let handoff = GameMessage::ZoneHandoff {
+            player_id: "ada".to_string(),
+            zone: "house".to_string(),
+            server: "http://127.0.0.1:8082".to_string(),
+            ticket: "t1".to_string(),
+        };
+        let text = serde_json::to_string(&handoff).unwrap();
+        assert_eq!(
+            parse_handoff(&text),
+            Some(("http://127.0.0.1:8082".to_string(), "t1".to_string()))
+        );
+        let resume = serde_json::to_string(&GameMessage::ResumeHandoff {
+            ticket: "ZoneHandoff".to_string(),
+        })
+        .unwrap();
+        assert_eq!(parse_handoff(&resume), None);
+        assert_eq!(parse_handoff("ZoneHandoff, but not JSON"), None);
+    }
+
+    #[test]
+    fn websocket_urls_keep_the_scheme_secure() {
+        assert_eq!(
+            websocket_url("http://127.0.0.1:8081/"),
+            "ws://127.0.0.1:8081/ws"
+        );
+        assert_eq!(
+            websocket_url("https://zones.example"),
+            "wss://zones.example/ws"
+        );
+    }
+
+    /// Serve a WebSocket endpoint answering each connection with `answer`.
+    async fn serve_upstream<F, Fut>(answer: F) -> String
+    where
+        F: Fn(WebSocket) -> Fut + Clone + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let app = Router::new().route(
+            "/ws",
+            get(move |ws: WebSocketUpgrade| {
+                let answer = answer.clone();
+                async move { ws.on_upgrade(answer) }
+            }),
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        url
+    }
+
+    #[tokio::test]
+    async fn the_gateway_follows_handoffs_to_the_next_server() {
+        // The next server echoes what it is sent
+        let next = serve_upstream(|mut socket: WebSocket| async move {
+            while let Some(Ok(message)) = socket.recv().await {
+                if socket.send(message).await.is_err() {
+                    break;
+                }
+            }
+        })
+        .await;
+        // The home server hands off on the first message
+        let handoff = serde_json::to_string(&GameMessage::ZoneHandoff {
+            player_id: "ada".to_string(),
+            zone: "house".to_string(),
+            server: next,
+            ticket: "t1".to_string(),
+        })
+        .unwrap();
+        let home = serve_upstream(move |mut socket: WebSocket| {
+            let handoff = handoff.clone();
+            async move {
+                if let Some(Ok(Message::Text(_))) = socket.recv().await {
+                    let _ = socket.send(Message::Text(handoff.into())).await;
+                }
+            }
+        })
+        .await;
+
+        let directory = Directory::new(vec![DirectoryEntry {
+            zone: "arena".to_string(),
+            server: home,
+        }]);
+        let gateway =
+            serve_upstream(move |socket: WebSocket| relay(socket, Arc::new(directory.clone())))
+                .await;
+        let (mut client, _) = connect_async(websocket_url(&gateway)).await.unwrap();
+        client.send(UpstreamMessage::text("hello")).await.unwrap();
+
+        // The client never sees the handoff, only the next server's answers
+        let reply = tokio::time::timeout(Duration::from_secs(5), client.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let message: GameMessage = serde_json::from_str(reply.to_text().unwrap()).unwrap();
+        assert!(matches!(message, GameMessage::ResumeHandoff { ticket } if ticket == "t1"));
+        client.send(UpstreamMessage::text("again")).await.unwrap();
+        let reply = tokio::time::timeout(Duration::from_secs(5), client.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(reply.to_text().unwrap(), "again");
+    }
+}
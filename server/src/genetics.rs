@@ -0,0 +1,294 @@
+//! Genetics and appearance module.
+//!
+//! Every character carries a genome: a pair of alleles (one from each parent) at
+//! each of a handful of loci. The average of the two alleles is the gene's
+//! expression, from 0 to 1, and expressions decide the character's traits
+//! (height, build, metabolism, temperament) and the colours of their skin, hair
+//! and eyes. A child inherits one allele per locus from each parent, with the
+//! occasional small mutation.
+//!
+//! Metabolism scales how fast hunger and thirst build up; temperament makes a
+//! character quicker or slower to anger and worry. Clients render avatars from
+//! the [`Appearance`], which combines what the genome decides with the hair style
+//! and outfit colour chosen at character creation.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::needs::NeedRates;
+
+/// Hair styles to choose from at character creation.
+pub const HAIR_STYLES: [&str; 6] = ["short", "long", "curly", "ponytail", "buzz", "bald"];
+/// Chance that an inherited allele mutates.
+const MUTATION_CHANCE: f64 = 0.02;
+/// Largest change a mutation makes to an allele.
+const MUTATION_STEP: i16 = 24;
+/// Height (m) at the lowest and highest expression.
+const HEIGHT_RANGE: (f32, f32) = (1.50, 1.95);
+/// Metabolism factor at the lowest and highest expression.
+const METABOLISM_RANGE: (f32, f32) = (0.8, 1.2);
+/// Skin tones from lightest to darkest expression.
+const SKIN_TONES: [[u8; 3]; 3] = [[0xf3, 0xd9, 0xc4], [0xc6, 0x8e, 0x66], [0x4a, 0x2c, 0x1d]];
+/// Hair colours from lightest to darkest expression.
+const HAIR_COLORS: [[u8; 3]; 4] = [
+    [0xe6, 0xce, 0x8a],
+    [0xa0, 0x52, 0x2d],
+    [0x5a, 0x3a, 0x22],
+    [0x1c, 0x14, 0x10],
+];
+/// Eye colours from lightest to darkest expression.
+const EYE_COLORS: [[u8; 3]; 4] = [
+    [0x7a, 0xa6, 0xc8],
+    [0x6c, 0x8a, 0x4e],
+    [0x8b, 0x5a, 0x2b],
+    [0x3b, 0x24, 0x16],
+];
+
+/// Two alleles at one locus, each 0 to 255.
+///
+/// Serialized as a two-element array.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Gene(pub u8, pub u8);
+
+impl Default for Gene {
+    fn default() -> Self {
+        Gene(128, 128)
+    }
+}
+
+impl Gene {
+    /// How strongly the gene is expressed, from 0 to 1.
+    pub fn expression(&self) -> f32 {
+        (self.0 as f32 + self.1 as f32) / 510.0
+    }
+
+    fn random(rng: &mut impl Rng) -> Self {
+        Gene(rng.gen(), rng.gen())
+    }
+
+    /// One allele from each parent, each possibly mutated.
+    fn inherit(mother: &Gene, father: &Gene, rng: &mut impl Rng) -> Self {
+        let mut pick = |gene: &Gene| {
+            let allele = if rng.gen_bool(0.5) { gene.0 } else { gene.1 };
+            if rng.gen_bool(MUTATION_CHANCE) {
+                let step = rng.gen_range(-MUTATION_STEP..=MUTATION_STEP);
+                (allele as i16 + step).clamp(0, 255) as u8
+            } else {
+                allele
+            }
+        };
+        Gene(pick(mother), pick(father))
+    }
+}
+
+/// Heritable makeup of a character.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Genome {
+    pub height: Gene,
+    pub build: Gene,
+    pub metabolism: Gene,
+    pub temperament: Gene,
+    pub skin: Gene,
+    pub hair: Gene,
+    pub eyes: Gene,
+}
+
+/// Traits that follow from a genome.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Traits {
+    /// Standing height (m)
+    pub height_m: f32,
+    /// 0 (slight) to 1 (heavy)
+    pub build: f32,
+    /// Factor on how fast hunger and thirst build up
+    pub metabolism: f32,
+    /// -1 (calm) to 1 (volatile)
+    pub temperament: f32,
+}
+
+impl Genome {
+    /// A genome with random alleles.
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self {
+            height: Gene::random(rng),
+            build: Gene::random(rng),
+            metabolism: Gene::random(rng),
+            temperament: Gene::random(rng),
+            skin: Gene::random(rng),
+            hair: Gene::random(rng),
+            eyes: Gene::random(rng),
+        }
+    }
+
+    /// A random genome that is always the same for the same seed (e.g. an NPC ID).
+    pub fn seeded(seed: &str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        Self::random(&mut StdRng::seed_from_u64(hasher.finish()))
+    }
+
+    /// A child's genome: one allele per locus from each parent.
+    pub fn inherit(mother: &Genome, father: &Genome, rng: &mut impl Rng) -> Self {
+        Self {
+            height: Gene::inherit(&mother.height, &father.height, rng),
+            build: Gene::inherit(&mother.build, &father.build, rng),
+            metabolism: Gene::inherit(&mother.metabolism, &father.metabolism, rng),
+            temperament: Gene::inherit(&mother.temperament, &father.temperament, rng),
+            skin: Gene::inherit(&mother.skin, &father.skin, rng),
+            hair: Gene::inherit(&mother.hair, &father.hair, rng),
+            eyes: Gene::inherit(&mother.eyes, &father.eyes, rng),
+        }
+    }
+
+    pub fn traits(&self) -> Traits {
+        let lerp = |(low, high): (f32, f32), t: f32| low + (high - low) * t;
+        Traits {
+            height_m: lerp(HEIGHT_RANGE, self.height.expression()),
+            build: self.build.expression(),
+            metabolism: lerp(METABOLISM_RANGE, self.metabolism.expression()),
+            temperament: self.temperament.expression() * 2.0 - 1.0,
+        }
+    }
+
+    /// Per-game-minute change to needs on top of the base rates, from metabolism.
+    pub fn need_effects(&self) -> NeedRates {
+        let extra = self.traits().metabolism - 1.0;
+        NeedRates {
+            hunger: NeedRates::BASE.hunger * extra,
+            thirst: NeedRates::BASE.thirst * extra,
+            ..NeedRates::default()
+        }
+    }
+}
+
+/// Choices made at character creation.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AppearanceChoice {
+    /// One of [`HAIR_STYLES`]
+    pub hair_style: String,
+    /// Outfit colour ("#rrggbb")
+    pub outfit_color: String,
+}
+
+impl Default for AppearanceChoice {
+    fn default() -> Self {
+        Self {
+            hair_style: HAIR_STYLES[0].to_string(),
+            outfit_color: "#4a6fa5".to_string(),
+        }
+    }
+}
+
+/// What a client needs to render a character's avatar.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Appearance {
+    /// Standing height (m)
+    pub height_m: f32,
+    /// 0 (slight) to 1 (heavy)
+    pub build: f32,
+    /// Colours as "#rrggbb"
+    pub skin_color: String,
+    pub hair_color: String,
+    pub eye_color: String,
+    pub hair_style: String,
+    pub outfit_color: String,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self::new(&Genome::default(), &AppearanceChoice::default())
+            .expect("default choice should be valid")
+    }
+}
+
+impl Appearance {
+    /// Appearance from a genome and the player's choices.
+    pub fn new(genome: &Genome, choice: &AppearanceChoice) -> Result<Self, String> {
+        if !HAIR_STYLES.contains(&choice.hair_style.as_str()) {
+            return Err(format!("unknown hair style {}", choice.hair_style));
+        }
+        let color = &choice.outfit_color;
+        let valid_color = color.len() == 7
+            && color.starts_with('#')
+            && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+        if !valid_color {
+            return Err(format!("{color} is not a #rrggbb colour"));
+        }
+        let traits = genome.traits();
+        Ok(Self {
+            height_m: traits.height_m,
+            build: traits.build,
+            skin_color: blend(&SKIN_TONES, genome.skin.expression()),
+            hair_color: blend(&HAIR_COLORS, genome.hair.expression()),
+            eye_color: blend(&EYE_COLORS, genome.eyes.expression()),
+            hair_style: choice.hair_style.clone(),
+            outfit_color: color.to_lowercase(),
+        })
+    }
+}
+
+/// Colour at `t` (0 to 1) along a palette, as "#rrggbb".
+fn blend(palette: &[[u8; 3]], t: f32) -> String {
+    let position = t.clamp(0.0, 1.0) * (palette.len() - 1) as f32;
+    let index = (position.floor() as usize).min(palette.len() - 2);
+    let local = position - index as f32;
+    let channel = |c: usize| {
+        let (from, to) = (palette[index][c] as f32, palette[index + 1][c] as f32);
+        (from + (to - from) * local).round() as u8
+    };
+    format!("#{:02x}{:02x}{:02x}", channel(0), channel(1), channel(2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn children_inherit_one_allele_from_each_parent() {
+        let mother = Genome {
+            height: Gene(10, 20),
+            ..Genome::default()
+        };
+        let father = Genome {
+            height: Gene(200, 210),
+            ..Genome::default()
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..50 {
+            let child = Genome::inherit(&mother, &father, &mut rng);
+            // Allow for the occasional mutation
+            assert!(child.height.0 <= 20 + MUTATION_STEP as u8);
+            assert!(child.height.1 >= 200 - MUTATION_STEP as u8);
+        }
+        assert_eq!(Genome::seeded("npc_ada"), Genome::seeded("npc_ada"));
+    }
+
+    #[test]
+    fn appearance_follows_genome_and_choices() {
+        let genome = Genome {
+            height: Gene(255, 255),
+            skin: Gene(0, 0),
+            ..Genome::default()
+        };
+        let choice = AppearanceChoice {
+            hair_style: "curly".to_string(),
+            outfit_color: "#AA3300".to_string(),
+        };
+        let appearance = Appearance::new(&genome, &choice).unwrap();
+        assert_eq!(appearance.height_m, HEIGHT_RANGE.1);
+        assert_eq!(appearance.skin_color, "#f3d9c4");
+        assert_eq!(appearance.outfit_color, "#aa3300");
+        let mohawk = AppearanceChoice {
+            hair_style: "mohawk".to_string(),
+            ..choice
+        };
+        assert!(Appearance::new(&genome, &mohawk).is_err());
+        assert!(Genome::default().need_effects().hunger.abs() < 1e-3);
+    }
+}
@@ -0,0 +1,371 @@
+//! Character health module.
+//!
+//! Characters age by the game calendar (360-day years), catch illnesses that
+//! incubate for a while before symptoms show and then run their course, and get
+//! injured by hard physical impacts. Symptoms and injuries slow a character down,
+//! wear on their needs and rule out strenuous activities. Older characters walk
+//! more slowly and heal more slowly.
+
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::{DAYS_PER_YEAR, MINUTES_PER_DAY, MINUTES_PER_HOUR};
+use crate::game::Activity;
+use crate::needs::NeedRates;
+
+/// Game minutes in a game year.
+const MINUTES_PER_YEAR: i64 = DAYS_PER_YEAR * MINUTES_PER_DAY;
+/// Age of a new character (game years).
+pub const STARTING_AGE_YEARS: i64 = 25;
+/// Age from which characters slow down and heal more slowly (game years).
+const ELDERLY_AGE_YEARS: i64 = 65;
+/// Walking speed factor of elderly characters.
+const ELDERLY_SPEED_FACTOR: f32 = 0.8;
+/// Impulse (N·s) of a single impact below which nobody gets hurt.
+pub const INJURY_IMPULSE: f32 = 20.0;
+/// Impulse (N·s) above [`INJURY_IMPULSE`] that makes the most severe injury.
+const SEVERE_IMPULSE: f32 = 200.0;
+/// Injury severity a young adult heals per game day.
+const HEAL_PER_DAY: f32 = 0.1;
+/// Total injury severity from which strenuous activities are ruled out.
+const DISABLING_SEVERITY: f32 = 0.4;
+/// Slowest walking speed factor, however badly hurt.
+const MIN_SPEED_FACTOR: f32 = 0.3;
+/// Distance (m) within which a contagious illness can spread.
+pub const CONTAGION_DISTANCE: f32 = 2.0;
+/// Chance per game minute of catching an illness from a sick character nearby.
+pub const CONTAGION_CHANCE: f64 = 0.005;
+/// Chance per game minute that a character with critical exhaustion catches a cold.
+pub const RUN_DOWN_CHANCE: f64 = 0.0005;
+/// Chance that eating spoiled food causes food poisoning.
+pub const FOOD_POISONING_CHANCE: f64 = 0.5;
+
+/// Activities too strenuous for a sick or badly hurt character.
+const STRENUOUS: [Activity; 4] = [
+    Activity::Exercising,
+    Activity::Working,
+    Activity::Cleaning,
+    Activity::Shopping,
+];
+
+/// Kinds of illness.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IllnessKind {
+    Cold,
+    Flu,
+    FoodPoisoning,
+}
+
+/// How an illness runs its course.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IllnessSpec {
+    /// Game minutes from catching it to the first symptoms
+    pub incubation_minutes: i64,
+    /// Game minutes the symptoms last
+    pub duration_minutes: i64,
+    /// Whether it spreads to characters nearby while symptomatic
+    pub contagious: bool,
+    /// Walking speed factor while symptomatic
+    pub speed_factor: f32,
+    /// Per-game-minute effect on needs while symptomatic
+    pub need_effects: NeedRates,
+    /// Activities ruled out while symptomatic
+    pub forbids: &'static [Activity],
+}
+
+impl IllnessKind {
+    /// Course and effects of the illness.
+    pub fn spec(&self) -> IllnessSpec {
+        let zero = NeedRates::default();
+        match self {
+            IllnessKind::Cold => IllnessSpec {
+                incubation_minutes: 2 * MINUTES_PER_DAY,
+                duration_minutes: 5 * MINUTES_PER_DAY,
+                contagious: true,
+                speed_factor: 0.9,
+                need_effects: NeedRates {
+                    sleepiness: 0.03,
+                    exhaustion: 0.02,
+                    ..zero
+                },
+                forbids: &[Activity::Exercising],
+            },
+            IllnessKind::Flu => IllnessSpec {
+                incubation_minutes: MINUTES_PER_DAY,
+                duration_minutes: 7 * MINUTES_PER_DAY,
+                contagious: true,
+                speed_factor: 0.6,
+                need_effects: NeedRates {
+                    thirst: 0.05,
+                    sleepiness: 0.05,
+                    exhaustion: 0.06,
+                    ..zero
+                },
+                forbids: &STRENUOUS,
+            },
+            IllnessKind::FoodPoisoning => IllnessSpec {
+                incubation_minutes: 6 * MINUTES_PER_HOUR,
+                duration_minutes: MINUTES_PER_DAY,
+                contagious: false,
+                speed_factor: 0.7,
+                need_effects: NeedRates {
+                    thirst: 0.2,
+                    exhaustion: 0.05,
+                    ..zero
+                },
+                forbids: &[
+                    Activity::Eating,
+                    Activity::Exercising,
+                    Activity::Working,
+                    Activity::Cleaning,
+                    Activity::Shopping,
+                ],
+            },
+        }
+    }
+}
+
+/// An illness a character caught.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Illness {
+    pub kind: IllnessKind,
+    /// Game minute the illness was caught
+    pub caught_at: i64,
+    /// Game minute the symptoms start
+    pub symptoms_at: i64,
+    /// Game minute the character is well again
+    pub recovers_at: i64,
+}
+
+impl Illness {
+    /// Whether the character shows symptoms at a game minute.
+    pub fn is_symptomatic(&self, now: i64) -> bool {
+        (self.symptoms_at..self.recovers_at).contains(&now)
+    }
+}
+
+/// A healing injury.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Injury {
+    /// 0 (healed) to 1 (as bad as it gets)
+    pub severity: f32,
+    /// What caused it, e.g. the entity that hit the character
+    pub cause: String,
+    /// Game minute of the injury
+    pub at: i64,
+}
+
+/// Age, illnesses and injuries of a character.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Health {
+    /// Game minute the character was born
+    pub born_at: i64,
+    /// Illnesses incubating or running their course
+    #[serde(default)]
+    pub illnesses: Vec<Illness>,
+    /// Injuries not yet healed
+    #[serde(default)]
+    pub injuries: Vec<Injury>,
+}
+
+impl Default for Health {
+    /// A healthy adult of [`STARTING_AGE_YEARS`].
+    fn default() -> Self {
+        Self::aged(
+            STARTING_AGE_YEARS,
+            crate::game::GameState::get_game_time_minutes(),
+        )
+    }
+}
+
+impl Health {
+    /// A healthy character of an age (game years) at a game minute.
+    pub fn aged(age_years: i64, now: i64) -> Self {
+        Self::born(now - age_years * MINUTES_PER_YEAR)
+    }
+
+    /// A healthy character born at a game minute.
+    pub fn born(born_at: i64) -> Self {
+        Self {
+            born_at,
+            illnesses: Vec::new(),
+            injuries: Vec::new(),
+        }
+    }
+
+    /// Age in whole game years.
+    pub fn age_years(&self, now: i64) -> i64 {
+        (now - self.born_at).div_euclid(MINUTES_PER_YEAR)
+    }
+
+    /// Catch an illness, unless the character already has it.
+    pub fn catch(&mut self, kind: IllnessKind, now: i64) -> bool {
+        if self.illnesses.iter().any(|illness| illness.kind == kind) {
+            return false;
+        }
+        let spec = kind.spec();
+        let symptoms_at = now + spec.incubation_minutes;
+        self.illnesses.push(Illness {
+            kind,
+            caught_at: now,
+            symptoms_at,
+            recovers_at: symptoms_at + spec.duration_minutes,
+        });
+        true
+    }
+
+    /// Record an injury.
+    pub fn injure(&mut self, severity: f32, cause: String, now: i64) {
+        self.injuries.push(Injury {
+            severity: severity.clamp(0.0, 1.0),
+            cause,
+            at: now,
+        });
+    }
+
+    /// Whether the character was injured at this game minute.
+    pub fn injured_at(&self, now: i64) -> bool {
+        self.injuries.iter().any(|injury| injury.at == now)
+    }
+
+    /// Illnesses showing symptoms at a game minute.
+    pub fn symptoms(&self, now: i64) -> impl Iterator<Item = &Illness> {
+        self.illnesses
+            .iter()
+            .filter(move |illness| illness.is_symptomatic(now))
+    }
+
+    /// Contagious illnesses the character can pass on at a game minute.
+    pub fn contagious(&self, now: i64) -> Vec<IllnessKind> {
+        self.symptoms(now)
+            .filter(|illness| illness.kind.spec().contagious)
+            .map(|illness| illness.kind)
+            .collect()
+    }
+
+    /// Total severity of all injuries (0 to 1).
+    pub fn injury_severity(&self) -> f32 {
+        self.injuries
+            .iter()
+            .map(|injury| injury.severity)
+            .sum::<f32>()
+            .min(1.0)
+    }
+
+    /// Factor applied to walking speed.
+    pub fn speed_factor(&self, now: i64) -> f32 {
+        let age = if self.age_years(now) >= ELDERLY_AGE_YEARS {
+            ELDERLY_SPEED_FACTOR
+        } else {
+            1.0
+        };
+        let illness = self
+            .symptoms(now)
+            .map(|illness| illness.kind.spec().speed_factor)
+            .fold(1.0, f32::min);
+        let injury = 1.0 - self.injury_severity();
+        (age * illness * injury).max(MIN_SPEED_FACTOR)
+    }
+
+    /// Per-game-minute effect of symptoms on needs.
+    pub fn need_effects(&self, now: i64) -> NeedRates {
+        self.symptoms(now)
+            .fold(NeedRates::default(), |rates, illness| {
+                rates.plus(illness.kind.spec().need_effects)
+            })
+    }
+
+    /// Why the character cannot do an activity right now, if they cannot.
+    pub fn forbids(&self, activity: &Activity, now: i64) -> Option<String> {
+        if let Some(illness) = self
+            .symptoms(now)
+            .find(|illness| illness.kind.spec().forbids.contains(activity))
+        {
+            return Some(format!("too ill ({:?})", illness.kind));
+        }
+        if self.injury_severity() >= DISABLING_SEVERITY && STRENUOUS.contains(activity) {
+            return Some("too badly hurt".to_string());
+        }
+        None
+    }
+
+    /// Advance by a number of game minutes ending at `now`: injuries heal and
+    /// illnesses start showing or pass. Returns what happened, for the journal.
+    pub fn tick(&mut self, now: i64, minutes: i64) -> Vec<String> {
+        let mut events = Vec::new();
+        let before = now - minutes;
+        let age = self.age_years(now);
+        if age > self.age_years(before) {
+            events.push(format!("turned {age}"));
+        }
+
+        for illness in &self.illnesses {
+            if (before + 1..=now).contains(&illness.symptoms_at) {
+                events.push(format!("came down with {:?}", illness.kind));
+            }
+        }
+        self.illnesses.retain(|illness| {
+            let recovered = illness.recovers_at <= now;
+            if recovered {
+                events.push(format!("recovered from {:?}", illness.kind));
+            }
+            !recovered
+        });
+
+        let heal_factor = if age >= ELDERLY_AGE_YEARS { 0.5 } else { 1.0 };
+        let healed = HEAL_PER_DAY * heal_factor * minutes as f32 / MINUTES_PER_DAY as f32;
+        for injury in &mut self.injuries {
+            injury.severity -= healed;
+        }
+        self.injuries.retain(|injury| {
+            let done = injury.severity <= 0.0;
+            if done {
+                events.push(format!("healed from {}", injury.cause));
+            }
+            !done
+        });
+        events
+    }
+}
+
+/// Severity of the injury an impact of some impulse (N·s) causes, if any.
+pub fn injury_severity(impulse: f32) -> Option<f32> {
+    (impulse > INJURY_IMPULSE).then(|| ((impulse - INJURY_IMPULSE) / SEVERE_IMPULSE).min(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn illness_incubates_then_runs_its_course() {
+        let mut health = Health::born(0);
+        assert_eq!(health.age_years(30 * MINUTES_PER_YEAR + 5), 30);
+        let now = 30 * MINUTES_PER_YEAR;
+        assert!(health.catch(IllnessKind::FoodPoisoning, now));
+        assert!(!health.catch(IllnessKind::FoodPoisoning, now + 1));
+        assert_eq!(health.forbids(&Activity::Eating, now + 1), None);
+
+        let sick = now + 6 * MINUTES_PER_HOUR;
+        assert_eq!(health.tick(sick, 1), vec!["came down with FoodPoisoning"]);
+        assert!(health.forbids(&Activity::Eating, sick).is_some());
+        assert!(health.need_effects(sick).thirst > 0.0);
+        assert!(health.speed_factor(sick) < 1.0);
+
+        let well = sick + MINUTES_PER_DAY;
+        assert_eq!(health.tick(well, 1), vec!["recovered from FoodPoisoning"]);
+        assert_eq!(health.forbids(&Activity::Eating, well), None);
+    }
+
+    #[test]
+    fn hard_impacts_injure_and_heal() {
+        assert_eq!(injury_severity(5.0), None);
+        let mut health = Health::born(0);
+        health.injure(injury_severity(120.0).unwrap(), "chair".to_string(), 0);
+        assert!(health.forbids(&Activity::Exercising, 0).is_some());
+        assert!(health.speed_factor(0) < 1.0);
+        let events = health.tick(10 * MINUTES_PER_DAY, 10 * MINUTES_PER_DAY);
+        assert!(events.contains(&"healed from chair".to_string()));
+        assert_eq!(health.speed_factor(10 * MINUTES_PER_DAY), 1.0);
+    }
+}
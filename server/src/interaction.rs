@@ -0,0 +1,135 @@
+//! Object interaction module.
+//!
+//! Object types declare the verbs they afford in the catalogue (`"verbs": ["sit",
+//! "sleep"]`); this module says what each verb does. An interaction may require the
+//! object to be in some state (a stove must be on to cook), change the object's
+//! state (open a fridge, turn on a television), start an activity that uses the
+//! object, and change the character's needs on the spot.
+//!
+//! A few verbs act on something other than state: `pick_up` moves the item into
+//! the character's inventory, `talk` is a social contact between two characters,
+//! and `throw` sends a ball flying.
+
+use crate::game::Activity;
+use crate::needs::NeedRates;
+
+/// Speed (m/game-second) at which thrown objects leave the hand, forward and up.
+pub const THROW_SPEED: (f32, f32) = (8.0, 3.0);
+
+/// What a verb does to an object and to the character using it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Affordance {
+    /// States (name, value) the object must be in, e.g. ("on", true) to cook
+    pub requires: &'static [(&'static str, bool)],
+    /// States (name, value) the object is put in, e.g. ("open", true)
+    pub sets: &'static [(&'static str, bool)],
+    /// Activity the character starts with the object
+    pub activity: Option<Activity>,
+    /// One-off change to the character's needs
+    pub needs: NeedRates,
+}
+
+/// Verbs handled by dedicated game logic rather than an [`Affordance`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpecialVerb {
+    /// Move the item into the character's inventory
+    PickUp,
+    /// Chat with another character
+    Talk,
+    /// Throw the object the way the character faces
+    Throw,
+}
+
+/// Meaning of an interaction verb.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verb {
+    Affordance(Affordance),
+    Special(SpecialVerb),
+}
+
+impl Verb {
+    /// Activity the verb starts, if any.
+    pub fn activity(&self) -> Option<&Activity> {
+        match self {
+            Verb::Affordance(affordance) => affordance.activity.as_ref(),
+            Verb::Special(_) => None,
+        }
+    }
+}
+
+/// Look up what a verb does, or None for verbs the server does not know.
+pub fn verb(name: &str) -> Option<Verb> {
+    let zero = NeedRates::default();
+    let affordance = |requires, sets, activity, needs| {
+        Verb::Affordance(Affordance {
+            requires,
+            sets,
+            activity,
+            needs,
+        })
+    };
+    Some(match name {
+        "open" => affordance(&[("open", false)], &[("open", true)], None, zero),
+        "close" => affordance(&[("open", true)], &[("open", false)], None, zero),
+        "turn_on" => affordance(&[("on", false)], &[("on", true)], None, zero),
+        "turn_off" => affordance(&[("on", true)], &[("on", false)], None, zero),
+        "sit" => affordance(
+            &[],
+            &[],
+            Some(Activity::Idle),
+            NeedRates {
+                exhaustion: -2.0,
+                ..zero
+            },
+        ),
+        "sleep" => affordance(&[], &[], Some(Activity::Sleeping), zero),
+        "cook" => affordance(&[("on", true)], &[], Some(Activity::Cooking), zero),
+        "watch" => affordance(&[("on", true)], &[], Some(Activity::WatchingTv), zero),
+        "bathe" => affordance(&[], &[], Some(Activity::Bathing), zero),
+        "eat" | "eat_at" => affordance(&[], &[], Some(Activity::Eating), zero),
+        "drink_from" => affordance(
+            &[],
+            &[],
+            None,
+            NeedRates {
+                thirst: -15.0,
+                ..zero
+            },
+        ),
+        "pick_up" => Verb::Special(SpecialVerb::PickUp),
+        "talk" => Verb::Special(SpecialVerb::Talk),
+        "throw" => Verb::Special(SpecialVerb::Throw),
+        _ => return None,
+    })
+}
+
+/// States (name, value) an activity's object must stay in while it is used,
+/// e.g. a stove stays on while Cooking.
+pub fn required_state(activity: &Activity) -> &'static [(&'static str, bool)] {
+    match activity {
+        Activity::Cooking | Activity::WatchingTv | Activity::Gaming => &[("on", true)],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalogue::Catalogue;
+    use std::path::Path;
+
+    #[test]
+    fn every_catalogue_verb_is_known() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/objects");
+        let catalogue = Catalogue::load_dir(&dir).expect("object files should parse");
+        for object_type in catalogue.types() {
+            for name in &object_type.verbs {
+                assert!(
+                    verb(name).is_some(),
+                    "{}: unknown verb {name}",
+                    object_type.name
+                );
+            }
+        }
+    }
+}
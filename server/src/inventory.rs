@@ -0,0 +1,242 @@
+//! Inventory module.
+//!
+//! Characters and containers (fridges, cupboards) hold items in inventories: a
+//! fixed number of slots, each holding a stack of one item type up to the type's
+//! `max_stack`, with a limit on the total weight. Items are catalogue object types
+//! that can be picked up; in an inventory they exist only as stacks, and become
+//! world entities again when dropped or placed. A stack keeps the properties its
+//! items had as entities (e.g. freshness), so only items alike in every property
+//! share a stack.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::catalogue::Catalogue;
+use crate::properties::Properties;
+
+/// Inventory slots of a character.
+pub const CHARACTER_SLOTS: usize = 12;
+/// Weight a character can carry (kilograms).
+pub const CHARACTER_MAX_WEIGHT: f32 = 25.0;
+
+/// A number of items of one type in a slot.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ItemStack {
+    /// Catalogue type name of the items
+    pub item_type: String,
+    pub count: u32,
+    /// Player who owns the items, if anyone
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Properties of the items, e.g. freshness; others have the type's default
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub properties: Properties,
+}
+
+/// Slots holding item stacks, with a weight limit.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Inventory {
+    /// One entry per slot, None when empty
+    pub slots: Vec<Option<ItemStack>>,
+    /// Maximum total weight of the contents (kilograms)
+    pub max_weight: f32,
+}
+
+impl Inventory {
+    /// Empty inventory.
+    pub fn new(slots: usize, max_weight: f32) -> Self {
+        Self {
+            slots: vec![None; slots],
+            max_weight,
+        }
+    }
+
+    /// Empty inventory of a character.
+    pub fn character() -> Self {
+        Self::new(CHARACTER_SLOTS, CHARACTER_MAX_WEIGHT)
+    }
+
+    /// Total weight of the contents (kilograms).
+    pub fn weight(&self, catalogue: &Catalogue) -> f32 {
+        self.stacks()
+            .map(|(_, stack)| {
+                let each = catalogue.get(&stack.item_type).map_or(0.0, |t| t.weight());
+                each * stack.count as f32
+            })
+            .sum()
+    }
+
+    /// Occupied slots with their index.
+    pub fn stacks(&self) -> impl Iterator<Item = (usize, &ItemStack)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, stack)| stack.as_ref().map(|stack| (slot, stack)))
+    }
+
+    /// Number of items of a type.
+    pub fn count(&self, item_type: &str) -> u32 {
+        self.stacks()
+            .filter(|(_, stack)| stack.item_type == item_type)
+            .map(|(_, stack)| stack.count)
+            .sum()
+    }
+
+    /// Add items, topping up stacks of the same type and owner before using
+    /// empty slots. Nothing is added unless everything fits.
+    pub fn add(&mut self, stack: ItemStack, catalogue: &Catalogue) -> Result<(), String> {
+        let Some(item) = catalogue.get(&stack.item_type).filter(|t| t.is_item()) else {
+            return Err(format!("{} cannot be carried", stack.item_type));
+        };
+        if stack.count == 0 {
+            return Ok(());
+        }
+        let added_weight = item.weight() * stack.count as f32;
+        if self.weight(catalogue) + added_weight > self.max_weight + f32::EPSILON {
+            return Err(format!(
+                "too heavy: {} {} weigh {added_weight:.1} kg",
+                stack.count, stack.item_type
+            ));
+        }
+
+        let max_stack = item.max_stack.max(1);
+        let mut slots = self.slots.clone();
+        let mut remaining = stack.count;
+        for existing in slots.iter_mut().flatten() {
+            if existing.item_type == stack.item_type
+                && existing.owner == stack.owner
+                && existing.properties == stack.properties
+            {
+                let moved = remaining.min(max_stack.saturating_sub(existing.count));
+                existing.count += moved;
+                remaining -= moved;
+            }
+        }
+        for slot in slots.iter_mut().filter(|slot| slot.is_none()) {
+            if remaining == 0 {
+                break;
+            }
+            let moved = remaining.min(max_stack);
+            *slot = Some(ItemStack {
+                count: moved,
+                ..stack.clone()
+            });
+            remaining -= moved;
+        }
+        if remaining > 0 {
+            return Err("no free slot".to_string());
+        }
+        self.slots = slots;
+        Ok(())
+    }
+
+    /// Take up to `count` items out of a slot.
+    pub fn remove(&mut self, slot: usize, count: u32) -> Result<ItemStack, String> {
+        let Some(Some(stack)) = self.slots.get_mut(slot) else {
+            return Err(format!("slot {slot} is empty"));
+        };
+        if count == 0 || count > stack.count {
+            return Err(format!(
+                "slot {slot} holds {} {}",
+                stack.count, stack.item_type
+            ));
+        }
+        let taken = ItemStack {
+            count,
+            ..stack.clone()
+        };
+        stack.count -= count;
+        if stack.count == 0 {
+            self.slots[slot] = None;
+        }
+        Ok(taken)
+    }
+
+    /// Take `count` items of a type, from as many slots as needed, all or nothing.
+    pub fn take(&mut self, item_type: &str, count: u32) -> Result<Vec<ItemStack>, String> {
+        if self.count(item_type) < count {
+            return Err(format!("needs {count} {item_type}"));
+        }
+        let slots: Vec<usize> = self
+            .stacks()
+            .filter(|(_, stack)| stack.item_type == item_type)
+            .map(|(slot, _)| slot)
+            .collect();
+        let mut taken = Vec::new();
+        let mut remaining = count;
+        for slot in slots {
+            if remaining == 0 {
+                break;
+            }
+            let held = self.slots[slot].as_ref().map_or(0, |stack| stack.count);
+            let stack = self.remove(slot, remaining.min(held))?;
+            remaining -= stack.count;
+            taken.push(stack);
+        }
+        Ok(taken)
+    }
+
+    /// Put stacks restored from storage back into their slots.
+    ///
+    /// Slots beyond the inventory's size are skipped and returned.
+    pub fn restore(&mut self, stacks: Vec<(usize, ItemStack)>) -> Vec<(usize, ItemStack)> {
+        let mut skipped = Vec::new();
+        for (slot, stack) in stacks {
+            match self.slots.get_mut(slot) {
+                Some(entry) => *entry = Some(stack),
+                None => skipped.push((slot, stack)),
+            }
+        }
+        skipped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn catalogue() -> Catalogue {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/objects");
+        Catalogue::load_dir(&dir).expect("object files should parse")
+    }
+
+    fn stack(item_type: &str, count: u32) -> ItemStack {
+        ItemStack {
+            item_type: item_type.to_string(),
+            count,
+            owner: None,
+            properties: Properties::new(),
+        }
+    }
+
+    #[test]
+    fn stacks_fill_up_before_using_new_slots() {
+        let catalogue = catalogue();
+        let mut inventory = Inventory::new(2, 20.0);
+        inventory.add(stack("plate", 5), &catalogue).unwrap();
+        inventory.add(stack("plate", 5), &catalogue).unwrap();
+        assert_eq!(inventory.slots[0].as_ref().unwrap().count, 8);
+        assert_eq!(inventory.slots[1].as_ref().unwrap().count, 2);
+        assert_eq!(inventory.count("plate"), 10);
+        assert!(inventory.add(stack("mug", 1), &catalogue).is_err());
+
+        let taken = inventory.remove(1, 2).unwrap();
+        assert_eq!(taken.count, 2);
+        assert_eq!(inventory.slots[1], None);
+        assert!(inventory.remove(0, 9).is_err());
+        assert!(inventory.take("plate", 9).is_err());
+        assert_eq!(inventory.take("plate", 8).unwrap().len(), 1);
+        assert_eq!(inventory.count("plate"), 0);
+    }
+
+    #[test]
+    fn weight_and_type_limits() {
+        let catalogue = catalogue();
+        let mut inventory = Inventory::new(4, 2.0);
+        assert!(inventory.add(stack("cooking_pot", 2), &catalogue).is_err());
+        assert_eq!(inventory, Inventory::new(4, 2.0));
+        assert!(inventory.add(stack("cooking_pot", 1), &catalogue).is_ok());
+        assert!(inventory.add(stack("fridge", 1), &catalogue).is_err());
+    }
+}
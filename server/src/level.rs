@@ -0,0 +1,820 @@
+//! Level description module.
+//!
+//! Describes the static world (ground, buildings, rooms, ship decks) as data so new
+//! areas can be built without editing Rust code. Levels are JSON files under
+//! `data/levels/`; the physics world turns each geometry piece into fixed colliders.
+//! Terrain can come from grayscale heightmap images or OBJ meshes under `data/`.
+//! Units: meters (1 unit = 1 m), Y-axis up.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Default level file name, used when `LEVEL_PATH` is not set.
+const DEFAULT_LEVEL_FILE: &str = "arena.json";
+
+/// Static level description loaded from a level file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Level {
+    /// Human-readable level name
+    pub name: String,
+    /// Horizontal area where dynamic objects may be (re)spawned
+    #[serde(default)]
+    pub bounds: Bounds,
+    /// Static geometry pieces (floors, walls, ramps, stairs, decks)
+    #[serde(default)]
+    pub geometry: Vec<Geometry>,
+    /// Navigation shortcuts the geometry alone does not provide (elevators, ladders)
+    #[serde(default)]
+    pub links: Vec<NavLink>,
+    /// Furniture, appliances and items present when the level loads
+    #[serde(default)]
+    pub objects: Vec<Placement>,
+    /// Where shop deliveries are set down [x, y, z] (defaults to the center of the bounds)
+    #[serde(default)]
+    pub delivery_point: Option<[f32; 3]>,
+    /// Where new characters appear [x, y, z] (defaults to the center of the bounds)
+    #[serde(default)]
+    pub spawn_point: Option<[f32; 3]>,
+    /// Roofed areas where characters are out of the weather
+    #[serde(default)]
+    pub shelters: Vec<Bounds>,
+}
+
+/// Catalogue object placed in the level.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Placement {
+    /// Entity ID (stable, so stored container contents find their container)
+    pub id: String,
+    /// Catalogue type name
+    pub object_type: String,
+    /// Point the object stands on [x, y, z]
+    pub position: [f32; 3],
+    /// Player who owns the object, if anyone
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Initial contents of a container: (item type, count)
+    #[serde(default)]
+    pub contents: Vec<(String, u32)>,
+}
+
+/// Two-way connection between two walkable points, travelled without walking
+/// (e.g. an elevator between floors). Points are feet positions [x, y, z].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NavLink {
+    /// Name for logs (e.g. "loft_lift")
+    pub name: String,
+    pub from: [f32; 3],
+    pub to: [f32; 3],
+    /// Travel time in game seconds, used as path cost
+    #[serde(default = "default_link_seconds")]
+    pub seconds: f32,
+}
+
+fn default_link_seconds() -> f32 {
+    10.0
+}
+
+/// Axis-aligned horizontal bounds (X/Z plane) in meters.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Bounds {
+    /// Minimum X/Z corner
+    pub min: [f32; 2],
+    /// Maximum X/Z corner
+    pub max: [f32; 2],
+}
+
+impl Bounds {
+    /// Check that the area is not empty: min is below max on both axes.
+    pub fn validate(&self) -> Result<(), String> {
+        for (axis, name) in [(0, "x"), (1, "z")] {
+            // NaN compares as nothing, so it fails too
+            if self.min[axis].partial_cmp(&self.max[axis]) != Some(std::cmp::Ordering::Less) {
+                return Err(format!(
+                    "min {name} {} is not below max {name} {}",
+                    self.min[axis], self.max[axis]
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Bounds {
+    /// 100 m × 100 m area centered on the origin.
+    fn default() -> Self {
+        Self {
+            min: [-50.0, -50.0],
+            max: [50.0, 50.0],
+        }
+    }
+}
+
+/// Surface material shared by all geometry pieces.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Material {
+    /// Friction coefficient
+    #[serde(default = "default_friction")]
+    pub friction: f32,
+    /// Restitution (bounciness, 1.0 = perfectly elastic)
+    #[serde(default)]
+    pub restitution: f32,
+}
+
+fn default_friction() -> f32 {
+    0.5
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            friction: default_friction(),
+            restitution: 0.0,
+        }
+    }
+}
+
+/// A single piece of static geometry.
+///
+/// Serialized with a "kind" tag, e.g. `{ "kind": "floor", "name": "lounge", ... }`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Geometry {
+    /// Flat rectangular floor; the top surface is at `center[1]`
+    Floor {
+        name: String,
+        /// Center of the top surface (x, y, z)
+        center: [f32; 3],
+        /// Full size along X and Z
+        size: [f32; 2],
+        /// Slab thickness below the top surface
+        #[serde(default = "default_thickness")]
+        thickness: f32,
+        #[serde(default)]
+        material: Material,
+    },
+    /// Vertical wall running along a segment in the X/Z plane
+    Wall {
+        name: String,
+        /// Segment start (x, z)
+        from: [f32; 2],
+        /// Segment end (x, z)
+        to: [f32; 2],
+        /// Height of the wall base
+        #[serde(default)]
+        base_y: f32,
+        height: f32,
+        #[serde(default = "default_thickness")]
+        thickness: f32,
+        #[serde(default)]
+        material: Material,
+    },
+    /// Inclined slab from a bottom edge to a top edge
+    Ramp {
+        name: String,
+        /// Center of the bottom edge of the walking surface (x, y, z)
+        from: [f32; 3],
+        /// Center of the top edge of the walking surface (x, y, z)
+        to: [f32; 3],
+        width: f32,
+        #[serde(default = "default_thickness")]
+        thickness: f32,
+        #[serde(default)]
+        material: Material,
+    },
+    /// Flight of stairs from a bottom edge to a top edge
+    Stairs {
+        name: String,
+        /// Center of the bottom edge of the first step (x, y, z)
+        from: [f32; 3],
+        /// Center of the top edge of the last step (x, y, z)
+        to: [f32; 3],
+        width: f32,
+        steps: u32,
+        #[serde(default)]
+        material: Material,
+    },
+    /// Polygonal floor (e.g. a ship deck footprint); the top surface is at `y`
+    Deck {
+        name: String,
+        /// Outline in the X/Z plane (simple polygon, either winding)
+        outline: Vec<[f32; 2]>,
+        y: f32,
+        #[serde(default)]
+        material: Material,
+    },
+    /// Terrain from a grayscale heightmap image (black = `center[1]`, white = `+max_height`)
+    ///
+    /// Image rows run along Z (top row at −Z), columns along X (left column at −X).
+    Heightmap {
+        name: String,
+        /// PNG path relative to the data directory
+        image: String,
+        /// Center of the terrain base (x, y, z)
+        center: [f32; 3],
+        /// Full size along X and Z
+        size: [f32; 2],
+        max_height: f32,
+        /// Sampled heights, filled in from `image` when the level is loaded
+        #[serde(default)]
+        heights: Option<HeightGrid>,
+        #[serde(default)]
+        material: Material,
+    },
+    /// Terrain from a triangle mesh, either inline or loaded from an OBJ file
+    Mesh {
+        name: String,
+        /// OBJ path relative to the data directory
+        #[serde(default)]
+        path: Option<String>,
+        /// Translation applied to the mesh vertices
+        #[serde(default)]
+        offset: [f32; 3],
+        /// Mesh data, filled in from `path` when the level is loaded
+        #[serde(default)]
+        mesh: Option<TriMesh>,
+        #[serde(default)]
+        material: Material,
+    },
+}
+
+/// Regular grid of normalized heights (0.0 – 1.0), row-major.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HeightGrid {
+    /// Number of rows (samples along Z)
+    pub rows: usize,
+    /// Number of columns (samples along X)
+    pub cols: usize,
+    /// `rows * cols` heights, row by row
+    pub heights: Vec<f32>,
+}
+
+impl HeightGrid {
+    /// Decode a grayscale (or RGB, using the red channel) PNG into a height grid.
+    pub fn from_png(path: &Path) -> anyhow::Result<Self> {
+        let mut decoder = png::Decoder::new(std::io::BufReader::new(std::fs::File::open(path)?));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0; reader.output_buffer_size().unwrap_or_default()];
+        let info = reader.next_frame(&mut buf)?;
+        let channels = info.color_type.samples();
+        let (cols, rows) = (info.width as usize, info.height as usize);
+        anyhow::ensure!(
+            rows >= 2 && cols >= 2,
+            "heightmap must be at least 2×2 pixels"
+        );
+        let heights = (0..rows * cols)
+            .map(|i| {
+                let (row, col) = (i / cols, i % cols);
+                buf[row * info.line_size + col * channels] as f32 / 255.0
+            })
+            .collect();
+        Ok(Self {
+            rows,
+            cols,
+            heights,
+        })
+    }
+}
+
+fn default_thickness() -> f32 {
+    0.2
+}
+
+/// An oriented box produced from a geometry piece.
+///
+/// `yaw` rotates around Y, then `pitch` tilts around the box's local X axis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoxShape {
+    pub center: [f32; 3],
+    pub half_extents: [f32; 3],
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+/// Triangle mesh in world coordinates.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TriMesh {
+    /// Vertex positions (x, y, z)
+    pub vertices: Vec<[f32; 3]>,
+    /// Triangles as indices into `vertices`
+    pub indices: Vec<[u32; 3]>,
+}
+
+impl Geometry {
+    /// Name of this piece, reported as the `World` side of physics events.
+    pub fn name(&self) -> &str {
+        match self {
+            Geometry::Floor { name, .. }
+            | Geometry::Wall { name, .. }
+            | Geometry::Ramp { name, .. }
+            | Geometry::Stairs { name, .. }
+            | Geometry::Deck { name, .. }
+            | Geometry::Heightmap { name, .. }
+            | Geometry::Mesh { name, .. } => name,
+        }
+    }
+
+    /// Surface material of this piece.
+    pub fn material(&self) -> &Material {
+        match self {
+            Geometry::Floor { material, .. }
+            | Geometry::Wall { material, .. }
+            | Geometry::Ramp { material, .. }
+            | Geometry::Stairs { material, .. }
+            | Geometry::Deck { material, .. }
+            | Geometry::Heightmap { material, .. }
+            | Geometry::Mesh { material, .. } => material,
+        }
+    }
+
+    /// Oriented boxes making up this piece (empty for decks and terrain).
+    pub fn boxes(&self) -> Vec<BoxShape> {
+        match self {
+            Geometry::Floor {
+                center,
+                size,
+                thickness,
+                ..
+            } => vec![BoxShape {
+                center: [center[0], center[1] - thickness * 0.5, center[2]],
+                half_extents: [size[0] * 0.5, thickness * 0.5, size[1] * 0.5],
+                yaw: 0.0,
+                pitch: 0.0,
+            }],
+            Geometry::Wall {
+                from,
+                to,
+                base_y,
+                height,
+                thickness,
+                ..
+            } => {
+                let (dx, dz) = (to[0] - from[0], to[1] - from[1]);
+                let length = (dx * dx + dz * dz).sqrt();
+                vec![BoxShape {
+                    center: [
+                        (from[0] + to[0]) * 0.5,
+                        base_y + height * 0.5,
+                        (from[1] + to[1]) * 0.5,
+                    ],
+                    // Local Z runs along the wall
+                    half_extents: [thickness * 0.5, height * 0.5, length * 0.5],
+                    yaw: dx.atan2(dz),
+                    pitch: 0.0,
+                }]
+            }
+            Geometry::Ramp {
+                from,
+                to,
+                width,
+                thickness,
+                ..
+            } => {
+                let (dx, dy, dz) = (to[0] - from[0], to[1] - from[1], to[2] - from[2]);
+                let run = (dx * dx + dz * dz).sqrt();
+                let length = (run * run + dy * dy).sqrt();
+                let pitch = dy.atan2(run);
+                // Shift the slab down so its top surface passes through from/to
+                let (down_y, down_run) =
+                    (pitch.cos() * thickness * 0.5, pitch.sin() * thickness * 0.5);
+                let (dir_x, dir_z) = if run > 0.0 {
+                    (dx / run, dz / run)
+                } else {
+                    (0.0, 1.0)
+                };
+                vec![BoxShape {
+                    center: [
+                        (from[0] + to[0]) * 0.5 + dir_x * down_run,
+                        (from[1] + to[1]) * 0.5 - down_y,
+                        (from[2] + to[2]) * 0.5 + dir_z * down_run,
+                    ],
+                    half_extents: [width * 0.5, thickness * 0.5, length * 0.5],
+                    yaw: dx.atan2(dz),
+                    pitch,
+                }]
+            }
+            Geometry::Stairs {
+                from,
+                to,
+                width,
+                steps,
+                ..
+            } => {
+                let steps = (*steps).max(1);
+                let (dx, dy, dz) = (to[0] - from[0], to[1] - from[1], to[2] - from[2]);
+                let run = (dx * dx + dz * dz).sqrt();
+                let yaw = dx.atan2(dz);
+                let rise = dy / steps as f32;
+                let tread = run / steps as f32;
+                // Each step is a solid block from the bottom of the flight to its tread height
+                (0..steps)
+                    .map(|i| {
+                        let t = (i as f32 + 0.5) / steps as f32;
+                        let top = from[1] + rise * (i + 1) as f32;
+                        BoxShape {
+                            center: [from[0] + dx * t, (from[1] + top) * 0.5, from[2] + dz * t],
+                            half_extents: [width * 0.5, (top - from[1]) * 0.5, tread * 0.5],
+                            yaw,
+                            pitch: 0.0,
+                        }
+                    })
+                    .collect()
+            }
+            Geometry::Deck { .. } | Geometry::Heightmap { .. } | Geometry::Mesh { .. } => {
+                Vec::new()
+            }
+        }
+    }
+
+    /// Triangle mesh for polygonal pieces.
+    pub fn mesh(&self) -> Option<TriMesh> {
+        match self {
+            Geometry::Deck { outline, y, .. } => Some(TriMesh {
+                vertices: outline.iter().map(|p| [p[0], *y, p[1]]).collect(),
+                indices: triangulate(outline),
+            }),
+            Geometry::Mesh {
+                offset,
+                mesh: Some(mesh),
+                ..
+            } => Some(TriMesh {
+                vertices: mesh
+                    .vertices
+                    .iter()
+                    .map(|v| [v[0] + offset[0], v[1] + offset[1], v[2] + offset[2]])
+                    .collect(),
+                indices: mesh.indices.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether this piece is terrain (sent to clients so they can render it).
+    pub fn is_terrain(&self) -> bool {
+        matches!(self, Geometry::Heightmap { .. } | Geometry::Mesh { .. })
+    }
+
+    /// Load external terrain data (heightmap images, OBJ meshes) into the piece.
+    fn resolve(&mut self, data_dir: &Path) -> anyhow::Result<()> {
+        match self {
+            Geometry::Heightmap {
+                image,
+                heights: heights @ None,
+                ..
+            } => *heights = Some(HeightGrid::from_png(&data_dir.join(image.as_str()))?),
+            Geometry::Mesh {
+                path: Some(path),
+                mesh: mesh @ None,
+                ..
+            } => *mesh = Some(TriMesh::from_obj(&data_dir.join(path.as_str()))?),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl TriMesh {
+    /// Load a triangle mesh from a Wavefront OBJ file.
+    ///
+    /// Only vertex positions and faces are read; polygons are fan-triangulated.
+    pub fn from_obj(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for line in text.lines() {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("v") => {
+                    let coords: Vec<f32> =
+                        parts.take(3).map(str::parse).collect::<Result<_, _>>()?;
+                    anyhow::ensure!(coords.len() == 3, "vertex needs 3 coordinates: {line}");
+                    vertices.push([coords[0], coords[1], coords[2]]);
+                }
+                Some("f") => {
+                    // Face entries look like `i`, `i/t`, `i//n` or `i/t/n`; negative indices count back
+                    let face: Vec<u32> = parts
+                        .map(|entry| {
+                            let index: i64 = entry.split('/').next().unwrap_or_default().parse()?;
+                            let resolved = if index < 0 {
+                                vertices.len() as i64 + index
+                            } else {
+                                index - 1
+                            };
+                            anyhow::ensure!(
+                                (0..vertices.len() as i64).contains(&resolved),
+                                "face index out of range: {line}"
+                            );
+                            Ok(resolved as u32)
+                        })
+                        .collect::<anyhow::Result<_>>()?;
+                    for i in 1..face.len().saturating_sub(1) {
+                        indices.push([face[0], face[i], face[i + 1]]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(Self { vertices, indices })
+    }
+}
+
+/// Triangulate a simple polygon by ear clipping.
+///
+/// Works for concave outlines such as the U-shaped upper ship decks.
+pub fn triangulate(outline: &[[f32; 2]]) -> Vec<[u32; 3]> {
+    let n = outline.len();
+    if n < 3 {
+        return Vec::new();
+    }
+    // Twice the signed area; positive means counter-clockwise
+    let area2: f32 = (0..n)
+        .map(|i| {
+            let (a, b) = (outline[i], outline[(i + 1) % n]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum();
+    let ccw = area2 > 0.0;
+    let cross = |a: [f32; 2], b: [f32; 2], c: [f32; 2]| {
+        let c = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+        if ccw {
+            c
+        } else {
+            -c
+        }
+    };
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = Vec::with_capacity(n - 2);
+    let mut guard = 0;
+    while remaining.len() > 3 && guard < n * n {
+        guard += 1;
+        let m = remaining.len();
+        let ear = (0..m).find(|&i| {
+            let (ia, ib, ic) = (
+                remaining[(i + m - 1) % m],
+                remaining[i],
+                remaining[(i + 1) % m],
+            );
+            let (a, b, c) = (outline[ia], outline[ib], outline[ic]);
+            if cross(a, b, c) <= 0.0 {
+                return false;
+            }
+            // No other vertex may lie inside the candidate ear
+            remaining.iter().all(|&j| {
+                j == ia
+                    || j == ib
+                    || j == ic
+                    || !(cross(a, b, outline[j]) >= 0.0
+                        && cross(b, c, outline[j]) >= 0.0
+                        && cross(c, a, outline[j]) >= 0.0)
+            })
+        });
+        let Some(i) = ear else {
+            break;
+        };
+        let prev = remaining[(i + m - 1) % m];
+        let next = remaining[(i + 1) % m];
+        triangles.push([prev as u32, remaining[i] as u32, next as u32]);
+        remaining.remove(i);
+    }
+    if remaining.len() == 3 {
+        triangles.push([
+            remaining[0] as u32,
+            remaining[1] as u32,
+            remaining[2] as u32,
+        ]);
+    }
+    triangles
+}
+
+impl Level {
+    /// Where new characters appear: the spawn point, or the center of the bounds.
+    pub fn spawn_point(&self) -> [f32; 3] {
+        self.spawn_point.unwrap_or([
+            (self.bounds.min[0] + self.bounds.max[0]) / 2.0,
+            0.0,
+            (self.bounds.min[1] + self.bounds.max[1]) / 2.0,
+        ])
+    }
+
+    /// Whether a point is under one of the level's shelters.
+    pub fn is_sheltered(&self, x: f32, z: f32) -> bool {
+        self.shelters.iter().any(|shelter| {
+            (shelter.min[0]..=shelter.max[0]).contains(&x)
+                && (shelter.min[1]..=shelter.max[1]).contains(&z)
+        })
+    }
+
+    /// Built-in 100 m × 100 m walled arena.
+    ///
+    /// Used when no level file can be found. Matches `data/levels/arena.json`.
+    pub fn arena() -> Self {
+        let bouncy = Material {
+            friction: 0.0,
+            restitution: 1.0, // Perfect elasticity
+        };
+        let wall = |name: &str, from: [f32; 2], to: [f32; 2]| Geometry::Wall {
+            name: name.to_string(),
+            from,
+            to,
+            base_y: 0.0,
+            height: 20.0, // Tall enough to contain high bounces
+            thickness: 1.0,
+            material: bouncy.clone(),
+        };
+        Self {
+            name: "arena".to_string(),
+            bounds: Bounds::default(),
+            geometry: vec![
+                Geometry::Floor {
+                    name: "ground".to_string(),
+                    center: [0.0, 0.0, 0.0],
+                    size: [100.0, 100.0],
+                    thickness: 0.2,
+                    material: bouncy.clone(),
+                },
+                // Walls sit just outside the ground so their inner faces are at ±50
+                wall("wall_east", [50.5, -50.0], [50.5, 50.0]),
+                wall("wall_west", [-50.5, -50.0], [-50.5, 50.0]),
+                wall("wall_north", [-50.0, 50.5], [50.0, 50.5]),
+                wall("wall_south", [-50.0, -50.5], [50.0, -50.5]),
+            ],
+            links: Vec::new(),
+            objects: Vec::new(),
+            delivery_point: None,
+            spawn_point: None,
+            shelters: Vec::new(),
+        }
+    }
+
+    /// Load a level description from a JSON file.
+    ///
+    /// Heightmap images and OBJ meshes are read relative to the data directory.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let mut level: Level = serde_json::from_str(&text)?;
+        // Objects are respawned at random positions within the bounds
+        level
+            .bounds
+            .validate()
+            .map_err(|e| anyhow::anyhow!("bounds: {e}"))?;
+        let data_dir = data_dir();
+        for geometry in &mut level.geometry {
+            geometry
+                .resolve(&data_dir)
+                .map_err(|e| anyhow::anyhow!("terrain '{}': {e}", geometry.name()))?;
+        }
+        Ok(level)
+    }
+
+    /// Terrain pieces with their height/mesh data, for sending to clients.
+    pub fn terrain(&self) -> Vec<Geometry> {
+        self.geometry
+            .iter()
+            .filter(|g| g.is_terrain())
+            .cloned()
+            .collect()
+    }
+
+    /// Load the configured level.
+    ///
+    /// Uses `LEVEL_PATH` if set, otherwise `data/levels/arena.json`. Falls back to
+    /// the built-in arena if the file is missing or invalid.
+    pub fn load_configured() -> Self {
+        let path = std::env::var("LEVEL_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| data_dir().join("levels").join(DEFAULT_LEVEL_FILE));
+        match Self::load(&path) {
+            Ok(level) => {
+                tracing::info!(
+                    "Loaded level '{}' ({} geometry pieces) from {}",
+                    level.name,
+                    level.geometry.len(),
+                    path.display()
+                );
+                level
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to load level from {}: {e}; using built-in arena",
+                    path.display()
+                );
+                Self::arena()
+            }
+        }
+    }
+}
+
+/// Returns the path to the game data directory (levels, catalogues).
+/// Tries multiple locations for local dev vs Docker/production.
+pub fn data_dir() -> PathBuf {
+    let candidates = [
+        "data",    // From project root
+        "../data", // From server/ directory
+    ];
+    for path in candidates {
+        let p = Path::new(path);
+        if p.is_dir() {
+            return p.to_path_buf();
+        }
+    }
+    candidates[0].into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arena_file_matches_built_in_arena() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/levels/arena.json");
+        let from_file = Level::load(&path).expect("arena.json should parse");
+        let boxes = |level: &Level| -> Vec<Vec<BoxShape>> {
+            level.geometry.iter().map(Geometry::boxes).collect()
+        };
+        assert_eq!(boxes(&from_file), boxes(&Level::arena()));
+    }
+
+    #[test]
+    fn all_level_files_parse() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/levels");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if let Err(e) = Level::load(&path) {
+                panic!("{} failed to parse: {e}", path.display());
+            }
+        }
+    }
+
+    #[test]
+    fn empty_or_inverted_bounds_are_rejected() {
+        assert!(Bounds::default().validate().is_ok());
+        let inverted = Bounds {
+            min: [10.0, -5.0],
+            max: [-10.0, 5.0],
+        };
+        assert!(inverted.validate().is_err());
+        let empty = Bounds {
+            min: [0.0, 3.0],
+            max: [1.0, 3.0],
+        };
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn outdoor_terrain_is_resolved() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/levels/outdoor.json");
+        let level = Level::load(&path).unwrap();
+        for piece in level.terrain() {
+            match piece {
+                Geometry::Heightmap {
+                    heights: Some(grid),
+                    ..
+                } => assert_eq!(grid.heights.len(), grid.rows * grid.cols),
+                Geometry::Mesh { .. } => assert_eq!(piece.mesh().unwrap().indices.len(), 4),
+                other => panic!("unresolved terrain piece: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn triangulates_concave_outline() {
+        // U shape: 3 m wide, 2 m deep, with a 1 m notch cut from the top
+        let outline = [
+            [0.0, 0.0],
+            [3.0, 0.0],
+            [3.0, 2.0],
+            [2.0, 2.0],
+            [2.0, 1.0],
+            [1.0, 1.0],
+            [1.0, 2.0],
+            [0.0, 2.0],
+        ];
+        let triangles = triangulate(&outline);
+        assert_eq!(triangles.len(), outline.len() - 2);
+        let area: f32 = triangles
+            .iter()
+            .map(|t| {
+                let [a, b, c] = t.map(|i| outline[i as usize]);
+                ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])).abs() * 0.5
+            })
+            .sum();
+        assert!((area - 5.0).abs() < 1e-4, "area was {area}");
+    }
+
+    #[test]
+    fn stairs_reach_top_height() {
+        let stairs = Geometry::Stairs {
+            name: "stairs".to_string(),
+            from: [0.0, 0.0, 0.0],
+            to: [0.0, 2.0, 4.0],
+            width: 1.0,
+            steps: 8,
+            material: Material::default(),
+        };
+        let boxes = stairs.boxes();
+        assert_eq!(boxes.len(), 8);
+        let last = boxes.last().unwrap();
+        assert!((last.center[1] + last.half_extents[1] - 2.0).abs() < 1e-5);
+    }
+}
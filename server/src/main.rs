@@ -7,44 +7,81 @@
 use axum::extract::WebSocketUpgrade;
 use axum::{extract::State, response::Response, routing::get, Router};
 use sqlx::PgPool;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{mpsc, RwLock};
 use tower_http::{cors::CorsLayer, services::ServeDir};
 
 // mod auth;  // Commented out - users/sessions tables not in use
+mod account;
+mod activity;
+mod calendar;
+mod catalogue;
+mod chat;
 mod db;
+mod economy;
+mod environment;
+mod food;
 mod game;
+mod gateway;
+mod genetics;
+mod health;
+mod interaction;
+mod inventory;
+mod level;
 mod messages;
+mod mood;
+mod nav;
+mod needs;
+mod npc;
 mod physics;
+mod properties;
+mod relationship;
+mod schedule;
+mod shard;
 mod websocket;
+mod zone;
 
-use db::{create_pool, save_all_entities, set_game_time_minutes};
+use catalogue::Catalogue;
+use db::{
+    create_pool, load_accounts, load_economy, load_entities, load_entity_properties,
+    load_inventories, load_relationships, save_accounts, save_chat_messages, save_economy,
+    save_objects, save_relationships, seed_entity_types, set_game_time_minutes, EconomyChanges,
+};
 use game::GameState;
+use level::Level;
 use messages::GameMessage;
+use physics::PhysicsEvent;
+use shard::Shard;
 use websocket::handle_websocket;
+use zone::{Zone, Zones};
 
 /// Application state shared across all request handlers.
 ///
 /// Contains:
-/// - `game`: Thread-safe game state (players, entities, physics)
+/// - `zones`: Zones hosted by this server, each with its own game state (players,
+///   entities, physics) and channel for broadcasting world state updates
+/// - `shard`: Zone directory, for handing characters to zones on other servers
 /// - `db`: PostgreSQL connection pool
-/// - `broadcast_tx`: Channel for broadcasting world state updates to all connected clients
+/// - `clients`: Channels to individual connected clients, for targeted messages such as chat
 #[derive(Clone)]
 pub struct AppState {
-    /// Thread-safe game state containing players, entities, and physics simulation
-    pub game: Arc<RwLock<GameState>>,
+    /// Zones hosted by this server, the home zone among them if hosted here
+    pub zones: Arc<Zones>,
+    /// Where the zones hosted by other servers are, and handoff tickets
+    pub shard: Arc<Shard>,
     /// PostgreSQL database connection pool (optional for local dev)
     pub db: Option<PgPool>,
-    /// Broadcast channel sender for distributing world state updates to WebSocket clients
-    pub broadcast_tx: broadcast::Sender<String>,
+    /// Message channel of each connected client, by player ID
+    pub clients: Arc<RwLock<HashMap<String, mpsc::Sender<String>>>>,
 }
 
 /// Main entry point for the Time Helm server.
 ///
 /// Initializes:
 /// 1. Database connection pool
-/// 2. Game state (thread-safe)
-/// 3. Background tasks for:
+/// 2. Game state of every zone (thread-safe)
+/// 3. Background tasks, for every zone, for:
 ///    - Game time persistence (every 60 seconds)
 ///    - Entity persistence (every 60 seconds)
 ///    - Physics simulation (60 FPS)
@@ -57,6 +94,16 @@ async fn main() -> anyhow::Result<()> {
     // Initialize tracing for structured logging
     tracing_subscriber::fmt::init();
 
+    // Get port from environment variable or default to 8080
+    let port = std::env::var("PORT")
+        .unwrap_or_else(|_| "8080".to_string())
+        .parse::<u16>()?;
+
+    // A gateway only relays client connections to the servers hosting the zones
+    if gateway::enabled() {
+        return gateway::serve(port, static_dir()).await;
+    }
+
     // Connect to PostgreSQL database (optional for local dev).
     // If DATABASE_URL is not set, the server still runs, but persistence is disabled.
     let pool = match std::env::var("DATABASE_URL") {
@@ -71,16 +118,62 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
-    // Initialize game state with thread-safe access
-    let game_state = Arc::new(RwLock::new(GameState::new()));
-    // Create broadcast channel for sending world state updates to all WebSocket clients
-    // Channel capacity: 100 messages
-    let (broadcast_tx, _) = broadcast::channel::<String>(100);
+    // Static world geometry of the home zone is loaded from the level file
+    // (LEVEL_PATH or data/levels/arena.json); further zones are listed in
+    // ZONES_PATH or data/zones.json
+    let home_level = Level::load_configured();
+    let home_zone = home_level.name.clone();
+    let mut levels = vec![(home_zone.clone(), home_level)];
+    for (name, level) in zone::load_configured() {
+        if levels.iter().any(|(existing, _)| *existing == name) {
+            tracing::warn!("Zone {name} is already hosted; skipping the duplicate");
+            continue;
+        }
+        levels.push((name, level));
+    }
+    // With a zone directory (ZONE_DIRECTORY_PATH) this server only hosts the zones
+    // the directory gives to it; the others are reached by handoff
+    let shard = Shard::configured(port);
+    levels.retain(|(name, _)| shard.hosts(name));
+    if levels.is_empty() {
+        anyhow::bail!("the zone directory gives no zone to this server");
+    }
+    // Object types (humans, balls, furniture, appliances) are loaded from data/objects/
+    let catalogue = Catalogue::load_configured();
+    if let Some(pool) = &pool {
+        if let Err(e) = seed_entity_types(pool, &catalogue).await {
+            tracing::error!("Failed to seed entity types: {e}");
+        }
+    }
+    // Recipes are loaded from data/recipes/
+    let recipes = food::load_configured();
+    let mut zones = Vec::new();
+    for (name, level) in levels {
+        let home = name == home_zone;
+        let mut game = GameState::with_level(level, catalogue.clone());
+        // Chat filter from data/chat/filter.json; history is kept if PERSIST_CHAT is set
+        game.chat = chat::Chat::configured();
+        for recipe in &recipes {
+            game.add_recipe(recipe.clone());
+        }
+        // Non-player characters are loaded from data/npcs/ and live in the home zone
+        if home {
+            for def in npc::load_configured() {
+                game.spawn_npc(def);
+            }
+        }
+        if let Some(pool) = &pool {
+            restore_zone(pool, &mut game, &name, home).await;
+        }
+        tracing::info!("Hosting zone {name}");
+        zones.push(Zone::new(name, game));
+    }
 
     let app_state = AppState {
-        game: game_state,
+        zones: Arc::new(Zones::new(zones, home_zone)),
+        shard: Arc::new(shard),
         db: pool.clone(),
-        broadcast_tx: broadcast_tx.clone(),
+        clients: Arc::new(RwLock::new(HashMap::new())),
     };
 
     if let Some(pool) = pool.clone() {
@@ -100,58 +193,363 @@ async fn main() -> anyhow::Result<()> {
             }
         });
 
-        // Background task: Persist all entities to database every real-world minute
-        // This ensures entity positions and states are saved periodically
-        let persist_pool_entities = pool.clone();
-        let game_state_for_entities = app_state.game.clone();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
-            loop {
-                interval.tick().await;
-                // Read lock to get all entities, then drop lock before database write
-                let game = game_state_for_entities.read().await;
-                let entities: Vec<_> = game.get_all_entities();
-                drop(game);
+        for zone in app_state.zones.all() {
+            spawn_persistence(&pool, zone);
+        }
+    }
 
-                if let Err(e) = save_all_entities(&persist_pool_entities, &entities).await {
-                    tracing::error!("Failed to persist entities: {e}");
-                } else {
-                    tracing::debug!("Persisted {} entities", entities.len());
-                }
-            }
-        });
+    // Forward collision events to clients (e.g. for impact sounds) when enabled
+    let forward_physics_events = std::env::var("FORWARD_PHYSICS_EVENTS")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    for zone in app_state.zones.all() {
+        spawn_simulation(zone, forward_physics_events);
+    }
+
+    // Background task: Take out characters handed off by other servers whose
+    // transfer was never confirmed
+    let expiry_state = app_state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
+        loop {
+            interval.tick().await;
+            shard::expire_arrivals(&expiry_state).await;
+        }
+    });
+
+    // Set up HTTP routes
+    let app = Router::new()
+        // WebSocket endpoint for game client connections
+        .route("/ws", get(websocket_handler))
+        // Handoff endpoints called by the servers hosting other zones
+        .merge(shard::routes())
+        // Serve static files from client/public (index.html = ship game at root)
+        .fallback_service(ServeDir::new(static_dir()).append_index_html_on_directories(true))
+        // Enable CORS for all origins (development)
+        .layer(CorsLayer::permissive())
+        .with_state(app_state);
+
+    // Bind to all network interfaces on the specified port
+    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{port}")).await?;
+    tracing::info!("Server listening on 0.0.0.0:{port} — open http://localhost:{port}/");
+
+    // Start the HTTP server
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+/// Restore a zone's objects, entity properties and inventories from the database,
+/// and for the home zone what lives there only.
+///
+/// Characters start out in the home zone, so the other zones only get back the
+/// inventories of their containers.
+async fn restore_zone(pool: &PgPool, game: &mut GameState, zone: &str, home: bool) {
+    let entity_ids: Vec<String> = game.get_all_entities().into_iter().map(|e| e.id).collect();
+    match load_entity_properties(pool, &entity_ids).await {
+        Ok(rows) => game.restore_properties(rows),
+        Err(e) => tracing::error!("Failed to load entity properties: {e}"),
+    }
+    // Before inventories, so delivered containers get their contents back
+    match load_entities(pool, zone).await {
+        Ok(entities) => game.restore_entities(entities),
+        Err(e) => tracing::error!("Failed to load entities: {e}"),
+    }
+    if home {
+        restore_home(pool, game).await;
+    }
+    // Only once the zone's characters are in it: a zone restores, and later
+    // saves, just the inventories of the characters and containers it hosts
+    match load_inventories(pool).await {
+        Ok(rows) => game.restore_inventories(rows),
+        Err(e) => tracing::error!("Failed to load inventories: {e}"),
+    }
+}
+
+/// Restore what lives in the home zone only: balances, pending deliveries,
+/// account characters and relationships.
+async fn restore_home(pool: &PgPool, game: &mut GameState) {
+    // Account characters are restored before any character opens a new account
+    match load_economy(pool).await {
+        Ok((balances, orders)) => {
+            game.ledger.restore(balances);
+            game.orders.restore(orders);
+        }
+        Err(e) => tracing::error!("Failed to load accounts and orders: {e}"),
+    }
+    match load_accounts(pool).await {
+        Ok(accounts) => game.restore_accounts(accounts),
+        Err(e) => tracing::error!("Failed to load accounts: {e}"),
+    }
+    match load_relationships(pool).await {
+        Ok(rows) => game.relationships.restore(rows),
+        Err(e) => tracing::error!("Failed to load relationships: {e}"),
     }
+}
+
+/// Start the background tasks that persist a zone's entities, inventories,
+/// economy, accounts, chat history and relationships.
+fn spawn_persistence(pool: &PgPool, zone: &Zone) {
+    // Background task: Persist all objects and the inventories of characters and
+    // containers every real-world minute, from one snapshot in one transaction
+    let persist_pool_objects = pool.clone();
+    let game_state_for_objects = zone.game.clone();
+    let zone_name = zone.name.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            // Write lock to take the removals, then drop lock before database write
+            let mut game = game_state_for_objects.write().await;
+            let entities = game.stored_entities();
+            let removed = game.take_removed_entities();
+            let inventories = game.stored_inventories();
+            drop(game);
+
+            if let Err(e) = save_objects(
+                &persist_pool_objects,
+                &zone_name,
+                &entities,
+                &removed,
+                &inventories,
+            )
+            .await
+            {
+                tracing::error!("Failed to persist objects and inventories: {e}");
+                game_state_for_objects
+                    .write()
+                    .await
+                    .return_removed_entities(removed);
+            } else {
+                tracing::debug!(
+                    "Persisted {} objects and {} inventories",
+                    entities.len(),
+                    inventories.len()
+                );
+            }
+        }
+    });
+
+    // Background task: Persist balances, orders and the transaction log every
+    // real-world minute; changes that fail to save are kept for the next try
+    let persist_pool_economy = pool.clone();
+    let game_state_for_economy = zone.game.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            let mut game = game_state_for_economy.write().await;
+            let (orders, deliveries) = game.orders.take_unsaved();
+            let changes = EconomyChanges {
+                balances: game.ledger.balances(),
+                orders,
+                deliveries,
+                transactions: game.ledger.take_unsaved(),
+            };
+            drop(game);
+
+            if let Err(e) = save_economy(&persist_pool_economy, &changes).await {
+                tracing::error!("Failed to persist economy: {e}");
+                let mut game = game_state_for_economy.write().await;
+                game.orders
+                    .return_unsaved(changes.orders, changes.deliveries);
+                game.ledger.return_unsaved(changes.transactions);
+            } else {
+                tracing::debug!("Persisted {} transactions", changes.transactions.len());
+            }
+        }
+    });
+
+    // Background task: Persist new accounts and characters every real-world minute;
+    // failed saves are retried
+    let persist_pool_accounts = pool.clone();
+    let game_state_for_accounts = zone.game.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            let accounts = game_state_for_accounts
+                .write()
+                .await
+                .accounts
+                .take_unsaved();
+            if accounts.is_empty() {
+                continue;
+            }
+
+            if let Err(e) = save_accounts(&persist_pool_accounts, &accounts).await {
+                tracing::error!("Failed to persist accounts: {e}");
+                game_state_for_accounts
+                    .write()
+                    .await
+                    .accounts
+                    .return_unsaved(accounts);
+            } else {
+                tracing::debug!("Persisted {} accounts", accounts.len());
+            }
+        }
+    });
+
+    // Background task: Persist chat history (only recorded when PERSIST_CHAT is
+    // enabled) every real-world minute; failed saves are retried
+    let persist_pool_chat = pool.clone();
+    let game_state_for_chat = zone.game.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            let messages = game_state_for_chat.write().await.chat.take_unsaved();
+            if messages.is_empty() {
+                continue;
+            }
+
+            if let Err(e) = save_chat_messages(&persist_pool_chat, &messages).await {
+                tracing::error!("Failed to persist chat messages: {e}");
+                game_state_for_chat
+                    .write()
+                    .await
+                    .chat
+                    .return_unsaved(messages);
+            } else {
+                tracing::debug!("Persisted {} chat messages", messages.len());
+            }
+        }
+    });
+
+    // Background task: Persist relationships changed since the last save every
+    // real-world minute; failed saves are retried
+    let persist_pool_relationships = pool.clone();
+    let game_state_for_relationships = zone.game.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            let relationships = game_state_for_relationships
+                .write()
+                .await
+                .take_unsaved_relationships();
+            if relationships.is_empty() {
+                continue;
+            }
+
+            if let Err(e) = save_relationships(&persist_pool_relationships, &relationships).await {
+                tracing::error!("Failed to persist relationships: {e}");
+                game_state_for_relationships
+                    .write()
+                    .await
+                    .relationships
+                    .return_unsaved(relationships);
+            } else {
+                tracing::debug!("Persisted {} relationships", relationships.len());
+            }
+        }
+    });
+}
 
+/// Start the background tasks that simulate a zone and broadcast it to the
+/// clients whose character is there.
+fn spawn_simulation(zone: &Zone, forward_physics_events: bool) {
     // Background task: Physics simulation update loop running at 60 FPS
     // Updates physics world and syncs entity positions from physics simulation
-    let game_state_for_physics = app_state.game.clone();
+    let game_state_for_physics = zone.game.clone();
+    let broadcast_tx_for_physics = zone.broadcast_tx.clone();
     tokio::spawn(async move {
         // 16,666,667 nanoseconds = ~16.67ms = ~60 FPS
         let mut interval = tokio::time::interval(tokio::time::Duration::from_nanos(16_666_667));
+        // Collision-start events waiting to be forwarded, flushed every 6 steps (10 FPS)
+        let mut outbox = Vec::new();
+        let mut step_count: u64 = 0;
         loop {
             interval.tick().await;
             let mut game = game_state_for_physics.write().await;
             // Step physics with delta time of 1.0 game second
             // Each real-time step (1/60 second) represents 1 game second (60x time scale)
-            game.step_physics(1.0);
+            let events = game.step_physics(1.0);
+            drop(game);
+
+            if !forward_physics_events {
+                continue;
+            }
+            outbox.extend(
+                events
+                    .into_iter()
+                    .filter(|e| matches!(e, PhysicsEvent::CollisionStarted { .. })),
+            );
+            step_count += 1;
+            if step_count.is_multiple_of(6) && !outbox.is_empty() {
+                let msg = GameMessage::PhysicsEvents {
+                    events: std::mem::take(&mut outbox),
+                };
+                if let Ok(json) = serde_json::to_string(&msg) {
+                    let _ = broadcast_tx_for_physics.send(json);
+                }
+            }
+        }
+    });
+
+    // Background task: Needs, mood, activity, schedule and weather simulation, once per
+    // game minute (1 real second). Broadcasts activity start/progress/finish events,
+    // deliveries and, every few game minutes, the environment
+    let game_state_for_needs = zone.game.clone();
+    let broadcast_tx_for_needs = zone.broadcast_tx.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            let mut game = game_state_for_needs.write().await;
+            game.tick_characters(game::GameState::get_game_time_minutes());
+            let events = game.take_activity_events();
+            let deliveries = game.take_deliveries();
+            let environment = game.take_environment();
+            drop(game);
+
+            let delivery_messages =
+                deliveries
+                    .into_iter()
+                    .map(|delivery| GameMessage::DeliveryArrived {
+                        player_id: delivery.order.player_id,
+                        order_id: delivery.order.id,
+                        entity_ids: delivery.entity_ids,
+                    });
+            let environment_message =
+                environment.map(|environment| GameMessage::Environment { environment });
+            for msg in events
+                .into_iter()
+                .map(GameMessage::from)
+                .chain(delivery_messages)
+                .chain(environment_message)
+            {
+                if let Ok(json) = serde_json::to_string(&msg) {
+                    let _ = broadcast_tx_for_needs.send(json);
+                }
+            }
         }
     });
 
     // Background task: Broadcast world state updates to all connected clients
     // Runs at 10 FPS (every 100ms) for network efficiency
     // Sends complete world state (all players + all entities) to all WebSocket clients
-    let game_state_for_broadcast = app_state.game.clone();
-    let broadcast_tx_for_task = broadcast_tx.clone();
+    let game_state_for_broadcast = zone.game.clone();
+    let broadcast_tx_for_task = zone.broadcast_tx.clone();
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(100)); // 10 FPS
         loop {
             interval.tick().await;
             // Read lock to get world state, then drop lock before serialization
-            let game = game_state_for_broadcast.read().await;
+            let mut game = game_state_for_broadcast.write().await;
             let all_players = game.get_all_players();
             let all_entities = game.get_all_entities();
+            let changes = game.take_property_changes();
             drop(game);
 
+            // Property changes go out as deltas ahead of the snapshot that includes them
+            if !changes.is_empty() {
+                if let Ok(json) = serde_json::to_string(&GameMessage::PropertiesChanged { changes })
+                {
+                    let _ = broadcast_tx_for_task.send(json);
+                }
+            }
+
             let world_state = GameMessage::WorldState {
                 players: all_players,
                 entities: all_entities.clone(),
@@ -174,30 +572,6 @@ async fn main() -> anyhow::Result<()> {
             }
         }
     });
-
-    // Set up HTTP routes
-    let app = Router::new()
-        // WebSocket endpoint for game client connections
-        .route("/ws", get(websocket_handler))
-        // Serve static files from client/public (index.html = ship game at root)
-        .fallback_service(ServeDir::new(static_dir()).append_index_html_on_directories(true))
-        // Enable CORS for all origins (development)
-        .layer(CorsLayer::permissive())
-        .with_state(app_state);
-
-    // Get port from environment variable or default to 8080
-    let port = std::env::var("PORT")
-        .unwrap_or_else(|_| "8080".to_string())
-        .parse::<u16>()?;
-
-    // Bind to all network interfaces on the specified port
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{port}")).await?;
-    tracing::info!("Server listening on 0.0.0.0:{port} — open http://localhost:{port}/");
-
-    // Start the HTTP server
-    axum::serve(listener, app).await?;
-
-    Ok(())
 }
 
 /// Returns the path to the static files directory (client/public).
@@ -3,7 +3,21 @@
 //! All messages use tagged JSON serialization with a "type" field
 //! to enable polymorphic message handling.
 
-use crate::game::{Activity, Entity, Player, Position};
+use crate::account::CharacterSummary;
+use crate::activity::{ActivityEvent, ActivityOutcome, TimedActivity};
+use crate::catalogue::ObjectType;
+use crate::chat::{ChatChannel, ChatMessage};
+use crate::economy::{Order, OrderLine, ShopItem};
+use crate::environment::EnvironmentState;
+use crate::food::Recipe;
+use crate::game::{Activity, AwaySummary, Entity, Player, Position};
+use crate::genetics::AppearanceChoice;
+use crate::inventory::Inventory;
+use crate::level::Geometry;
+use crate::physics::PhysicsEvent;
+use crate::properties::PropertyChange;
+use crate::relationship::{RelationshipView, SocialAction};
+use crate::schedule::Schedule;
 use serde::{Deserialize, Serialize};
 
 /// WebSocket message types exchanged between client and server.
@@ -14,9 +28,69 @@ use serde::{Deserialize, Serialize};
 #[serde(tag = "type")]
 pub enum GameMessage {
     /// Client -> Server: Player joining the game
+    ///
+    /// For clients without accounts: joins as a new character, or takes back one
+    /// joined this way before. Refused for characters of an account and NPCs.
     Join {
-        /// Player data for the joining player
-        player: Player,
+        /// Player data for the joining player (boxed: by far the largest message)
+        player: Box<Player>,
+    },
+    /// Client -> Server: Log in to an account (opened on first login)
+    ///
+    /// Answered with `CharacterList`. The player then creates a character or
+    /// selects one to control; `Join` remains for clients without accounts.
+    ///
+    /// Development stub: the account ID is taken on trust, so anyone who knows
+    /// an ID can log in to it. Sign-in is to go through the `auth` module (Twitter
+    /// OAuth sessions) once that is enabled.
+    Login {
+        /// Account ID
+        account_id: String,
+        /// Display username
+        username: String,
+    },
+    /// Server -> Client: Characters of the logged-in account
+    CharacterList {
+        /// Account ID
+        account_id: String,
+        /// Characters with what they are doing and how they look
+        characters: Vec<CharacterSummary>,
+        /// Hair styles to choose from when creating a character
+        hair_styles: Vec<String>,
+    },
+    /// Client -> Server: Create a character for the logged-in account
+    ///
+    /// The genome is random, or inherited from two parents in the world; it
+    /// decides height, build, metabolism, temperament and colouring. The character
+    /// starts living in the world at once. Answered with `CharacterList`, or
+    /// `CommandRefused` (name taken, too many characters, unknown hair style).
+    CreateCharacter {
+        /// Display name of the character
+        name: String,
+        /// Hair style and outfit colour
+        #[serde(default)]
+        appearance: AppearanceChoice,
+        /// IDs of the two parent characters (of the same account), if any
+        #[serde(default)]
+        parents: Option<[String; 2]>,
+    },
+    /// Client -> Server: Take control of a character of the logged-in account
+    ///
+    /// Answered like `Join`. The character controlled before, if any, goes back
+    /// to its schedule.
+    SelectCharacter {
+        /// ID of the character
+        character_id: String,
+    },
+    /// Server -> Client: What the character did while the player was offline
+    ///
+    /// Sent after `Join` when the player takes over a character that kept
+    /// living in the world since their last connection.
+    WelcomeBack {
+        /// ID of the player
+        player_id: String,
+        /// Time away, events, and the character's condition now
+        summary: AwaySummary,
     },
     /// Server -> Client: Player leaving the game
     Leave {
@@ -35,6 +109,31 @@ pub enum GameMessage {
         #[serde(default)]
         is_moving: bool,
     },
+    /// Client -> Server: Continuous walking intent
+    ///
+    /// The server keeps moving the player's character in this direction on every
+    /// physics step (with collisions and gravity) until a zero direction is sent.
+    MoveIntent {
+        /// ID of the moving player
+        player_id: String,
+        /// Walking direction in the X/Z plane (normalized by the server)
+        direction: Direction,
+        /// Walking speed (m/game-second)
+        #[serde(default = "default_walk_speed")]
+        speed: f32,
+        /// Facing rotation (Y-axis, radians)
+        rotation: f32,
+    },
+    /// Server -> Client: Authoritative position after collision handling
+    ///
+    /// Sent to the owning client when the server moved its character somewhere
+    /// other than the requested position (blocked by a wall, fell, climbed a step).
+    PositionCorrection {
+        /// ID of the corrected player
+        player_id: String,
+        /// Corrected position
+        position: Position,
+    },
     /// Client -> Server: Set player activity
     SetActivity {
         /// ID of the player
@@ -42,13 +141,408 @@ pub enum GameMessage {
         /// New activity
         activity: Activity,
     },
-    /// Server -> Client: Player activity changed
-    ActivityChanged {
+    /// Client -> Server: Use an object with one of the verbs its type affords
+    Interact {
         /// ID of the player
         player_id: String,
-        /// New activity
+        /// Object to use
+        entity_id: String,
+        /// Verb from the object type's catalogue entry, e.g. "turn_on"
+        verb: String,
+    },
+    /// Client -> Server: Cook a recipe on an appliance within reach
+    ///
+    /// The ingredients come from the character's inventory and the meal goes
+    /// back into it when the Cooking activity completes.
+    Cook {
+        /// ID of the player
+        player_id: String,
+        /// Appliance to cook on, e.g. the stove
+        entity_id: String,
+        /// Name of the recipe
+        recipe: String,
+    },
+    /// Client -> Server: Chat with, argue with or hug a character close by
+    ///
+    /// Broadcast as `SocialInteraction` and answered with `Relationships`, or
+    /// `CommandRefused` (too far away, asleep, not close enough for a hug).
+    Socialize {
+        /// ID of the player
+        player_id: String,
+        /// ID of the other character
+        target_id: String,
+        /// What to do
+        action: SocialAction,
+    },
+    /// Server -> Client: Two characters interacted socially
+    SocialInteraction {
+        /// ID of the character who started it
+        player_id: String,
+        /// ID of the other character
+        target_id: String,
+        /// What they did
+        action: SocialAction,
+    },
+    /// Client -> Server: Ask how the character gets along with everyone they met
+    ///
+    /// Answered with `Relationships`.
+    GetRelationships {
+        /// ID of the player
+        player_id: String,
+    },
+    /// Server -> Client: The character's relationships
+    Relationships {
+        /// ID of the player
+        player_id: String,
+        /// Familiarity, affection and trust towards each character met
+        relationships: Vec<RelationshipView>,
+    },
+    /// Client -> Server: Send a chat message
+    ///
+    /// Delivered as `ChatReceived` to the players on the channel, or answered
+    /// with `CommandRefused` (too long, too fast, blocked by moderation).
+    Chat {
+        /// ID of the player
+        player_id: String,
+        /// Say, whisper or group channel
+        channel: ChatChannel,
+        /// Text of the message
+        text: String,
+    },
+    /// Server -> Client: A chat message for this player
+    ChatReceived {
+        /// Message after moderation
+        message: ChatMessage,
+    },
+    /// Client -> Server: Join a group chat channel, creating it if needed
+    ///
+    /// Answered with `ChatGroups`.
+    JoinChatGroup {
+        /// ID of the player
+        player_id: String,
+        /// Name of the group channel
+        group: String,
+    },
+    /// Client -> Server: Leave a group chat channel
+    ///
+    /// Answered with `ChatGroups`.
+    LeaveChatGroup {
+        /// ID of the player
+        player_id: String,
+        /// Name of the group channel
+        group: String,
+    },
+    /// Server -> Client: Group chat channels the player is in
+    ChatGroups {
+        /// ID of the player
+        player_id: String,
+        /// Names of the group channels
+        groups: Vec<String>,
+    },
+    /// Client -> Server: Stop (or start again) receiving chat from a player
+    ///
+    /// Answered with `BlockedPlayers`.
+    BlockPlayer {
+        /// ID of the player
+        player_id: String,
+        /// Player to block or unblock
+        target_id: String,
+        /// Whether to block
+        blocked: bool,
+    },
+    /// Server -> Client: Players whose chat this player does not receive
+    BlockedPlayers {
+        /// ID of the player
+        player_id: String,
+        /// IDs of the blocked players
+        blocked: Vec<String>,
+    },
+    /// Server -> Client: Everything that can be cooked
+    ///
+    /// Sent when a player joins, after the object catalogue.
+    Recipes {
+        /// All recipes
+        recipes: Vec<Recipe>,
+    },
+    /// Server -> Client: A character started an activity
+    ActivityStarted {
+        /// ID of the player
+        player_id: String,
+        /// Activity with its start time, expected duration and object used
+        activity: TimedActivity,
+    },
+    /// Server -> Client: Progress of a running activity
+    ///
+    /// Sent every few game minutes while the activity runs.
+    ActivityProgress {
+        /// ID of the player
+        player_id: String,
+        /// Running activity
+        activity: Activity,
+        /// Game minutes since the activity started
+        elapsed_minutes: i64,
+        /// Expected duration in game minutes (0 = open-ended)
+        duration_minutes: i64,
+    },
+    /// Server -> Client: A character's activity ended
+    ActivityFinished {
+        /// ID of the player
+        player_id: String,
+        /// Finished activity
+        activity: Activity,
+        /// Whether it completed or was interrupted
+        outcome: ActivityOutcome,
+        /// Why it was interrupted
+        reason: Option<String>,
+    },
+    /// Server -> Client: A command was refused by the character
+    ///
+    /// Sent to the owning client when the character's emotional state or
+    /// condition makes them unwilling to act.
+    CommandRefused {
+        /// ID of the player
+        player_id: String,
+        /// Refused command ("set_activity" or "move")
+        command: String,
+        /// Why the character refused
+        reason: String,
+    },
+    /// Server -> Client: A requested activity will start later
+    ///
+    /// Sent to the owning client when the character is reluctant; an
+    /// `ActivityStarted` follows when the activity starts.
+    ActivityDelayed {
+        /// ID of the player
+        player_id: String,
+        /// Requested activity
         activity: Activity,
+        /// Delay before the activity starts (game minutes)
+        delay_minutes: i64,
+        /// Why the character is reluctant
+        reason: String,
     },
+    /// Client -> Server: Put a schedule in control of the player's character
+    ///
+    /// An empty schedule removes the current one. Answered with `ScheduleValidation`.
+    UploadSchedule {
+        /// ID of the player
+        player_id: String,
+        /// Time blocks and if-then rules
+        schedule: Schedule,
+    },
+    /// Client -> Server: Check a schedule without putting it in force
+    ///
+    /// Answered with `ScheduleValidation`.
+    ValidateSchedule {
+        /// ID of the player
+        player_id: String,
+        /// Time blocks and if-then rules
+        schedule: Schedule,
+    },
+    /// Server -> Client: Result of `UploadSchedule` or `ValidateSchedule`
+    ScheduleValidation {
+        /// ID of the player
+        player_id: String,
+        /// Whether the schedule was put in force
+        accepted: bool,
+        /// Every problem found (empty when the schedule is valid)
+        errors: Vec<String>,
+    },
+    /// Client -> Server: Ask for the character's schedule
+    ///
+    /// Answered with `ScheduleInfo`.
+    GetSchedule {
+        /// ID of the player
+        player_id: String,
+    },
+    /// Server -> Client: The character's schedule and what it asks for now
+    ScheduleInfo {
+        /// ID of the player
+        player_id: String,
+        /// Schedule in force, if any
+        schedule: Option<Schedule>,
+        /// Activity the schedule currently asks for
+        current_activity: Option<Activity>,
+        /// Rule or time block that chose the current activity
+        reason: Option<String>,
+    },
+    /// Client -> Server: Pick an item within reach up into the character's inventory
+    ///
+    /// Answered with `InventoryContents`, or `CommandRefused` when not allowed.
+    PickUp {
+        /// ID of the player
+        player_id: String,
+        /// Item entity to pick up
+        entity_id: String,
+    },
+    /// Client -> Server: Drop items from an inventory slot at the character's feet
+    ///
+    /// Answered with `InventoryContents`, or `CommandRefused` when not allowed.
+    DropItem {
+        /// ID of the player
+        player_id: String,
+        /// Inventory slot
+        slot: usize,
+        /// Number of items to drop
+        count: u32,
+    },
+    /// Client -> Server: Put items from an inventory slot down at a point within reach
+    ///
+    /// Answered with `InventoryContents`, or `CommandRefused` when not allowed.
+    PlaceItem {
+        /// ID of the player
+        player_id: String,
+        /// Inventory slot
+        slot: usize,
+        /// Number of items to place
+        count: u32,
+        /// Where to put them (meters)
+        position: Position,
+    },
+    /// Client -> Server: Move items from the character's inventory into a container
+    ///
+    /// Answered with `InventoryContents` for both, or `CommandRefused`.
+    StoreItem {
+        /// ID of the player
+        player_id: String,
+        /// Inventory slot of the character
+        slot: usize,
+        /// Number of items to move
+        count: u32,
+        /// Container entity within reach
+        container_id: String,
+    },
+    /// Client -> Server: Move items from a container into the character's inventory
+    ///
+    /// Answered with `InventoryContents` for both, or `CommandRefused`.
+    TakeItem {
+        /// ID of the player
+        player_id: String,
+        /// Container entity within reach
+        container_id: String,
+        /// Inventory slot of the container
+        slot: usize,
+        /// Number of items to move
+        count: u32,
+    },
+    /// Client -> Server: Ask for the contents of the character's inventory or a container
+    ///
+    /// Answered with `InventoryContents`.
+    GetInventory {
+        /// ID of the player
+        player_id: String,
+        /// Player or container entity whose inventory to show
+        owner_id: String,
+    },
+    /// Server -> Client: Contents of an inventory
+    InventoryContents {
+        /// Player or container entity holding the items
+        owner_id: String,
+        /// Slots, weight limit and items
+        inventory: Inventory,
+    },
+    /// Client -> Server: Ask what the shop sells
+    ///
+    /// Answered with `ShopCatalogue`.
+    GetShop {
+        /// ID of the player
+        player_id: String,
+    },
+    /// Server -> Client: Object types on sale and the character's balance
+    ShopCatalogue {
+        /// Object types with their prices (cents)
+        items: Vec<ShopItem>,
+        /// Balance of the character (cents)
+        balance: i64,
+    },
+    /// Client -> Server: Buy objects for delivery to the house
+    ///
+    /// Answered with `OrderPlaced`, or `CommandRefused` (not for sale, not enough money).
+    Purchase {
+        /// ID of the player
+        player_id: String,
+        /// Object types and counts to buy
+        lines: Vec<OrderLine>,
+    },
+    /// Server -> Client: A purchase was paid and will be delivered
+    OrderPlaced {
+        /// Order with its delivery time
+        order: Order,
+        /// Balance of the character after paying (cents)
+        balance: i64,
+    },
+    /// Client -> Server: Ask for the character's balance and pending orders
+    ///
+    /// Answered with `Account`.
+    GetAccount {
+        /// ID of the player
+        player_id: String,
+    },
+    /// Server -> Client: The character's balance and orders on the way
+    Account {
+        /// ID of the player
+        player_id: String,
+        /// Balance (cents)
+        balance: i64,
+        /// Orders not yet delivered
+        pending_orders: Vec<Order>,
+    },
+    /// Server -> Client: The goods of an order arrived at the delivery point
+    ///
+    /// Broadcast to all clients; the entities appear in the next `WorldState`.
+    DeliveryArrived {
+        /// ID of the buyer
+        player_id: String,
+        /// ID of the order
+        order_id: String,
+        /// Entities spawned for the order
+        entity_ids: Vec<String>,
+    },
+    /// Server -> Client: Entity properties changed (e.g. a stove was turned on)
+    ///
+    /// Broadcast to all clients as deltas; the next `WorldState` includes them.
+    PropertiesChanged {
+        /// Changes in the order they happened
+        changes: Vec<PropertyChange>,
+    },
+    /// Client -> Server: Move the controlled character to another zone
+    ///
+    /// The character arrives at the zone's spawn point with its needs, inventory,
+    /// money and schedule. Answered with `ZoneEntered` and the new zone's world, or
+    /// `CommandRefused`.
+    TransferZone { player_id: String, zone: String },
+    /// Server -> Client: The controlled character is in a zone
+    ///
+    /// Sent when a character is joined, selected or transferred, ahead of the
+    /// zone's world. World state and events come from this zone from now on.
+    ZoneEntered {
+        player_id: String,
+        zone: String,
+        /// Every zone hosted by the server
+        zones: Vec<String>,
+    },
+    /// Server -> Client: The character now lives on another server
+    ///
+    /// Sent after a transfer to, or the selection of a character in, a zone hosted
+    /// by another server. The client connects to `server` (its `/ws` endpoint) and
+    /// sends `ResumeHandoff` with the ticket. A gateway does this for its clients.
+    ZoneHandoff {
+        player_id: String,
+        zone: String,
+        /// Base URL of the server hosting the zone
+        server: String,
+        /// One-time ticket, valid for a minute
+        ticket: String,
+    },
+    /// Client -> Server: Take control of a character handed off to this server
+    ///
+    /// Answered like `SelectCharacter`, or with `CommandRefused` if the ticket is
+    /// unknown or expired.
+    ResumeHandoff { ticket: String },
+    /// Server -> Client: Time of day, season and weather
+    ///
+    /// Sent when a player joins and broadcast every few game minutes.
+    Environment { environment: EnvironmentState },
     /// Server -> Client: Complete world state snapshot
     ///
     /// Sent periodically (10 FPS) to all clients to keep them synchronized.
@@ -58,6 +552,31 @@ pub enum GameMessage {
         /// All entities in the game
         entities: Vec<Entity>,
     },
+    /// Server -> Client: Object catalogue
+    ///
+    /// Sent when a player joins so the client knows how to render every entity
+    /// type (mesh, shape) and which interaction verbs it offers.
+    ObjectCatalogue {
+        /// All object types
+        types: Vec<ObjectType>,
+    },
+    /// Server -> Client: Terrain geometry of the current level
+    ///
+    /// Sent when a player joins so the client renders the same hills and slopes
+    /// that the server collides against. Heightmap pieces include their sampled
+    /// heights; mesh pieces include their vertices and triangles.
+    Terrain {
+        /// Heightmap and mesh terrain pieces
+        pieces: Vec<Geometry>,
+    },
+    /// Server -> Client: Physics interaction events (e.g. for impact sounds)
+    ///
+    /// Only sent when `FORWARD_PHYSICS_EVENTS` is enabled. Batched at the
+    /// world state broadcast rate (10 FPS).
+    PhysicsEvents {
+        /// Events since the previous batch
+        events: Vec<PhysicsEvent>,
+    },
     /// Server -> Client: Game time synchronization
     ///
     /// Sent when client connects to sync game time.
@@ -67,3 +586,53 @@ pub enum GameMessage {
         game_time_minutes: i64,
     },
 }
+
+impl From<ActivityEvent> for GameMessage {
+    fn from(event: ActivityEvent) -> Self {
+        match event {
+            ActivityEvent::Started {
+                player_id,
+                activity,
+            } => GameMessage::ActivityStarted {
+                player_id,
+                activity,
+            },
+            ActivityEvent::Progress {
+                player_id,
+                activity,
+                elapsed_minutes,
+                duration_minutes,
+            } => GameMessage::ActivityProgress {
+                player_id,
+                activity,
+                elapsed_minutes,
+                duration_minutes,
+            },
+            ActivityEvent::Finished {
+                player_id,
+                activity,
+                outcome,
+                reason,
+            } => GameMessage::ActivityFinished {
+                player_id,
+                activity,
+                outcome,
+                reason,
+            },
+        }
+    }
+}
+
+/// Horizontal direction in the X/Z plane.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Direction {
+    /// X component (east-west)
+    pub x: f32,
+    /// Z component (north-south)
+    pub z: f32,
+}
+
+/// Default walking speed: 1.4 m/game-second (typical human walking pace).
+fn default_walk_speed() -> f32 {
+    1.4
+}
@@ -0,0 +1,244 @@
+//! Emotional state module.
+//!
+//! Every character carries a mood made of emotion levels (sadness, anger, anxiety,
+//! loneliness), each from 0 (absent) to 100 (overwhelming). Emotions drift every
+//! game minute toward levels implied by the character's needs and activity, and
+//! jump on events such as social contact or being hit by a ball.
+//!
+//! The mood gates player control: a character who is unwilling can refuse or
+//! delay `SetActivity` and movement commands, and the reason is sent back to the
+//! client.
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::Activity;
+use crate::needs::Needs;
+
+/// Fraction of the gap to the target level closed every game minute.
+const DRIFT_PER_MINUTE: f32 = 0.02;
+/// How strongly temperament pulls anger and anxiety, relative to their drift.
+const TEMPERAMENT_WEIGHT: f32 = 0.2;
+/// Emotion level above which a character is reluctant (commands are delayed).
+pub const RELUCTANT: f32 = 60.0;
+/// Emotion level above which a character refuses commands outright.
+pub const UNWILLING: f32 = 80.0;
+/// Sleepiness or exhaustion level above which a character can do nothing but rest.
+const COLLAPSE: f32 = 95.0;
+/// How long a reluctant character puts off a requested activity (game minutes).
+const RELUCTANCE_DELAY_MINUTES: i64 = 15;
+
+/// Emotion levels of a character, 0 = absent, 100 = overwhelming.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Mood {
+    pub sadness: f32,
+    pub anger: f32,
+    pub anxiety: f32,
+    pub loneliness: f32,
+}
+
+/// Something that happened to a character and moves their emotions.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MoodEvent {
+    /// Friendly contact with another character
+    Socialized,
+    /// Quarrel with another character
+    Argued,
+    /// Hug from someone the character is fond of
+    Hugged,
+    /// Hit by a moving object
+    Hit,
+}
+
+/// Outcome of asking a character to do something.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Decision {
+    /// The command is carried out immediately
+    Allow,
+    /// The command is carried out after a number of game minutes
+    Delay { minutes: i64, reason: String },
+    /// The command is refused
+    Refuse { reason: String },
+}
+
+impl Mood {
+    /// Advance emotions by a number of game minutes.
+    ///
+    /// Sadness follows unmet needs and loneliness, anger follows hunger and
+    /// fatigue, anxiety follows critical needs. Loneliness builds slowly while
+    /// alone and falls while socializing.
+    pub fn tick(&mut self, needs: &Needs, activity: &Activity, minutes: f32) {
+        let meters = needs.meters();
+        let average_need = meters.iter().map(|(_, v)| v).sum::<f32>() / meters.len() as f32;
+        let critical = needs.critical().len() as f32;
+
+        self.loneliness = match activity {
+            Activity::Socializing => self.loneliness - 1.0 * minutes,
+            _ => self.loneliness + 0.05 * minutes,
+        }
+        .clamp(0.0, 100.0);
+
+        let sadness_target = 0.6 * average_need + 0.4 * self.loneliness;
+        let anger_target = 0.7 * needs.hunger.max(needs.exhaustion);
+        let anxiety_target = (0.3 * average_need + 25.0 * critical).min(100.0);
+
+        // Exponential approach, exact for any number of minutes
+        let blend = 1.0 - (1.0 - DRIFT_PER_MINUTE).powf(minutes);
+        self.sadness += (sadness_target - self.sadness) * blend;
+        self.anger += (anger_target - self.anger) * blend;
+        self.anxiety += (anxiety_target - self.anxiety) * blend;
+    }
+
+    /// Let temperament (-1 calm to 1 volatile) colour emotions over a number of
+    /// game minutes: volatile characters drift towards anger and anxiety, calm
+    /// ones away from them.
+    pub fn temper(&mut self, temperament: f32, minutes: f32) {
+        let target = if temperament > 0.0 { 100.0 } else { 0.0 };
+        let blend = (1.0 - (1.0 - DRIFT_PER_MINUTE).powf(minutes)) * TEMPERAMENT_WEIGHT;
+        let pull = blend * temperament.abs().min(1.0);
+        self.anger += (target - self.anger) * pull;
+        self.anxiety += (target - self.anxiety) * pull;
+    }
+
+    /// Look up an emotion level by name (as used in schedule rules).
+    pub fn get(&self, name: &str) -> Option<f32> {
+        Some(match name {
+            "sadness" => self.sadness,
+            "anger" => self.anger,
+            "anxiety" => self.anxiety,
+            "loneliness" => self.loneliness,
+            _ => return None,
+        })
+    }
+
+    /// React to an event.
+    pub fn apply_event(&mut self, event: &MoodEvent) {
+        match event {
+            MoodEvent::Socialized => {
+                self.loneliness -= 10.0;
+                self.sadness -= 5.0;
+            }
+            MoodEvent::Argued => {
+                self.anger += 20.0;
+                self.sadness += 5.0;
+                self.loneliness += 5.0;
+            }
+            MoodEvent::Hugged => {
+                self.loneliness -= 20.0;
+                self.sadness -= 10.0;
+                self.anxiety -= 5.0;
+            }
+            MoodEvent::Hit => {
+                self.anger += 10.0;
+                self.anxiety += 5.0;
+            }
+        }
+        for level in [
+            &mut self.sadness,
+            &mut self.anger,
+            &mut self.anxiety,
+            &mut self.loneliness,
+        ] {
+            *level = level.clamp(0.0, 100.0);
+        }
+    }
+
+    /// Decide whether the character is willing to start an activity.
+    pub fn decide_activity(&self, needs: &Needs, activity: &Activity) -> Decision {
+        // Resting is always allowed
+        if matches!(activity, Activity::Idle | Activity::Sleeping) {
+            return Decision::Allow;
+        }
+        if needs.sleepiness >= COLLAPSE || needs.exhaustion >= COLLAPSE {
+            return Decision::Refuse {
+                reason: "too exhausted to do anything but rest".to_string(),
+            };
+        }
+
+        // (emotion level, description) that stands in the way of the activity
+        let obstacle = match activity {
+            Activity::Exercising | Activity::Working | Activity::Cleaning => {
+                Some((self.sadness, "too sad"))
+            }
+            Activity::Socializing => Some(if self.anger >= self.anxiety {
+                (self.anger, "too angry")
+            } else {
+                (self.anxiety, "too anxious")
+            }),
+            Activity::Shopping | Activity::Commuting => Some((self.anxiety, "too anxious")),
+            _ => None,
+        };
+        match obstacle {
+            Some((level, why)) if level >= UNWILLING => Decision::Refuse {
+                reason: format!("{why} to start {activity:?}"),
+            },
+            Some((level, why)) if level >= RELUCTANT => Decision::Delay {
+                minutes: RELUCTANCE_DELAY_MINUTES,
+                reason: format!("{why}; needs a moment before {activity:?}"),
+            },
+            _ => Decision::Allow,
+        }
+    }
+
+    /// Decide whether the character is willing to walk.
+    pub fn decide_movement(&self, needs: &Needs) -> Decision {
+        if needs.sleepiness >= COLLAPSE || needs.exhaustion >= COLLAPSE {
+            return Decision::Refuse {
+                reason: "too exhausted to move".to_string(),
+            };
+        }
+        if self.sadness >= UNWILLING && self.anxiety >= UNWILLING {
+            return Decision::Refuse {
+                reason: "too overwhelmed to move".to_string(),
+            };
+        }
+        Decision::Allow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unmet_needs_make_a_character_sad() {
+        let needs = Needs {
+            hunger: 90.0,
+            thirst: 90.0,
+            sleepiness: 90.0,
+            exhaustion: 90.0,
+            dirtiness: 90.0,
+            boredom: 90.0,
+        };
+        let mut mood = Mood::default();
+        mood.tick(&needs, &Activity::Idle, 24.0 * 60.0);
+        assert!(mood.sadness > UNWILLING, "sadness {}", mood.sadness);
+        assert!(mood.anxiety > UNWILLING, "anxiety {}", mood.anxiety);
+    }
+
+    #[test]
+    fn mood_gates_activities() {
+        let needs = Needs::default();
+        let mood = Mood {
+            sadness: 85.0,
+            anger: 65.0,
+            ..Mood::default()
+        };
+        assert!(matches!(
+            mood.decide_activity(&needs, &Activity::Exercising),
+            Decision::Refuse { .. }
+        ));
+        assert!(matches!(
+            mood.decide_activity(&needs, &Activity::Socializing),
+            Decision::Delay { .. }
+        ));
+        assert_eq!(
+            mood.decide_activity(&needs, &Activity::Reading),
+            Decision::Allow
+        );
+        assert_eq!(
+            mood.decide_activity(&needs, &Activity::Sleeping),
+            Decision::Allow
+        );
+    }
+}
@@ -0,0 +1,415 @@
+//! Navigation module.
+//!
+//! Bakes a walkable grid from the static world geometry in the physics world:
+//! every column of the level bounds is scanned top to bottom for upward-facing
+//! surfaces (ground, floors, ship decks, stair treads, terrain), and each surface
+//! with room for a standing human becomes a node. Neighbouring nodes are
+//! connected when the height difference is a climbable step, so stairs and ramps
+//! link floors together; level links (elevators) connect the rest.
+//!
+//! Paths are found with A* and returned as waypoints for characters to follow.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::game::Position;
+use crate::level::Level;
+use crate::physics::PhysicsWorld;
+
+/// Grid cell size (m).
+pub const CELL_SIZE: f32 = 0.5;
+/// Highest step between neighbouring cells a character can climb (m).
+const MAX_STEP: f32 = 0.4;
+/// Cosine of the steepest walkable slope (45°).
+const MAX_SLOPE_COS: f32 = std::f32::consts::FRAC_1_SQRT_2;
+/// Standing human, matching the catalogue's human capsule (m).
+const AGENT_HALF_HEIGHT: f32 = 0.5;
+const AGENT_RADIUS: f32 = 0.3;
+/// Horizontal distance (m) within which a position snaps to a node.
+const SNAP_DISTANCE: f32 = 2.0;
+
+/// Point of a path to walk to.
+#[derive(Clone, Debug)]
+pub struct Waypoint {
+    pub position: Position,
+    /// Reached through a level link (e.g. an elevator) rather than by walking:
+    /// the character is moved there directly once at the previous waypoint
+    pub via_link: bool,
+}
+
+/// Walkable surface in a grid column.
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    column: usize,
+    y: f32,
+}
+
+/// Walkable grid baked from static geometry.
+#[derive(Clone, Debug, Default)]
+pub struct NavGrid {
+    /// X/Z of the corner of cell (0, 0)
+    origin: [f32; 2],
+    cols: usize,
+    rows: usize,
+    nodes: Vec<Node>,
+    /// Index of the first node of each column in `nodes` (one extra entry at the end)
+    column_start: Vec<usize>,
+    /// Extra edges from level links: node -> (node, cost)
+    links: HashMap<usize, Vec<(usize, f32)>>,
+}
+
+/// Open-set entry for A*, ordered by lowest estimated total cost.
+#[derive(Clone, Copy, PartialEq)]
+struct Open {
+    estimate: f32,
+    node: usize,
+}
+
+impl Eq for Open {}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl NavGrid {
+    /// Bake the grid for a level from its physics world.
+    pub fn bake(physics: &PhysicsWorld, level: &Level) -> Self {
+        let [min_x, min_z] = level.bounds.min;
+        let [max_x, max_z] = level.bounds.max;
+        let cols = ((max_x - min_x) / CELL_SIZE).ceil().max(0.0) as usize;
+        let rows = ((max_z - min_z) / CELL_SIZE).ceil().max(0.0) as usize;
+        let mut grid = NavGrid {
+            origin: [min_x, min_z],
+            cols,
+            rows,
+            ..Default::default()
+        };
+        let Some((bottom, top)) = physics.static_height_range() else {
+            grid.column_start = vec![0; cols * rows + 1];
+            return grid;
+        };
+
+        for column in 0..cols * rows {
+            grid.column_start.push(grid.nodes.len());
+            let (x, z) = grid.column_center(column);
+            for y in physics.static_surfaces(x, z, top + 1.0, bottom - 1.0, MAX_SLOPE_COS) {
+                // Hover the capsule one step above the surface so stair treads
+                // and bumps ahead do not count as obstacles
+                let center = y + MAX_STEP + AGENT_RADIUS + AGENT_HALF_HEIGHT;
+                if physics.static_capsule_is_free((x, center, z), AGENT_HALF_HEIGHT, AGENT_RADIUS) {
+                    grid.nodes.push(Node { column, y });
+                }
+            }
+        }
+        grid.column_start.push(grid.nodes.len());
+
+        for link in &level.links {
+            let [fx, fy, fz] = link.from;
+            let [tx, ty, tz] = link.to;
+            let from = grid.snap(&Position {
+                x: fx,
+                y: fy,
+                z: fz,
+            });
+            let to = grid.snap(&Position {
+                x: tx,
+                y: ty,
+                z: tz,
+            });
+            match (from, to) {
+                (Some(from), Some(to)) => {
+                    grid.links.entry(from).or_default().push((to, link.seconds));
+                    grid.links.entry(to).or_default().push((from, link.seconds));
+                }
+                _ => tracing::warn!("Navigation link '{}' has no walkable end", link.name),
+            }
+        }
+
+        tracing::info!(
+            "Baked navigation grid for level '{}': {}x{} cells, {} walkable nodes",
+            level.name,
+            cols,
+            rows,
+            grid.nodes.len()
+        );
+        grid
+    }
+
+    /// Number of walkable nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the grid has no walkable nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn column_center(&self, column: usize) -> (f32, f32) {
+        let (col, row) = (column % self.cols, column / self.cols);
+        (
+            self.origin[0] + (col as f32 + 0.5) * CELL_SIZE,
+            self.origin[1] + (row as f32 + 0.5) * CELL_SIZE,
+        )
+    }
+
+    fn column_at(&self, x: f32, z: f32) -> Option<(usize, usize)> {
+        let col = ((x - self.origin[0]) / CELL_SIZE).floor();
+        let row = ((z - self.origin[1]) / CELL_SIZE).floor();
+        if col < 0.0 || row < 0.0 || col as usize >= self.cols || row as usize >= self.rows {
+            return None;
+        }
+        Some((col as usize, row as usize))
+    }
+
+    fn column_nodes(&self, column: usize) -> std::ops::Range<usize> {
+        self.column_start[column]..self.column_start[column + 1]
+    }
+
+    fn position(&self, node: usize) -> Position {
+        let Node { column, y } = self.nodes[node];
+        let (x, z) = self.column_center(column);
+        Position { x, y, z }
+    }
+
+    /// Nearest node to a position: within [`SNAP_DISTANCE`] horizontally, on a
+    /// surface not far above the position, preferring the same floor.
+    fn snap(&self, position: &Position) -> Option<usize> {
+        let (col, row) = self.column_at(position.x, position.z)?;
+        let reach = (SNAP_DISTANCE / CELL_SIZE).ceil() as isize;
+        let mut best: Option<(usize, f32)> = None;
+        for dr in -reach..=reach {
+            for dc in -reach..=reach {
+                let (c, r) = (col as isize + dc, row as isize + dr);
+                if c < 0 || r < 0 || c as usize >= self.cols || r as usize >= self.rows {
+                    continue;
+                }
+                for node in self.column_nodes(r as usize * self.cols + c as usize) {
+                    let p = self.position(node);
+                    let horizontal =
+                        ((p.x - position.x).powi(2) + (p.z - position.z).powi(2)).sqrt();
+                    let dy = p.y - position.y;
+                    if horizontal > SNAP_DISTANCE || !(-2.0..=1.0).contains(&dy) {
+                        continue;
+                    }
+                    // Height differences weigh more: stay on the same floor
+                    let cost = horizontal + 4.0 * dy.abs();
+                    if best.is_none_or(|(_, c)| cost < c) {
+                        best = Some((node, cost));
+                    }
+                }
+            }
+        }
+        best.map(|(node, _)| node)
+    }
+
+    /// Node in a neighbouring column reachable from `node` by stepping.
+    fn step_to(&self, node: usize, column: usize) -> Option<usize> {
+        let y = self.nodes[node].y;
+        self.column_nodes(column)
+            .filter(|&n| (self.nodes[n].y - y).abs() <= MAX_STEP)
+            .min_by(|&a, &b| {
+                (self.nodes[a].y - y)
+                    .abs()
+                    .total_cmp(&(self.nodes[b].y - y).abs())
+            })
+    }
+
+    /// Walkable neighbours of a node with the cost to reach them.
+    fn neighbours(&self, node: usize) -> Vec<(usize, f32)> {
+        let column = self.nodes[node].column;
+        let (col, row) = ((column % self.cols) as isize, (column / self.cols) as isize);
+        let column_of = |dc: isize, dr: isize| -> Option<usize> {
+            let (c, r) = (col + dc, row + dr);
+            (c >= 0 && r >= 0 && (c as usize) < self.cols && (r as usize) < self.rows)
+                .then(|| r as usize * self.cols + c as usize)
+        };
+        let mut result = Vec::with_capacity(8);
+        for (dc, dr) in [
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ] {
+            let Some(next) = column_of(dc, dr).and_then(|c| self.step_to(node, c)) else {
+                continue;
+            };
+            // No cutting corners past walls
+            if dc != 0 && dr != 0 {
+                let side_a = column_of(dc, 0).and_then(|c| self.step_to(node, c));
+                let side_b = column_of(0, dr).and_then(|c| self.step_to(node, c));
+                if side_a.is_none() || side_b.is_none() {
+                    continue;
+                }
+            }
+            let horizontal = if dc != 0 && dr != 0 {
+                CELL_SIZE * std::f32::consts::SQRT_2
+            } else {
+                CELL_SIZE
+            };
+            let climb = (self.nodes[next].y - self.nodes[node].y).abs();
+            result.push((next, horizontal + climb));
+        }
+        if let Some(links) = self.links.get(&node) {
+            result.extend(links.iter().copied());
+        }
+        result
+    }
+
+    /// Find a walking path between two positions.
+    ///
+    /// Returns the waypoints after the start, ending at the walkable point
+    /// nearest the goal, or None when the goal cannot be reached.
+    pub fn find_path(&self, from: &Position, to: &Position) -> Option<Vec<Waypoint>> {
+        let start = self.snap(from)?;
+        let goal = self.snap(to)?;
+        let goal_position = self.position(goal);
+        let heuristic = |node: usize| {
+            let p = self.position(node);
+            ((p.x - goal_position.x).powi(2)
+                + (p.y - goal_position.y).powi(2)
+                + (p.z - goal_position.z).powi(2))
+            .sqrt()
+        };
+
+        let mut cost: HashMap<usize, f32> = HashMap::from([(start, 0.0)]);
+        let mut came_from: HashMap<usize, usize> = HashMap::new();
+        let mut open = BinaryHeap::from([Open {
+            estimate: heuristic(start),
+            node: start,
+        }]);
+        while let Some(Open { node, estimate }) = open.pop() {
+            if node == goal {
+                break;
+            }
+            let node_cost = cost[&node];
+            if estimate > node_cost + heuristic(node) + 1e-3 {
+                continue; // Stale entry
+            }
+            for (next, step_cost) in self.neighbours(node) {
+                let next_cost = node_cost + step_cost;
+                if cost.get(&next).is_none_or(|&c| next_cost < c) {
+                    cost.insert(next, next_cost);
+                    came_from.insert(next, node);
+                    open.push(Open {
+                        estimate: next_cost + heuristic(next),
+                        node: next,
+                    });
+                }
+            }
+        }
+        if start != goal && !came_from.contains_key(&goal) {
+            return None;
+        }
+
+        let mut nodes = vec![goal];
+        while let Some(&previous) = came_from.get(nodes.last()?) {
+            nodes.push(previous);
+        }
+        nodes.reverse();
+        Some(self.waypoints(&nodes))
+    }
+
+    /// Turn a node path into waypoints, keeping only turns, height changes and links.
+    fn waypoints(&self, nodes: &[usize]) -> Vec<Waypoint> {
+        // A link edge between nodes that are not a walking step apart
+        let is_link = |a: usize, b: usize| {
+            let (na, nb) = (self.nodes[a], self.nodes[b]);
+            let walkable = (na.column % self.cols).abs_diff(nb.column % self.cols) <= 1
+                && (na.column / self.cols).abs_diff(nb.column / self.cols) <= 1
+                && (na.y - nb.y).abs() <= MAX_STEP;
+            !walkable
+                && self
+                    .links
+                    .get(&a)
+                    .is_some_and(|links| links.iter().any(|&(n, _)| n == b))
+        };
+        let step = |a: usize, b: usize| {
+            let (pa, pb) = (self.position(a), self.position(b));
+            (
+                ((pb.x - pa.x) / CELL_SIZE).round() as i32,
+                ((pb.z - pa.z) / CELL_SIZE).round() as i32,
+            )
+        };
+        let mut waypoints: Vec<Waypoint> = Vec::new();
+        for i in 1..nodes.len() {
+            let (prev, node) = (nodes[i - 1], nodes[i]);
+            let via_link = is_link(prev, node);
+            let last = i + 1 == nodes.len();
+            let turns = !last && step(prev, node) != step(node, nodes[i + 1]);
+            let climbs = (self.nodes[node].y - self.nodes[prev].y).abs() > 0.05;
+            let next_is_link = !last && is_link(node, nodes[i + 1]);
+            if last || via_link || turns || climbs || next_is_link {
+                waypoints.push(Waypoint {
+                    position: self.position(node),
+                    via_link,
+                });
+            }
+        }
+        waypoints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn house() -> (NavGrid, Level) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/levels/house.json");
+        let level = Level::load(&path).expect("house level");
+        let physics = PhysicsWorld::from_level(&level);
+        (NavGrid::bake(&physics, &level), level)
+    }
+
+    fn at(x: f32, y: f32, z: f32) -> Position {
+        Position { x, y, z }
+    }
+
+    #[test]
+    fn path_from_yard_enters_through_the_door_and_climbs_to_the_loft() {
+        let (grid, _) = house();
+        let path = grid
+            .find_path(&at(0.0, 0.0, -12.0), &at(-3.0, 3.1, 3.5))
+            .expect("loft is reachable");
+        let end = &path.last().unwrap().position;
+        assert!((end.y - 3.1).abs() < 0.1, "ends on the loft: {end:?}");
+        // The south wall is at z = -5 with a door between x = -0.75 and 0.75
+        let mut previous = at(0.0, 0.0, -12.0);
+        for waypoint in &path {
+            let p = &waypoint.position;
+            if (previous.z < -5.0) != (p.z < -5.0) && !waypoint.via_link {
+                let t = (-5.0 - previous.z) / (p.z - previous.z);
+                let x = previous.x + t * (p.x - previous.x);
+                assert!(
+                    x.abs() < 0.75,
+                    "crosses the wall outside the door at x = {x}"
+                );
+            }
+            previous = p.clone();
+        }
+    }
+
+    #[test]
+    fn elevator_link_connects_floors() {
+        let (grid, level) = house();
+        let [x, y, z] = level.links[0].from;
+        let [tx, ty, tz] = level.links[0].to;
+        let path = grid
+            .find_path(&at(x, y, z), &at(tx, ty, tz))
+            .expect("link is usable");
+        assert!(path.iter().any(|w| w.via_link));
+    }
+}
@@ -0,0 +1,285 @@
+//! Character needs simulation module.
+//!
+//! Every character carries a set of need meters (hunger, thirst, sleepiness,
+//! exhaustion, dirtiness, boredom). Each meter runs from 0 (fully satisfied) to
+//! 100 (desperate). Meters rise by a base rate every game minute, and the
+//! character's current [`Activity`] adds its own per-minute effects on top,
+//! restoring some needs and draining others.
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::Activity;
+
+/// Lowest value of a need meter (fully satisfied).
+pub const NEED_MIN: f32 = 0.0;
+/// Highest value of a need meter (desperate).
+pub const NEED_MAX: f32 = 100.0;
+/// Level above which a need is considered critical.
+pub const NEED_CRITICAL: f32 = 80.0;
+
+/// Need meters of a character, 0 = satisfied, 100 = desperate.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Needs {
+    /// Need for food
+    pub hunger: f32,
+    /// Need for drink
+    pub thirst: f32,
+    /// Need for sleep
+    pub sleepiness: f32,
+    /// Physical fatigue
+    pub exhaustion: f32,
+    /// Need for a wash
+    pub dirtiness: f32,
+    /// Need for entertainment
+    pub boredom: f32,
+}
+
+impl Default for Needs {
+    /// A rested, fed and clean character.
+    fn default() -> Self {
+        Self {
+            hunger: 20.0,
+            thirst: 20.0,
+            sleepiness: 10.0,
+            exhaustion: 0.0,
+            dirtiness: 10.0,
+            boredom: 20.0,
+        }
+    }
+}
+
+/// Per-game-minute change of each need meter.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NeedRates {
+    pub hunger: f32,
+    pub thirst: f32,
+    pub sleepiness: f32,
+    pub exhaustion: f32,
+    pub dirtiness: f32,
+    pub boredom: f32,
+}
+
+impl NeedRates {
+    /// Base increase of every need per game minute, whatever the character is doing.
+    ///
+    /// Roughly: hungry after 8 hours, thirsty after 6, sleepy after 16 awake,
+    /// grimy after a day and a half, bored after 10 hours of nothing.
+    pub const BASE: NeedRates = NeedRates {
+        hunger: 0.125,
+        thirst: 0.17,
+        sleepiness: 0.09,
+        exhaustion: 0.01,
+        dirtiness: 0.045,
+        boredom: 0.1,
+    };
+
+    /// Sum of two sets of rates.
+    pub fn plus(self, other: NeedRates) -> NeedRates {
+        NeedRates {
+            hunger: self.hunger + other.hunger,
+            thirst: self.thirst + other.thirst,
+            sleepiness: self.sleepiness + other.sleepiness,
+            exhaustion: self.exhaustion + other.exhaustion,
+            dirtiness: self.dirtiness + other.dirtiness,
+            boredom: self.boredom + other.boredom,
+        }
+    }
+
+    /// Look up a rate by need name.
+    pub fn get(&self, name: &str) -> Option<f32> {
+        Some(match name {
+            "hunger" => self.hunger,
+            "thirst" => self.thirst,
+            "sleepiness" => self.sleepiness,
+            "exhaustion" => self.exhaustion,
+            "dirtiness" => self.dirtiness,
+            "boredom" => self.boredom,
+            _ => return None,
+        })
+    }
+}
+
+impl Activity {
+    /// Per-game-minute effect of this activity on needs, added to [`NeedRates::BASE`].
+    ///
+    /// Negative values restore a need, positive values drain it faster.
+    pub fn need_effects(&self) -> NeedRates {
+        let zero = NeedRates::default();
+        match self {
+            Activity::Idle => zero,
+            Activity::Sleeping => NeedRates {
+                // Metabolism slows and boredom does not build while asleep
+                hunger: -0.08,
+                thirst: -0.08,
+                sleepiness: -0.3,
+                exhaustion: -0.3,
+                boredom: -0.1,
+                ..zero
+            },
+            Activity::Eating => NeedRates {
+                hunger: -3.0,
+                thirst: -0.5,
+                ..zero
+            },
+            Activity::Cooking => NeedRates {
+                dirtiness: 0.02,
+                boredom: -0.15,
+                ..zero
+            },
+            Activity::Working => NeedRates {
+                exhaustion: 0.05,
+                boredom: 0.05,
+                ..zero
+            },
+            Activity::Exercising => NeedRates {
+                hunger: 0.1,
+                thirst: 0.3,
+                exhaustion: 0.6,
+                dirtiness: 0.25,
+                boredom: -0.3,
+                ..zero
+            },
+            Activity::Socializing => NeedRates {
+                boredom: -0.35,
+                ..zero
+            },
+            Activity::Shopping => NeedRates {
+                exhaustion: 0.05,
+                boredom: -0.2,
+                ..zero
+            },
+            Activity::Cleaning => NeedRates {
+                exhaustion: 0.1,
+                dirtiness: 0.05,
+                boredom: 0.05,
+                ..zero
+            },
+            Activity::Bathing => NeedRates {
+                dirtiness: -4.0,
+                exhaustion: -0.1,
+                ..zero
+            },
+            Activity::Reading => NeedRates {
+                boredom: -0.3,
+                ..zero
+            },
+            Activity::WatchingTv => NeedRates {
+                boredom: -0.4,
+                exhaustion: -0.05,
+                ..zero
+            },
+            Activity::Gaming => NeedRates {
+                boredom: -0.5,
+                sleepiness: 0.03,
+                ..zero
+            },
+            Activity::Commuting => NeedRates {
+                exhaustion: 0.05,
+                boredom: 0.05,
+                ..zero
+            },
+        }
+    }
+}
+
+impl Needs {
+    /// Advance the meters by a number of game minutes spent on an activity.
+    pub fn tick(&mut self, activity: &Activity, minutes: f32) {
+        self.apply(NeedRates::BASE.plus(activity.need_effects()), minutes);
+    }
+
+    /// Apply a one-off change to the meters (e.g. drinking a glass of water).
+    pub fn adjust(&mut self, delta: NeedRates) {
+        self.apply(delta, 1.0);
+    }
+
+    fn apply(&mut self, rates: NeedRates, minutes: f32) {
+        let step = |value: &mut f32, rate: f32| {
+            *value = (*value + rate * minutes).clamp(NEED_MIN, NEED_MAX);
+        };
+        step(&mut self.hunger, rates.hunger);
+        step(&mut self.thirst, rates.thirst);
+        step(&mut self.sleepiness, rates.sleepiness);
+        step(&mut self.exhaustion, rates.exhaustion);
+        step(&mut self.dirtiness, rates.dirtiness);
+        step(&mut self.boredom, rates.boredom);
+    }
+
+    /// Look up a meter by name (as used in schedule rules and client messages).
+    pub fn get(&self, name: &str) -> Option<f32> {
+        Some(match name {
+            "hunger" => self.hunger,
+            "thirst" => self.thirst,
+            "sleepiness" => self.sleepiness,
+            "exhaustion" => self.exhaustion,
+            "dirtiness" => self.dirtiness,
+            "boredom" => self.boredom,
+            _ => return None,
+        })
+    }
+
+    /// All meters as (name, value) pairs.
+    pub fn meters(&self) -> [(&'static str, f32); 6] {
+        [
+            ("hunger", self.hunger),
+            ("thirst", self.thirst),
+            ("sleepiness", self.sleepiness),
+            ("exhaustion", self.exhaustion),
+            ("dirtiness", self.dirtiness),
+            ("boredom", self.boredom),
+        ]
+    }
+
+    /// Names of the meters at or above [`NEED_CRITICAL`].
+    pub fn critical(&self) -> Vec<&'static str> {
+        self.meters()
+            .into_iter()
+            .filter(|(_, value)| *value >= NEED_CRITICAL)
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_needs_rise_at_base_rate() {
+        let mut needs = Needs::default();
+        needs.tick(&Activity::Idle, 60.0);
+        let mut expected = Needs::default();
+        expected.hunger += NeedRates::BASE.hunger * 60.0;
+        expected.thirst += NeedRates::BASE.thirst * 60.0;
+        expected.sleepiness += NeedRates::BASE.sleepiness * 60.0;
+        expected.exhaustion += NeedRates::BASE.exhaustion * 60.0;
+        expected.dirtiness += NeedRates::BASE.dirtiness * 60.0;
+        expected.boredom += NeedRates::BASE.boredom * 60.0;
+        assert_eq!(needs, expected);
+    }
+
+    #[test]
+    fn a_night_of_sleep_restores_sleepiness() {
+        let mut needs = Needs {
+            sleepiness: 90.0,
+            exhaustion: 50.0,
+            ..Needs::default()
+        };
+        needs.tick(&Activity::Sleeping, 8.0 * 60.0);
+        assert_eq!(needs.sleepiness, NEED_MIN);
+        assert_eq!(needs.exhaustion, NEED_MIN);
+        assert!(
+            needs.hunger < 50.0,
+            "hunger rose too fast: {}",
+            needs.hunger
+        );
+    }
+
+    #[test]
+    fn meters_are_clamped() {
+        let mut needs = Needs::default();
+        needs.tick(&Activity::Exercising, 10_000.0);
+        assert!(needs.meters().iter().all(|(_, v)| *v <= NEED_MAX));
+        assert_eq!(needs.critical().len(), 5);
+    }
+}
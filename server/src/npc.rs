@@ -0,0 +1,212 @@
+//! Non-player character module.
+//!
+//! NPCs are ordinary players (with a human entity, needs, mood and timed
+//! activities) that nobody controls. They are spawned from `data/npcs/*.json`
+//! and choose what to do with a utility scorer: every candidate activity is
+//! scored by how much it relieves the character's current needs, weighted by
+//! personality and by how far away the object it needs is. NPCs walk to that
+//! object along a path found on the navigation grid before starting the activity.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::game::{Activity, Position};
+use crate::genetics::AppearanceChoice;
+use crate::level::data_dir;
+use crate::needs::Needs;
+
+/// Distance (m) within which an NPC looks for objects.
+pub const SEARCH_RADIUS: f32 = 40.0;
+/// Distance (m) at which an object's attraction is halved.
+const DISTANCE_FALLOFF: f32 = 20.0;
+/// Score below which an NPC stays idle.
+const MIN_SCORE: f32 = 0.05;
+
+/// NPC definition from the configuration files.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NpcDef {
+    /// Unique ID (also the player ID)
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// Spawn position (feet)
+    pub position: Position,
+    /// Personality: multiplier per need name (e.g. {"boredom": 1.5} for a
+    /// character who hates being bored); missing needs weigh 1.0
+    #[serde(default)]
+    pub need_weights: HashMap<String, f32>,
+    /// Age in game years (a young adult if omitted)
+    #[serde(default)]
+    pub age_years: Option<i64>,
+    /// Hair style and outfit (the genome is derived from the ID)
+    #[serde(default)]
+    pub appearance: AppearanceChoice,
+}
+
+impl NpcDef {
+    fn weight(&self, need: &str) -> f32 {
+        self.need_weights.get(need).copied().unwrap_or(1.0)
+    }
+}
+
+/// What an NPC decided to do.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Intention {
+    pub activity: Activity,
+    /// Object to walk to first (None when the activity needs no object)
+    pub object_id: Option<String>,
+    pub score: f32,
+}
+
+/// Per-minute relief each activity gives each need, normalized so the best
+/// activity for a need scores 1.0 on it.
+fn relief(activity: &Activity, need: &str) -> f32 {
+    let best = Activity::ALL
+        .iter()
+        .filter_map(|a| a.need_effects().get(need))
+        .fold(0.0_f32, |best, effect| best.max(-effect));
+    let effect = activity.need_effects().get(need).unwrap_or(0.0);
+    if best > 0.0 {
+        (-effect / best).max(0.0)
+    } else {
+        0.0
+    }
+}
+
+/// Score every activity for a character and return the best one, if worth doing.
+///
+/// `nearest_object` finds the nearest object with a catalogue tag within
+/// [`SEARCH_RADIUS`], returning its ID and distance. Activities whose object
+/// cannot be found are skipped.
+pub fn choose(
+    def: &NpcDef,
+    needs: &Needs,
+    nearest_object: impl Fn(&str) -> Option<(String, f32)>,
+) -> Option<Intention> {
+    let mut best: Option<Intention> = None;
+    for activity in Activity::ALL {
+        if activity == Activity::Idle {
+            continue;
+        }
+        let (object_id, distance) = match activity.spec().required_tag {
+            Some(tag) => match nearest_object(tag) {
+                Some((id, distance)) => (Some(id), distance),
+                None => continue,
+            },
+            None => (None, 0.0),
+        };
+        // Urgency grows with the square of the need level
+        let mut score: f32 = needs
+            .meters()
+            .iter()
+            .map(|(name, level)| {
+                let urgency = (level / 100.0).powi(2);
+                urgency * relief(&activity, name) * def.weight(name)
+            })
+            .sum();
+        // Activities that make needs worse are less attractive
+        let harm: f32 = needs
+            .meters()
+            .iter()
+            .map(|(name, _)| activity.need_effects().get(name).unwrap_or(0.0).max(0.0))
+            .sum();
+        score -= 0.1 * harm;
+        score /= 1.0 + distance / DISTANCE_FALLOFF;
+        if score >= MIN_SCORE && best.as_ref().is_none_or(|b| score > b.score) {
+            best = Some(Intention {
+                activity,
+                object_id,
+                score,
+            });
+        }
+    }
+    best
+}
+
+/// Load every `*.json` file (an array of NPC definitions) in a directory.
+pub fn load_dir(dir: &Path) -> anyhow::Result<Vec<NpcDef>> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut npcs = Vec::new();
+    for path in paths {
+        let text = std::fs::read_to_string(&path)?;
+        let defs: Vec<NpcDef> =
+            serde_json::from_str(&text).map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+        npcs.extend(defs);
+    }
+    Ok(npcs)
+}
+
+/// Load NPC definitions from `NPC_DIR` or `data/npcs/`.
+///
+/// No NPCs are spawned if the directory is missing or invalid.
+pub fn load_configured() -> Vec<NpcDef> {
+    let dir = std::env::var("NPC_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|_| data_dir().join("npcs"));
+    match load_dir(&dir) {
+        Ok(npcs) => {
+            tracing::info!("Loaded {} NPCs from {}", npcs.len(), dir.display());
+            npcs
+        }
+        Err(e) => {
+            tracing::warn!("Failed to load NPCs from {}: {e}", dir.display());
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def() -> NpcDef {
+        NpcDef {
+            id: "npc_test".to_string(),
+            name: "Test".to_string(),
+            position: Position {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            need_weights: HashMap::new(),
+            age_years: None,
+            appearance: AppearanceChoice::default(),
+        }
+    }
+
+    #[test]
+    fn hungry_npc_eats_and_sleepy_npc_looks_for_a_bed() {
+        let hungry = Needs {
+            hunger: 90.0,
+            ..Needs::default()
+        };
+        let intention = choose(&def(), &hungry, |_| None).unwrap();
+        assert_eq!(intention.activity, Activity::Eating);
+
+        let sleepy = Needs {
+            sleepiness: 90.0,
+            exhaustion: 60.0,
+            ..Needs::default()
+        };
+        let no_bed = choose(&def(), &sleepy, |_| None).map(|i| i.activity);
+        assert_ne!(no_bed, Some(Activity::Sleeping));
+        let with_bed = choose(&def(), &sleepy, |tag| {
+            (tag == "bed").then(|| ("bed_1".to_string(), 8.0))
+        })
+        .unwrap();
+        assert_eq!(with_bed.activity, Activity::Sleeping);
+        assert_eq!(with_bed.object_id.as_deref(), Some("bed_1"));
+    }
+
+    #[test]
+    fn data_files_parse() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/npcs");
+        assert!(!load_dir(&dir).expect("npc files should parse").is_empty());
+    }
+}
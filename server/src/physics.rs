@@ -4,13 +4,100 @@
 //! Units: meters (1 unit = 1 m).
 
 use rand::Rng;
+use rapier3d::control::{CharacterAutostep, CharacterLength, KinematicCharacterController};
 use rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver};
+
+use crate::catalogue::{BodyKind, ObjectType, ShapeDef};
+use crate::level::{Geometry, Level};
+
+/// Minimum total contact force (newtons) before a contact force event is reported.
+///
+/// Keeps resting contacts (a human standing still, a ball rolling) from flooding
+/// the event stream while still reporting real impacts.
+const CONTACT_FORCE_EVENT_THRESHOLD: f32 = 1.0;
+
+/// Maximum downward speed of a falling character (m/game-second).
+const MAX_FALL_SPEED: f32 = 50.0;
+
+/// Per-character movement state for the kinematic character controller.
+#[derive(Clone, Debug, Default)]
+pub struct CharacterState {
+    /// Horizontal walking velocity requested by the character's controller (m/game-second)
+    pub intent: Vector<Real>,
+    /// Current vertical velocity from gravity (m/game-second, negative = falling)
+    pub vertical_velocity: f32,
+    /// Whether the character was standing on the ground after its last move
+    pub grounded: bool,
+}
+
+/// One side of a physics interaction.
+///
+/// Colliders are mapped back to the game entity that owns them; static world
+/// geometry (ground, walls) is reported by its label instead.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContactTarget {
+    /// A game entity (e.g. `ball_3`, `human_<player_id>`)
+    Entity(String),
+    /// A piece of static world geometry (e.g. `ground`, `wall_east`)
+    World(String),
+}
+
+impl ContactTarget {
+    /// Entity ID of this side of the contact, if it is an entity.
+    pub fn entity_id(&self) -> Option<&str> {
+        match self {
+            ContactTarget::Entity(id) => Some(id),
+            ContactTarget::World(_) => None,
+        }
+    }
+}
+
+/// Physics interaction event produced by a simulation step.
+///
+/// Serialized with a "kind" tag so it can be forwarded to clients as-is.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PhysicsEvent {
+    /// Two colliders started touching
+    CollisionStarted { a: ContactTarget, b: ContactTarget },
+    /// Two colliders stopped touching
+    CollisionStopped { a: ContactTarget, b: ContactTarget },
+    /// Contact force between two colliders exceeded the event threshold
+    ContactForce {
+        a: ContactTarget,
+        b: ContactTarget,
+        /// Sum of the magnitudes of all contact forces (newtons)
+        magnitude: f32,
+    },
+}
+
+impl PhysicsEvent {
+    /// Both sides of the interaction.
+    pub fn targets(&self) -> (&ContactTarget, &ContactTarget) {
+        match self {
+            PhysicsEvent::CollisionStarted { a, b }
+            | PhysicsEvent::CollisionStopped { a, b }
+            | PhysicsEvent::ContactForce { a, b, .. } => (a, b),
+        }
+    }
+
+    /// Whether the event involves the given entity.
+    pub fn involves(&self, entity_id: &str) -> bool {
+        let (a, b) = self.targets();
+        a.entity_id() == Some(entity_id) || b.entity_id() == Some(entity_id)
+    }
+}
 
 /// Physics simulation world.
 ///
 /// Manages rigid bodies, colliders, and physics simulation.
-/// Handles both dynamic objects (balls) and kinematic objects (humans).
+/// Handles dynamic objects (balls), kinematic objects and characters (humans),
+/// with body kind and shape taken from the object catalogue.
+/// Static geometry (ground, walls, buildings) comes from a [`Level`].
 pub struct PhysicsWorld {
     pub rigid_body_set: RigidBodySet,
     pub collider_set: ColliderSet,
@@ -24,107 +111,50 @@ pub struct PhysicsWorld {
     pub gravity: Vector<Real>,
     pub integration_parameters: IntegrationParameters,
     pub entity_handles: HashMap<String, RigidBodyHandle>,
+    /// Reverse lookup from rigid body to entity ID (for mapping physics events)
+    pub body_entities: HashMap<RigidBodyHandle, String>,
+    /// Labels of static world colliders (ground, walls) reported in physics events
+    pub static_labels: HashMap<ColliderHandle, String>,
+    /// Character controller used to move humans (slopes, steps, sliding, ground snapping)
+    pub character_controller: KinematicCharacterController,
+    /// Movement state of every character body, keyed by entity ID
+    pub characters: HashMap<String, CharacterState>,
+    /// Wind velocity (m/s) pushing balls around
+    pub wind: Vector<Real>,
 }
 
+/// Air drag on a ball (kg/m): ½ · air density · drag coefficient · cross-section
+/// of a 0.5 m radius ball.
+const BALL_WIND_DRAG: f32 = 0.5 * 1.2 * 0.47 * 0.785;
+
 impl PhysicsWorld {
-    /// Create a new physics world with ground plane.
+    /// Create a new physics world with the built-in arena level.
     ///
-    /// Initializes physics simulation components and creates a ground collider.
-    /// Ground is a large plane (10000x10000 units) with perfect elasticity.
+    /// The arena is a 100 m × 100 m elastic ground with 20 m tall boundary walls.
     pub fn new() -> Self {
-        let mut rigid_body_set = RigidBodySet::new();
-        let mut collider_set = ColliderSet::new();
-
-        // Create ground plane (large cuboid to match visual ground size of 100m x 100m)
-        // Ground surface is at y=0, so the top of the cuboid is at y=0.
-        // Using half-extents: 50 (half of 100) for x/z, 0.1 for y (0.2m thick ground).
-        // Create a static rigid body for the ground
-        // Position ground body at y = -0.1 so the top surface is at y = 0.0
-        let ground_body = RigidBodyBuilder::fixed()
-            .translation(vector![0.0, -0.1, 0.0])
-            .build();
-        let ground_handle = rigid_body_set.insert(ground_body);
-
-        let ground_collider = ColliderBuilder::cuboid(50.0, 0.1, 50.0)
-            .friction(0.0)
-            .restitution(1.0) // Perfect elasticity
-            .build();
-        collider_set.insert_with_parent(ground_collider, ground_handle, &mut rigid_body_set);
-
-        // Create boundary walls around the perimeter to contain bouncing balls.
-        // Ground is 100m x 100m, so boundaries are at ±50.
-        // Walls are 20 meters tall to contain high bounces.
-        let wall_half_height = 10.0;
-        let wall_half_thickness = 0.5; // 1 meter thick walls
-        let ground_half_size = 50.0;
-
-        // East wall (positive X) - inner edge at x = ground_half_size
-        let east_wall_body = RigidBodyBuilder::fixed()
-            .translation(vector![
-                ground_half_size + wall_half_thickness,
-                wall_half_height,
-                0.0
-            ])
-            .build();
-        let east_wall_handle = rigid_body_set.insert(east_wall_body);
-        let east_wall =
-            ColliderBuilder::cuboid(wall_half_thickness, wall_half_height, ground_half_size)
-                .friction(0.0)
-                .restitution(1.0) // Perfect elasticity
-                .build();
-        collider_set.insert_with_parent(east_wall, east_wall_handle, &mut rigid_body_set);
-
-        // West wall (negative X) - inner edge at x = -ground_half_size
-        let west_wall_body = RigidBodyBuilder::fixed()
-            .translation(vector![
-                -ground_half_size - wall_half_thickness,
-                wall_half_height,
-                0.0
-            ])
-            .build();
-        let west_wall_handle = rigid_body_set.insert(west_wall_body);
-        let west_wall =
-            ColliderBuilder::cuboid(wall_half_thickness, wall_half_height, ground_half_size)
-                .friction(0.0)
-                .restitution(1.0) // Perfect elasticity
-                .build();
-        collider_set.insert_with_parent(west_wall, west_wall_handle, &mut rigid_body_set);
-
-        // North wall (positive Z) - inner edge at z = ground_half_size
-        let north_wall_body = RigidBodyBuilder::fixed()
-            .translation(vector![
-                0.0,
-                wall_half_height,
-                ground_half_size + wall_half_thickness
-            ])
-            .build();
-        let north_wall_handle = rigid_body_set.insert(north_wall_body);
-        let north_wall =
-            ColliderBuilder::cuboid(ground_half_size, wall_half_height, wall_half_thickness)
-                .friction(0.0)
-                .restitution(1.0) // Perfect elasticity
-                .build();
-        collider_set.insert_with_parent(north_wall, north_wall_handle, &mut rigid_body_set);
-
-        // South wall (negative Z) - inner edge at z = -ground_half_size
-        let south_wall_body = RigidBodyBuilder::fixed()
-            .translation(vector![
-                0.0,
-                wall_half_height,
-                -ground_half_size - wall_half_thickness
-            ])
-            .build();
-        let south_wall_handle = rigid_body_set.insert(south_wall_body);
-        let south_wall =
-            ColliderBuilder::cuboid(ground_half_size, wall_half_height, wall_half_thickness)
-                .friction(0.0)
-                .restitution(1.0) // Perfect elasticity
-                .build();
-        collider_set.insert_with_parent(south_wall, south_wall_handle, &mut rigid_body_set);
+        Self::from_level(&Level::arena())
+    }
+
+    /// Create a new physics world from a level description.
+    ///
+    /// Each geometry piece becomes a fixed rigid body with one or more colliders,
+    /// labelled with the piece name for physics events.
+    pub fn from_level(level: &Level) -> Self {
+        let mut world = Self::empty();
+        for geometry in &level.geometry {
+            world.add_static_geometry(geometry);
+        }
+        // Run one empty step so static colliders are in the broad phase before the
+        // character controller queries it
+        world.step(0.0);
+        world
+    }
 
+    /// Create a physics world without any static geometry.
+    fn empty() -> Self {
         Self {
-            rigid_body_set,
-            collider_set,
+            rigid_body_set: RigidBodySet::new(),
+            collider_set: ColliderSet::new(),
             physics_pipeline: PhysicsPipeline::new(),
             island_manager: IslandManager::new(),
             broad_phase: BroadPhaseBvh::new(),
@@ -142,92 +172,187 @@ impl PhysicsWorld {
                 ..IntegrationParameters::default()
             },
             entity_handles: HashMap::new(),
+            body_entities: HashMap::new(),
+            static_labels: HashMap::new(),
+            character_controller: KinematicCharacterController {
+                up: Vector::y_axis(),
+                slide: true,
+                // Climb stairs up to 30 cm without jumping
+                autostep: Some(CharacterAutostep {
+                    max_height: CharacterLength::Absolute(0.3),
+                    min_width: CharacterLength::Absolute(0.2),
+                    include_dynamic_bodies: false,
+                }),
+                max_slope_climb_angle: 45.0_f32.to_radians(),
+                min_slope_slide_angle: 35.0_f32.to_radians(),
+                snap_to_ground: Some(CharacterLength::Absolute(0.3)),
+                ..KinematicCharacterController::default()
+            },
+            characters: HashMap::new(),
+            wind: Vector::zeros(),
         }
     }
 
-    /// Create a bouncy ball entity with physics simulation.
-    ///
-    /// Creates a dynamic rigid body (sphere) that responds to gravity and collisions.
-    /// Ball has perfect elasticity (restitution = 1.0) and no friction.
-    ///
-    /// # Arguments
-    /// * `entity_id` - Unique identifier for the entity
-    /// * `x` - Initial X position (meters)
-    /// * `z` - Initial Z position (meters)
+    /// Add a piece of static level geometry as fixed colliders.
     ///
-    /// # Returns
-    /// Rigid body handle for physics updates
-    pub fn create_bouncy_ball(&mut self, entity_id: String, x: f32, z: f32) -> RigidBodyHandle {
-        use rand::Rng;
+    /// Box-based pieces (floors, walls, ramps, stairs) become cuboids; decks and mesh
+    /// terrain become triangle meshes; heightmaps become heightfields.
+    pub fn add_static_geometry(&mut self, geometry: &Geometry) {
+        let body = self
+            .rigid_body_set
+            .insert(RigidBodyBuilder::fixed().build());
+        let material = geometry.material();
+        let mut builders = Vec::new();
 
-        // Random initial velocity for trajectory variation
-        // Velocities are in game-time units (m/game-second)
-        let mut rng = rand::thread_rng();
-        let vel_x = rng.gen_range(-1.0..1.0);
-        let vel_z = rng.gen_range(-1.0..1.0);
-        let vel_y = 0.0; // Zero vertical velocity
-
-        let rigid_body = RigidBodyBuilder::dynamic()
-            .translation(vector![x, 5.0, z]) // Start at 5 meters for visibility
-            .linvel(vector![vel_x, vel_y, vel_z])
-            .build();
-        let handle = self.rigid_body_set.insert(rigid_body);
+        for shape in geometry.boxes() {
+            let [cx, cy, cz] = shape.center;
+            let [hx, hy, hz] = shape.half_extents;
+            // Yaw around Y, then tilt around the local X axis (negative pitch raises local +Z)
+            let rotation =
+                rapier3d::na::UnitQuaternion::from_axis_angle(&Vector::y_axis(), shape.yaw)
+                    * rapier3d::na::UnitQuaternion::from_axis_angle(
+                        &Vector::x_axis(),
+                        -shape.pitch,
+                    );
+            builders.push(
+                ColliderBuilder::cuboid(hx, hy, hz)
+                    .position(Isometry::from_parts(Translation::new(cx, cy, cz), rotation)),
+            );
+        }
 
-        // Create sphere collider with perfect elasticity
-        // Ball radius is 0.5m to match visual representation
-        // For 100g (0.1 kg) mass: density = 0.1 kg / ((4/3)π(0.5)³) ≈ 0.191 kg/m³
-        let collider = ColliderBuilder::ball(0.5)
-            .restitution(1.0) // Perfect elasticity
-            .friction(0.0)
-            .density(0.191) // 100g ball: 0.1 kg / 0.523599 m³ ≈ 0.191 kg/m³
-            .build();
-        self.collider_set
-            .insert_with_parent(collider, handle, &mut self.rigid_body_set);
+        if let Some(mesh) = geometry.mesh() {
+            let points = mesh
+                .vertices
+                .iter()
+                .map(|v| point![v[0], v[1], v[2]])
+                .collect();
+            match ColliderBuilder::trimesh(points, mesh.indices) {
+                Ok(builder) => builders.push(builder),
+                Err(e) => tracing::warn!("Invalid mesh for geometry '{}': {e}", geometry.name()),
+            }
+        }
 
-        // Enable continuous collision detection (CCD) on the rigid body to prevent tunneling
-        if let Some(body) = self.rigid_body_set.get_mut(handle) {
-            body.enable_ccd(true);
+        if let Geometry::Heightmap {
+            center,
+            size,
+            max_height,
+            heights: Some(grid),
+            ..
+        } = geometry
+        {
+            let heights =
+                rapier3d::na::DMatrix::from_row_slice(grid.rows, grid.cols, &grid.heights);
+            builders.push(
+                ColliderBuilder::heightfield(heights, vector![size[0], *max_height, size[1]])
+                    .translation(vector![center[0], center[1], center[2]]),
+            );
         }
 
-        self.entity_handles.insert(entity_id, handle);
-        handle
+        for builder in builders {
+            let collider = builder
+                .friction(material.friction)
+                .restitution(material.restitution)
+                .build();
+            let handle =
+                self.collider_set
+                    .insert_with_parent(collider, body, &mut self.rigid_body_set);
+            self.static_labels
+                .insert(handle, geometry.name().to_string());
+        }
     }
 
-    /// Create a human entity with kinematic physics body.
+    /// Create an entity's physics body from its object type.
     ///
-    /// Creates a kinematic rigid body (position-controlled, not physics-controlled).
-    /// Uses a capsule collider for human shape.
+    /// The body kind, collision shape, mass and material come from the catalogue:
+    /// - `dynamic` bodies respond to gravity and collisions (optionally with CCD and
+    ///   a random initial horizontal velocity for trajectory variation)
+    /// - `fixed` bodies never move
+    /// - `kinematic` bodies are moved by the server
+    /// - `character` bodies are moved by the character controller; their origin is
+    ///   at the bottom of the shape (the feet) so positions match the ground height
     ///
     /// # Arguments
     /// * `entity_id` - Unique identifier for the entity
+    /// * `object_type` - Catalogue entry describing the object
     /// * `x` - Initial X position (meters)
     /// * `y` - Initial Y position (meters)
     /// * `z` - Initial Z position (meters)
     ///
     /// # Returns
-    /// Rigid body handle for position updates
-    pub fn create_human(&mut self, entity_id: String, x: f32, y: f32, z: f32) -> RigidBodyHandle {
-        let rigid_body = RigidBodyBuilder::kinematic_position_based()
-            .translation(vector![x, y, z])
-            .build();
+    /// Rigid body handle for physics updates
+    pub fn create_object(
+        &mut self,
+        entity_id: String,
+        object_type: &ObjectType,
+        x: f32,
+        y: f32,
+        z: f32,
+    ) -> RigidBodyHandle {
+        let builder = match object_type.body {
+            BodyKind::Dynamic => RigidBodyBuilder::dynamic().ccd_enabled(object_type.ccd),
+            BodyKind::Fixed => RigidBodyBuilder::fixed(),
+            BodyKind::Kinematic | BodyKind::Character => {
+                RigidBodyBuilder::kinematic_position_based()
+            }
+        };
+        let mut rigid_body = builder.translation(vector![x, y, z]).build();
+        if object_type.random_velocity > 0.0 {
+            // Random initial velocity for trajectory variation
+            // Velocities are in game-time units (m/game-second)
+            let mut rng = rand::thread_rng();
+            let max = object_type.random_velocity;
+            rigid_body.set_linvel(
+                vector![rng.gen_range(-max..max), 0.0, rng.gen_range(-max..max)],
+                true,
+            );
+        }
         let handle = self.rigid_body_set.insert(rigid_body);
 
-        // Create capsule collider for human
-        let collider = ColliderBuilder::capsule_y(0.5, 0.3)
-            .friction(0.5)
-            .restitution(0.0)
-            .build();
+        let mut collider = match object_type.shape {
+            ShapeDef::Ball { radius } => ColliderBuilder::ball(radius),
+            ShapeDef::Cuboid { half_extents } => {
+                ColliderBuilder::cuboid(half_extents[0], half_extents[1], half_extents[2])
+            }
+            ShapeDef::Capsule {
+                half_height,
+                radius,
+            } => ColliderBuilder::capsule_y(half_height, radius),
+            ShapeDef::Cylinder {
+                half_height,
+                radius,
+            } => ColliderBuilder::cylinder(half_height, radius),
+        }
+        .friction(object_type.friction)
+        .restitution(object_type.restitution);
+        if let Some(mass) = object_type.mass {
+            collider = collider.mass(mass);
+        }
+        if object_type.body == BodyKind::Character {
+            // Raise the shape so its bottom touches the body origin
+            collider = collider.translation(vector![0.0, object_type.shape.half_height(), 0.0]);
+        }
+        if object_type.body != BodyKind::Fixed {
+            collider = collider
+                .active_events(ActiveEvents::COLLISION_EVENTS | ActiveEvents::CONTACT_FORCE_EVENTS)
+                .contact_force_event_threshold(CONTACT_FORCE_EVENT_THRESHOLD);
+        }
         self.collider_set
-            .insert_with_parent(collider, handle, &mut self.rigid_body_set);
+            .insert_with_parent(collider.build(), handle, &mut self.rigid_body_set);
 
+        self.body_entities.insert(handle, entity_id.clone());
+        if object_type.body == BodyKind::Character {
+            self.characters
+                .insert(entity_id.clone(), CharacterState::default());
+        }
         self.entity_handles.insert(entity_id, handle);
         handle
     }
 
     /// Update human position (for kinematic bodies).
     ///
-    /// Sets the position of a kinematic human body directly.
-    /// Used when player position is updated from network input.
+    /// Teleports a kinematic human body without collision checks and resets its
+    /// fall speed. Used for spawning and server-side relocation; regular movement
+    /// goes through [`PhysicsWorld::move_character`].
     ///
     /// # Arguments
     /// * `entity_id` - Entity identifier
@@ -240,16 +365,130 @@ impl PhysicsWorld {
                 body.set_translation(vector![x, y, z], true);
             }
         }
+        if let Some(character) = self.characters.get_mut(entity_id) {
+            character.vertical_velocity = 0.0;
+        }
+    }
+
+    /// Set the velocity of a dynamic body (m/game-second), e.g. to throw it.
+    pub fn set_linear_velocity(&mut self, entity_id: &str, vx: f32, vy: f32, vz: f32) {
+        if let Some(body) = self
+            .entity_handles
+            .get(entity_id)
+            .and_then(|handle| self.rigid_body_set.get_mut(*handle))
+        {
+            if body.is_dynamic() {
+                body.set_linvel(vector![vx, vy, vz], true);
+            }
+        }
+    }
+
+    /// Set the walking velocity a character keeps applying on every physics step.
+    ///
+    /// # Arguments
+    /// * `entity_id` - Entity identifier of a human
+    /// * `vx` - X velocity (m/game-second)
+    /// * `vz` - Z velocity (m/game-second); zero in both stops walking
+    pub fn set_character_intent(&mut self, entity_id: &str, vx: f32, vz: f32) {
+        if let Some(character) = self.characters.get_mut(entity_id) {
+            character.intent = vector![vx, 0.0, vz];
+        }
+    }
+
+    /// Move a character with the kinematic character controller.
+    ///
+    /// Applies gravity, slides along walls and other bodies, climbs steps and
+    /// slopes up to the controller limits, and snaps to the ground.
+    ///
+    /// # Arguments
+    /// * `entity_id` - Entity identifier of a human
+    /// * `dx` - Desired X displacement for this move (meters)
+    /// * `dz` - Desired Z displacement for this move (meters)
+    /// * `dt` - Time covered by this move (game seconds), used for gravity
+    ///
+    /// # Returns
+    /// Corrected feet position (x, y, z) after collision handling, or None if the
+    /// entity is not a character
+    pub fn move_character(
+        &mut self,
+        entity_id: &str,
+        dx: f32,
+        dz: f32,
+        dt: f32,
+    ) -> Option<(f32, f32, f32)> {
+        let handle = *self.entity_handles.get(entity_id)?;
+        let character = self.characters.get_mut(entity_id)?;
+        let body = self.rigid_body_set.get(handle)?;
+        let collider = self.collider_set.get(*body.colliders().first()?)?;
+
+        // Moves chain from the pending kinematic target so several moves per step accumulate
+        let mut position = *body.next_position();
+        let collider_offset = collider.position_wrt_parent().copied().unwrap_or_default();
+
+        character.vertical_velocity = if character.grounded {
+            0.0
+        } else {
+            (character.vertical_velocity + self.gravity.y * dt).max(-MAX_FALL_SPEED)
+        };
+        let desired = vector![dx, character.vertical_velocity * dt, dz];
+
+        let queries = self.broad_phase.as_query_pipeline(
+            self.narrow_phase.query_dispatcher(),
+            &self.rigid_body_set,
+            &self.collider_set,
+            QueryFilter::new()
+                .exclude_rigid_body(handle)
+                .exclude_sensors(),
+        );
+        let movement = self.character_controller.move_shape(
+            dt,
+            &queries,
+            collider.shape(),
+            &(position * collider_offset),
+            desired,
+            |_| {},
+        );
+
+        character.grounded = movement.grounded;
+        if movement.grounded {
+            character.vertical_velocity = 0.0;
+        }
+        position.translation.vector += movement.translation;
+        let translation = position.translation.vector;
+        if let Some(body) = self.rigid_body_set.get_mut(handle) {
+            body.set_next_kinematic_translation(translation);
+        }
+        Some((translation.x, translation.y, translation.z))
+    }
+
+    /// Set the wind blowing over the level (m/s in the X/Z plane).
+    pub fn set_wind(&mut self, x: f32, z: f32) {
+        self.wind = vector![x, 0.0, z];
     }
 
     /// Step the physics simulation forward by one time step.
     ///
     /// Updates all physics bodies, handles collisions, and applies gravity.
-    /// Also adds random velocity perturbations to balls for visual variety.
+    /// Also adds random velocity perturbations to balls for visual variety, and
+    /// lets the wind push them.
     ///
     /// # Arguments
     /// * `_dt` - Delta time in seconds (unused, but kept for API consistency)
-    pub fn step(&mut self, _dt: f64) {
+    ///
+    /// # Returns
+    /// Collision and contact force events generated during this step
+    pub fn step(&mut self, _dt: f64) -> Vec<PhysicsEvent> {
+        // Move characters by their walking intent and gravity before integrating
+        let dt = self.integration_parameters.dt;
+        let intents: Vec<(String, Vector<Real>)> = self
+            .characters
+            .iter()
+            .map(|(id, character)| (id.clone(), character.intent * dt))
+            .collect();
+        for (entity_id, step) in intents {
+            self.move_character(&entity_id, step.x, step.z, dt);
+        }
+
         // Add randomness to ball velocities on each step (simulates random bounce effects)
         let mut rng = rand::thread_rng();
         for (entity_id, handle) in &self.entity_handles {
@@ -264,12 +503,24 @@ impl PhysicsWorld {
                         linvel.z += rng.gen_range(-0.2..0.2);
                         body.set_linvel(linvel, true);
                     }
+                    // Air drag pulls the ball towards the wind's velocity, at most
+                    // up to wind speed in one step
+                    let relative = vector![self.wind.x - linvel.x, 0.0, self.wind.z - linvel.z];
+                    let drag = BALL_WIND_DRAG * relative.norm() * dt;
+                    let impulse = relative * drag.min(body.mass());
+                    if impulse.norm_squared() > 0.0 {
+                        body.apply_impulse(impulse, true);
+                    }
                 }
             }
         }
 
+        let (collision_send, collision_recv) = channel();
+        let (contact_force_send, contact_force_recv) = channel();
+        let event_collector = ChannelEventCollector::new(collision_send, contact_force_send);
+
         let hooks: &dyn rapier3d::pipeline::PhysicsHooks = &();
-        let events: &dyn rapier3d::pipeline::EventHandler = &();
+        let events: &dyn rapier3d::pipeline::EventHandler = &event_collector;
         self.physics_pipeline.step(
             &self.gravity,
             &self.integration_parameters,
@@ -284,6 +535,129 @@ impl PhysicsWorld {
             hooks,
             events,
         );
+
+        self.collect_events(collision_recv, contact_force_recv)
+    }
+
+    /// Map raw Rapier events to entity-level physics events.
+    ///
+    /// Events referring to colliders that no longer exist (e.g. removed entities)
+    /// are dropped.
+    fn collect_events(
+        &self,
+        collision_recv: Receiver<CollisionEvent>,
+        contact_force_recv: Receiver<ContactForceEvent>,
+    ) -> Vec<PhysicsEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = collision_recv.try_recv() {
+            let (Some(a), Some(b)) = (
+                self.contact_target(event.collider1()),
+                self.contact_target(event.collider2()),
+            ) else {
+                continue;
+            };
+            events.push(match event {
+                CollisionEvent::Started(..) => PhysicsEvent::CollisionStarted { a, b },
+                CollisionEvent::Stopped(..) => PhysicsEvent::CollisionStopped { a, b },
+            });
+        }
+        while let Ok(event) = contact_force_recv.try_recv() {
+            if let (Some(a), Some(b)) = (
+                self.contact_target(event.collider1),
+                self.contact_target(event.collider2),
+            ) {
+                events.push(PhysicsEvent::ContactForce {
+                    a,
+                    b,
+                    magnitude: event.total_force_magnitude,
+                });
+            }
+        }
+        events
+    }
+
+    /// Resolve a collider to the entity or static geometry that owns it.
+    fn contact_target(&self, collider: ColliderHandle) -> Option<ContactTarget> {
+        if let Some(label) = self.static_labels.get(&collider) {
+            return Some(ContactTarget::World(label.clone()));
+        }
+        let parent = self.collider_set.get(collider)?.parent()?;
+        self.body_entities
+            .get(&parent)
+            .map(|id| ContactTarget::Entity(id.clone()))
+    }
+
+    /// Vertical extent (min y, max y) of the static world geometry.
+    pub fn static_height_range(&self) -> Option<(f32, f32)> {
+        self.collider_set
+            .iter()
+            .filter(|(_, collider)| {
+                collider
+                    .parent()
+                    .and_then(|body| self.rigid_body_set.get(body))
+                    .is_some_and(|body| body.is_fixed())
+            })
+            .map(|(_, collider)| collider.compute_aabb())
+            .map(|aabb| (aabb.mins.y, aabb.maxs.y))
+            .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+    }
+
+    /// Heights of the upward-facing static surfaces in a vertical line, top first.
+    ///
+    /// Casts rays down through the static world, so floors stacked above one
+    /// another (ground floor, loft, roof) are all found. Surfaces steeper than
+    /// `max_slope_cos` (cosine of the steepest walkable slope) are skipped.
+    pub fn static_surfaces(
+        &self,
+        x: f32,
+        z: f32,
+        top: f32,
+        bottom: f32,
+        max_slope_cos: f32,
+    ) -> Vec<f32> {
+        let queries = self.broad_phase.as_query_pipeline(
+            self.narrow_phase.query_dispatcher(),
+            &self.rigid_body_set,
+            &self.collider_set,
+            QueryFilter::only_fixed().exclude_sensors(),
+        );
+        let mut surfaces = Vec::new();
+        let mut y = top;
+        // Bounded so a degenerate shape cannot stall the scan
+        for _ in 0..64 {
+            let ray = Ray::new(point![x, y, z], vector![0.0, -1.0, 0.0]);
+            // Non-solid: a ray starting inside a shape reports where it leaves it
+            let Some((_, hit)) = queries.cast_ray_and_get_normal(&ray, y - bottom, false) else {
+                break;
+            };
+            let hit_y = y - hit.time_of_impact;
+            if hit.normal.y >= max_slope_cos {
+                surfaces.push(hit_y);
+            }
+            y = hit_y - 0.01;
+        }
+        surfaces
+    }
+
+    /// Whether a vertical capsule fits at a position without touching static geometry.
+    ///
+    /// `center` is the capsule's center.
+    pub fn static_capsule_is_free(
+        &self,
+        center: (f32, f32, f32),
+        half_height: f32,
+        radius: f32,
+    ) -> bool {
+        let queries = self.broad_phase.as_query_pipeline(
+            self.narrow_phase.query_dispatcher(),
+            &self.rigid_body_set,
+            &self.collider_set,
+            QueryFilter::only_fixed().exclude_sensors(),
+        );
+        let shape = Capsule::new_y(half_height, radius);
+        let position = Isometry::translation(center.0, center.1, center.2);
+        let free = queries.intersect_shape(position, &shape).next().is_none();
+        free
     }
 
     /// Get entity position from physics world.
@@ -346,7 +720,9 @@ impl PhysicsWorld {
     /// # Arguments
     /// * `entity_id` - Entity identifier to remove
     pub fn remove_entity(&mut self, entity_id: &str) {
+        self.characters.remove(entity_id);
         if let Some(handle) = self.entity_handles.remove(entity_id) {
+            self.body_entities.remove(&handle);
             // Remove the rigid body (this will also remove associated colliders)
             self.rigid_body_set.remove(
                 handle,
@@ -365,3 +741,60 @@ impl Default for PhysicsWorld {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalogue::Catalogue;
+
+    fn spawn_human(world: &mut PhysicsWorld, x: f32, y: f32, z: f32) {
+        let catalogue = Catalogue::core();
+        let human = catalogue.get("human").unwrap();
+        world.create_object("human_a".to_string(), human, x, y, z);
+    }
+
+    #[test]
+    fn character_falls_to_ground() {
+        let mut world = PhysicsWorld::new();
+        spawn_human(&mut world, 0.0, 3.0, 0.0);
+        for _ in 0..10 {
+            world.step(1.0);
+        }
+        let (_, y, _) = world.get_entity_position("human_a").unwrap();
+        assert!(
+            y.abs() < 0.1,
+            "character should rest on the ground, y = {y}"
+        );
+    }
+
+    #[test]
+    fn character_is_stopped_by_wall() {
+        let mut world = PhysicsWorld::new();
+        spawn_human(&mut world, 48.0, 0.0, 0.0);
+        world.step(1.0);
+        let (x, _, _) = world.move_character("human_a", 10.0, 0.0, 1.0).unwrap();
+        // Human capsule radius is 0.3 m
+        assert!(x < 49.75, "character passed the wall, x = {x}");
+    }
+
+    #[test]
+    fn ball_hitting_wall_produces_contact_events() {
+        let mut world = PhysicsWorld::new();
+        let catalogue = Catalogue::core();
+        let ball = catalogue.get("ball").unwrap();
+        world.create_object("ball_1".to_string(), ball, 47.0, 2.0, 0.0);
+        // Drag keeps a light ball at wind speed, so let the wind carry it
+        world.set_wind(10.0, 0.0);
+
+        let wall = ContactTarget::World("wall_east".to_string());
+        let ball = ContactTarget::Entity("ball_1".to_string());
+        let mut started = false;
+        for _ in 0..60 {
+            started |= world.step(1.0).iter().any(|event| {
+                matches!(event, PhysicsEvent::CollisionStarted { a, b }
+                    if (*a == ball && *b == wall) || (*a == wall && *b == ball))
+            });
+        }
+        assert!(started, "no collision between ball_1 and wall_east");
+    }
+}
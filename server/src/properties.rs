@@ -0,0 +1,108 @@
+//! Entity properties module.
+//!
+//! Entities carry a bag of typed properties: whether a stove is on, a fridge is
+//! open, how far some food has spoiled. An object type declares the properties its
+//! entities have in the catalogue together with their defaults (`"properties":
+//! {"on": false}`), and the default fixes each property's type. Entities only
+//! store the values that were set; anything else reads as the type's default.
+//!
+//! Properties are changed by the server's own validated actions (interactions,
+//! activities), never written directly by clients. Each change is recorded as a
+//! [`PropertyChange`] so clients can be sent deltas between world snapshots.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::catalogue::ObjectType;
+
+/// Value of one property.
+///
+/// Serialized as the plain JSON value (`true`, `3`, `0.5`, `"raw"`).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl PropertyValue {
+    /// Name of the value's type, for error messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PropertyValue::Bool(_) => "bool",
+            PropertyValue::Int(_) => "int",
+            PropertyValue::Float(_) => "float",
+            PropertyValue::Text(_) => "text",
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            PropertyValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            PropertyValue::Float(value) => Some(*value),
+            PropertyValue::Int(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+}
+
+/// Property values by name.
+pub type Properties = HashMap<String, PropertyValue>;
+
+/// A property of an entity that changed since the last world snapshot.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PropertyChange {
+    pub entity_id: String,
+    pub name: String,
+    pub value: PropertyValue,
+}
+
+/// Check a value against the property declared by an object type.
+pub fn check(object_type: &ObjectType, name: &str, value: &PropertyValue) -> Result<(), String> {
+    let Some(default) = object_type.properties.get(name) else {
+        return Err(format!("{} has no property {name}", object_type.name));
+    };
+    if default.kind() != value.kind() {
+        return Err(format!(
+            "{}.{name} is {}, not {}",
+            object_type.name,
+            default.kind(),
+            value.kind()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalogue::Catalogue;
+    use std::path::Path;
+
+    #[test]
+    fn values_keep_their_declared_type() {
+        let parsed: Properties = serde_json::from_str(
+            r#"{"on": true, "servings": 3, "freshness": 1.0, "state": "raw"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed["on"], PropertyValue::Bool(true));
+        assert_eq!(parsed["servings"], PropertyValue::Int(3));
+        assert_eq!(parsed["freshness"], PropertyValue::Float(1.0));
+        assert_eq!(parsed["state"], PropertyValue::Text("raw".to_string()));
+
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/objects");
+        let catalogue = Catalogue::load_dir(&dir).expect("object files should parse");
+        let stove = catalogue.get("stove").unwrap();
+        assert!(check(stove, "on", &PropertyValue::Bool(true)).is_ok());
+        assert!(check(stove, "on", &PropertyValue::Int(1)).is_err());
+        assert!(check(stove, "open", &PropertyValue::Bool(true)).is_err());
+    }
+}
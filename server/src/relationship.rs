@@ -0,0 +1,232 @@
+//! Relationships module.
+//!
+//! Every pair of characters who have met shares one relationship record:
+//! familiarity (0 strangers to 100 old acquaintances), affection and trust (each
+//! -100 to 100). Characters standing close to each other can chat, argue or hug;
+//! each social action shifts the record and the mood of both characters. Chats
+//! also happen on their own between characters who are socializing side by side,
+//! and turn into arguments when one of them is angry.
+//!
+//! Relationships are symmetric: both characters see the same record. The records
+//! changed since the last save are persisted in the `relationships` table.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::mood::MoodEvent;
+
+/// Distance (m) within which characters can interact socially.
+pub const SOCIAL_DISTANCE: f32 = 2.5;
+/// Game minutes between chats of characters socializing together.
+pub const CHAT_INTERVAL_MINUTES: i64 = 15;
+/// Anger from which a chat between socializing characters becomes an argument.
+pub const ARGUE_ANGER: f32 = 60.0;
+/// Affection needed before a hug is welcome.
+const HUG_AFFECTION: f32 = 20.0;
+
+/// Something one character does to another.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SocialAction {
+    Chat,
+    Argue,
+    Hug,
+}
+
+impl SocialAction {
+    /// Change to (familiarity, affection, trust) of the relationship.
+    pub fn effect(&self) -> (f32, f32, f32) {
+        match self {
+            SocialAction::Chat => (5.0, 2.0, 1.0),
+            SocialAction::Argue => (3.0, -8.0, -5.0),
+            SocialAction::Hug => (2.0, 5.0, 3.0),
+        }
+    }
+
+    /// How the action feels to both characters.
+    pub fn mood_event(&self) -> MoodEvent {
+        match self {
+            SocialAction::Chat => MoodEvent::Socialized,
+            SocialAction::Argue => MoodEvent::Argued,
+            SocialAction::Hug => MoodEvent::Hugged,
+        }
+    }
+
+    /// Past tense, for the journal ("chatted with Ada").
+    pub fn past_tense(&self) -> &'static str {
+        match self {
+            SocialAction::Chat => "chatted",
+            SocialAction::Argue => "argued",
+            SocialAction::Hug => "hugged",
+        }
+    }
+}
+
+/// How two characters feel about each other.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Relationship {
+    /// 0 (strangers) to 100 (know each other very well)
+    pub familiarity: f32,
+    /// -100 (hatred) to 100 (love)
+    pub affection: f32,
+    /// -100 (distrust) to 100 (complete trust)
+    pub trust: f32,
+    /// Game minute of the last social action
+    pub last_interaction: i64,
+}
+
+impl Relationship {
+    /// Apply a social action at a game minute, or say why it is unwelcome.
+    pub fn apply(&mut self, action: SocialAction, now: i64) -> Result<(), String> {
+        if action == SocialAction::Hug && self.affection < HUG_AFFECTION {
+            return Err("not close enough for a hug".to_string());
+        }
+        let (familiarity, affection, trust) = action.effect();
+        self.familiarity = (self.familiarity + familiarity).clamp(0.0, 100.0);
+        self.affection = (self.affection + affection).clamp(-100.0, 100.0);
+        self.trust = (self.trust + trust).clamp(-100.0, 100.0);
+        self.last_interaction = now;
+        Ok(())
+    }
+}
+
+/// A character's relationship with someone else, as sent to clients.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RelationshipView {
+    /// ID of the other character
+    pub other_id: String,
+    #[serde(flatten)]
+    pub relationship: Relationship,
+}
+
+/// Relationship graph of every pair of characters who have met.
+#[derive(Clone, Debug, Default)]
+pub struct Relationships {
+    pairs: HashMap<(String, String), Relationship>,
+    unsaved: HashSet<(String, String)>,
+}
+
+/// Key of a pair, the same whichever character comes first.
+fn key(a: &str, b: &str) -> (String, String) {
+    if a <= b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    }
+}
+
+impl Relationships {
+    /// Relationship of two characters (strangers if they never met).
+    pub fn get(&self, a: &str, b: &str) -> Relationship {
+        self.pairs.get(&key(a, b)).cloned().unwrap_or_default()
+    }
+
+    /// Apply a social action between two characters.
+    pub fn apply(
+        &mut self,
+        a: &str,
+        b: &str,
+        action: SocialAction,
+        now: i64,
+    ) -> Result<Relationship, String> {
+        let key = key(a, b);
+        let mut relationship = self.pairs.get(&key).cloned().unwrap_or_default();
+        relationship.apply(action, now)?;
+        self.pairs.insert(key.clone(), relationship.clone());
+        self.unsaved.insert(key);
+        Ok(relationship)
+    }
+
+    /// Everyone a character has met.
+    pub fn of(&self, player_id: &str) -> Vec<RelationshipView> {
+        let mut views: Vec<_> = self
+            .pairs
+            .iter()
+            .filter_map(|((a, b), relationship)| {
+                let other_id = if a == player_id {
+                    b
+                } else if b == player_id {
+                    a
+                } else {
+                    return None;
+                };
+                Some(RelationshipView {
+                    other_id: other_id.clone(),
+                    relationship: relationship.clone(),
+                })
+            })
+            .collect();
+        views.sort_by(|x, y| x.other_id.cmp(&y.other_id));
+        views
+    }
+
+    /// Set relationships loaded from the database.
+    pub fn restore(&mut self, rows: Vec<(String, String, Relationship)>) {
+        for (a, b, relationship) in rows {
+            self.pairs.insert(key(&a, &b), relationship);
+        }
+    }
+
+    /// Remove and return the relationships of a character, as stored.
+    pub fn take_of(&mut self, player_id: &str) -> Vec<(String, String, Relationship)> {
+        let keys: Vec<(String, String)> = self
+            .pairs
+            .keys()
+            .filter(|(a, b)| a == player_id || b == player_id)
+            .cloned()
+            .collect();
+        keys.into_iter()
+            .filter_map(|key| {
+                self.unsaved.remove(&key);
+                let relationship = self.pairs.remove(&key)?;
+                Some((key.0, key.1, relationship))
+            })
+            .collect()
+    }
+
+    /// Take the relationships changed since the last call, for persisting.
+    pub fn take_unsaved(&mut self) -> Vec<(String, String, Relationship)> {
+        std::mem::take(&mut self.unsaved)
+            .into_iter()
+            .filter_map(|key| {
+                let relationship = self.pairs.get(&key)?.clone();
+                Some((key.0, key.1, relationship))
+            })
+            .collect()
+    }
+
+    /// Mark relationships that failed to persist as unsaved again.
+    pub fn return_unsaved(&mut self, rows: Vec<(String, String, Relationship)>) {
+        self.unsaved
+            .extend(rows.into_iter().map(|(a, b, _)| key(&a, &b)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relationships_are_shared_and_hugs_need_affection() {
+        let mut graph = Relationships::default();
+        assert!(graph.apply("ada", "bruno", SocialAction::Hug, 0).is_err());
+        for minute in 0..10 {
+            graph
+                .apply("bruno", "ada", SocialAction::Chat, minute)
+                .unwrap();
+        }
+        let together = graph.get("ada", "bruno");
+        assert_eq!(together, graph.get("bruno", "ada"));
+        assert_eq!(together.familiarity, 50.0);
+        assert!(graph.apply("ada", "bruno", SocialAction::Hug, 10).is_ok());
+        graph
+            .apply("ada", "bruno", SocialAction::Argue, 11)
+            .unwrap();
+        assert!(graph.get("ada", "bruno").affection < together.affection + 5.0);
+
+        assert_eq!(graph.of("ada")[0].other_id, "bruno");
+        assert!(graph.of("chen").is_empty());
+        assert_eq!(graph.take_unsaved().len(), 1);
+        assert!(graph.take_unsaved().is_empty());
+    }
+}
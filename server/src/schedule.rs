@@ -0,0 +1,698 @@
+//! Schedule and rule engine module.
+//!
+//! Players mostly control their characters indirectly: a schedule of time-of-day
+//! blocks ("07:00-08:00 eating") plus if-then rules evaluated every game minute.
+//!
+//! Rule language:
+//!
+//! ```text
+//! if hunger > 70 and kitchen has food then Cooking
+//! if sleepiness >= 90 or (time >= 23:00 and activity is not sleeping) then Sleeping
+//! if loneliness > 60 and near seat then Socializing
+//! ```
+//!
+//! Conditions compare need meters, mood levels, `time` (HH:MM) or `hour` with a
+//! number; test the current activity (`activity is [not] <activity>`); look for
+//! objects by catalogue tag (`near <tag>`: within 10 m of the character,
+//! `<tag> has <tag>`: an object of the second tag sits on or in an object of the
+//! first); and combine with `and`, `or`, `not` and parentheses.
+//!
+//! Rules are tried in order and the first match wins; if none matches, the time
+//! block covering the current time of day applies.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::calendar::{GameTime, MINUTES_PER_DAY, MINUTES_PER_HOUR};
+use crate::catalogue::Catalogue;
+use crate::game::{distance, Activity, Entity, Player};
+
+/// Distance (m) within which `near <tag>` matches.
+const NEAR_DISTANCE: f32 = 10.0;
+/// Distance (m) within which an object counts as held by another for `<tag> has <tag>`.
+const HOLD_DISTANCE: f32 = 1.5;
+
+/// Time of day, serialized as "HH:MM".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ClockTime {
+    /// Minutes since midnight (0..1440)
+    pub minute_of_day: i64,
+}
+
+impl TryFrom<String> for ClockTime {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl std::str::FromStr for ClockTime {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid time '{text}', expected HH:MM");
+        let (hours, minutes) = text.split_once(':').ok_or_else(invalid)?;
+        let hours: i64 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i64 = minutes.parse().map_err(|_| invalid())?;
+        // 24:00 is allowed as the end of the day
+        if !(0..=24).contains(&hours)
+            || !(0..MINUTES_PER_HOUR).contains(&minutes)
+            || (hours == 24 && minutes != 0)
+        {
+            return Err(invalid());
+        }
+        Ok(Self {
+            minute_of_day: hours * MINUTES_PER_HOUR + minutes,
+        })
+    }
+}
+
+impl From<ClockTime> for String {
+    fn from(time: ClockTime) -> Self {
+        time.to_string()
+    }
+}
+
+impl fmt::Display for ClockTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}",
+            self.minute_of_day / MINUTES_PER_HOUR,
+            self.minute_of_day % MINUTES_PER_HOUR
+        )
+    }
+}
+
+/// A daily block of time reserved for an activity.
+///
+/// A block whose end is before its start wraps past midnight (e.g. 22:30-06:30).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TimeBlock {
+    pub start: ClockTime,
+    pub end: ClockTime,
+    pub activity: Activity,
+}
+
+impl TimeBlock {
+    /// Whether the block covers a minute of the day.
+    pub fn contains(&self, minute_of_day: i64) -> bool {
+        let (start, end) = (self.start.minute_of_day, self.end.minute_of_day);
+        if start <= end {
+            (start..end).contains(&minute_of_day)
+        } else {
+            minute_of_day >= start || minute_of_day < end
+        }
+    }
+}
+
+impl fmt::Display for TimeBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{} {:?}", self.start, self.end, self.activity)
+    }
+}
+
+/// A character's schedule as uploaded by the player.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Schedule {
+    /// Time-of-day blocks; they must not overlap
+    #[serde(default)]
+    pub blocks: Vec<TimeBlock>,
+    /// If-then rules in the rule language, tried in order
+    #[serde(default)]
+    pub rules: Vec<String>,
+}
+
+/// A validated schedule with parsed rules, ready to evaluate.
+#[derive(Clone, Debug)]
+pub struct CompiledSchedule {
+    pub source: Schedule,
+    rules: Vec<Rule>,
+}
+
+/// Comparison operator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl CmpOp {
+    fn parse(token: &str) -> Option<Self> {
+        Some(match token {
+            "<" => CmpOp::Lt,
+            "<=" => CmpOp::Le,
+            ">" => CmpOp::Gt,
+            ">=" => CmpOp::Ge,
+            "==" | "=" => CmpOp::Eq,
+            "!=" => CmpOp::Ne,
+            _ => return None,
+        })
+    }
+
+    fn apply(self, left: f32, right: f32) -> bool {
+        match self {
+            CmpOp::Lt => left < right,
+            CmpOp::Le => left <= right,
+            CmpOp::Gt => left > right,
+            CmpOp::Ge => left >= right,
+            CmpOp::Eq => left == right,
+            CmpOp::Ne => left != right,
+        }
+    }
+}
+
+/// Quantity a condition can compare.
+#[derive(Clone, Debug, PartialEq)]
+enum Variable {
+    Need(String),
+    Mood(String),
+    /// Minutes since midnight
+    Time,
+    Hour,
+}
+
+/// Parsed rule condition.
+#[derive(Clone, Debug, PartialEq)]
+enum Condition {
+    Compare {
+        variable: Variable,
+        op: CmpOp,
+        value: f32,
+    },
+    ActivityIs(Activity),
+    Near(String),
+    Has {
+        holder: String,
+        item: String,
+    },
+    Not(Box<Condition>),
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+}
+
+/// Parsed if-then rule.
+#[derive(Clone, Debug, PartialEq)]
+struct Rule {
+    condition: Condition,
+    activity: Activity,
+}
+
+/// A schedule in force for a character.
+#[derive(Clone, Debug)]
+pub struct ActiveSchedule {
+    pub schedule: CompiledSchedule,
+    /// Activity the schedule last asked for and the character accepted; the
+    /// schedule only issues a new request when its choice changes, so a manual
+    /// `SetActivity` sticks until the next block or rule takes over
+    pub last_choice: Option<Activity>,
+    /// Whether this is the needs autopilot standing in for an absent player
+    pub autopilot: bool,
+}
+
+/// What a character's schedule asks for at a given moment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Choice {
+    pub activity: Activity,
+    /// Human-readable source of the choice (the matching rule or block)
+    pub reason: String,
+}
+
+/// Everything a rule can look at.
+pub struct Context<'a> {
+    pub player: &'a Player,
+    pub time: GameTime,
+    pub entities: &'a HashMap<String, Entity>,
+    pub catalogue: &'a Catalogue,
+}
+
+impl Context<'_> {
+    fn tagged<'b>(&'b self, tag: &'b str) -> impl Iterator<Item = &'b Entity> + 'b {
+        self.entities.values().filter(move |entity| {
+            self.catalogue
+                .get(entity.entity_type.as_str())
+                .is_some_and(|t| t.has_tag(tag))
+        })
+    }
+}
+
+impl Condition {
+    fn eval(&self, ctx: &Context) -> bool {
+        match self {
+            Condition::Compare {
+                variable,
+                op,
+                value,
+            } => {
+                let left = match variable {
+                    Variable::Need(name) => ctx.player.needs.get(name).unwrap_or(0.0),
+                    Variable::Mood(name) => ctx.player.mood.get(name).unwrap_or(0.0),
+                    Variable::Time => ctx.time.minute_of_day() as f32,
+                    Variable::Hour => ctx.time.hour() as f32,
+                };
+                op.apply(left, *value)
+            }
+            Condition::ActivityIs(activity) => ctx.player.activity == *activity,
+            Condition::Near(tag) => ctx
+                .tagged(tag)
+                .any(|e| distance(&e.position, &ctx.player.position) <= NEAR_DISTANCE),
+            Condition::Has { holder, item } => ctx.tagged(holder).any(|holder| {
+                ctx.tagged(item).any(|item| {
+                    item.id != holder.id
+                        && distance(&item.position, &holder.position) <= HOLD_DISTANCE
+                })
+            }),
+            Condition::Not(inner) => !inner.eval(ctx),
+            Condition::And(all) => all.iter().all(|c| c.eval(ctx)),
+            Condition::Or(any) => any.iter().any(|c| c.eval(ctx)),
+        }
+    }
+}
+
+/// Parse an activity name, either snake_case ("watching_tv") or PascalCase ("WatchingTv").
+pub fn parse_activity(name: &str) -> Result<Activity, String> {
+    let mut snake = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && !snake.is_empty() && !snake.ends_with('_') {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    serde_json::from_value(serde_json::Value::String(snake))
+        .map_err(|_| format!("unknown activity '{name}'"))
+}
+
+/// Split rule text into words, numbers, times, operators and parentheses.
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' || c == ')' {
+            tokens.push(c.to_string());
+            chars.next();
+        } else if "<>=!".contains(c) {
+            let mut op = String::new();
+            while let Some(&c) = chars.peek().filter(|c| "<>=!".contains(**c)) {
+                op.push(c);
+                chars.next();
+            }
+            tokens.push(op);
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars
+                .peek()
+                .filter(|c| !c.is_whitespace() && !"()<>=!".contains(**c))
+            {
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(word);
+        }
+    }
+    tokens
+}
+
+/// Recursive-descent parser over rule tokens.
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Result<String, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| "unexpected end of rule".to_string())?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        if self.peek().is_some_and(|t| t.eq_ignore_ascii_case(word)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, word: &str) -> Result<(), String> {
+        if self.keyword(word) {
+            Ok(())
+        } else {
+            Err(match self.peek() {
+                Some(token) => format!("expected '{word}', found '{token}'"),
+                None => format!("expected '{word}' at end of rule"),
+            })
+        }
+    }
+
+    fn rule(&mut self) -> Result<Rule, String> {
+        self.expect("if")?;
+        let condition = self.or_expr()?;
+        self.expect("then")?;
+        let activity = parse_activity(&self.next()?)?;
+        if let Some(token) = self.peek() {
+            return Err(format!("unexpected '{token}' after activity"));
+        }
+        Ok(Rule {
+            condition,
+            activity,
+        })
+    }
+
+    fn or_expr(&mut self) -> Result<Condition, String> {
+        let mut terms = vec![self.and_expr()?];
+        while self.keyword("or") {
+            terms.push(self.and_expr()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Condition::Or(terms)
+        })
+    }
+
+    fn and_expr(&mut self) -> Result<Condition, String> {
+        let mut terms = vec![self.unary()?];
+        while self.keyword("and") {
+            terms.push(self.unary()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Condition::And(terms)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Condition, String> {
+        if self.keyword("not") {
+            return Ok(Condition::Not(Box::new(self.unary()?)));
+        }
+        if self.keyword("(") {
+            let inner = self.or_expr()?;
+            self.expect(")")?;
+            return Ok(inner);
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Condition, String> {
+        let subject = self.next()?.to_ascii_lowercase();
+        if subject == "near" {
+            return Ok(Condition::Near(self.next()?));
+        }
+        if subject == "activity" {
+            self.expect("is")?;
+            let negated = self.keyword("not");
+            let activity = Condition::ActivityIs(parse_activity(&self.next()?)?);
+            return Ok(if negated {
+                Condition::Not(Box::new(activity))
+            } else {
+                activity
+            });
+        }
+        if self.keyword("has") {
+            return Ok(Condition::Has {
+                holder: subject,
+                item: self.next()?,
+            });
+        }
+
+        let op_token = self.next()?;
+        let op = CmpOp::parse(&op_token)
+            .ok_or_else(|| format!("expected comparison after '{subject}', found '{op_token}'"))?;
+        let value_token = self.next()?;
+        let variable = match subject.as_str() {
+            "time" => Variable::Time,
+            "hour" => Variable::Hour,
+            name if crate::needs::Needs::default().get(name).is_some() => {
+                Variable::Need(name.to_string())
+            }
+            name if crate::mood::Mood::default().get(name).is_some() => {
+                Variable::Mood(name.to_string())
+            }
+            name => return Err(format!("unknown quantity '{name}'")),
+        };
+        let value = if variable == Variable::Time {
+            value_token.parse::<ClockTime>()?.minute_of_day as f32
+        } else {
+            value_token
+                .parse::<f32>()
+                .map_err(|_| format!("expected a number, found '{value_token}'"))?
+        };
+        Ok(Condition::Compare {
+            variable,
+            op,
+            value,
+        })
+    }
+}
+
+fn parse_rule(text: &str) -> Result<Rule, String> {
+    Parser {
+        tokens: tokenize(text),
+        pos: 0,
+    }
+    .rule()
+}
+
+impl Schedule {
+    /// Schedule that looks after the needs of a character whose owner is offline
+    /// and who has no schedule of their own.
+    pub fn autopilot() -> Self {
+        Self {
+            blocks: Vec::new(),
+            rules: [
+                "if sleepiness >= 75 or exhaustion >= 75 then Sleeping",
+                "if hunger >= 60 or thirst >= 60 then Eating",
+                "if dirtiness >= 70 then Bathing",
+                "if boredom >= 60 then Reading",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+
+    /// Validate the schedule and parse its rules.
+    ///
+    /// Returns every problem found (bad rules, overlapping blocks) so the player
+    /// can fix them all at once.
+    pub fn compile(&self) -> Result<CompiledSchedule, Vec<String>> {
+        let mut errors = Vec::new();
+        let mut rules = Vec::new();
+        for (i, text) in self.rules.iter().enumerate() {
+            match parse_rule(text) {
+                Ok(rule) => rules.push(rule),
+                Err(e) => errors.push(format!("rule {}: {e}", i + 1)),
+            }
+        }
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            if block.start == block.end {
+                errors.push(format!("block {}: {block} is empty", i + 1));
+            }
+        }
+        for minute in 0..MINUTES_PER_DAY {
+            let covering: Vec<_> = (0..self.blocks.len())
+                .filter(|&i| self.blocks[i].contains(minute))
+                .collect();
+            if covering.len() > 1 {
+                errors.push(format!(
+                    "blocks {} overlap at {}",
+                    covering
+                        .iter()
+                        .map(|i| (i + 1).to_string())
+                        .collect::<Vec<_>>()
+                        .join(" and "),
+                    ClockTime {
+                        minute_of_day: minute
+                    }
+                ));
+                break;
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(CompiledSchedule {
+                source: self.clone(),
+                rules,
+            })
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl CompiledSchedule {
+    /// Choose the activity the schedule asks for right now, if any.
+    pub fn choose(&self, ctx: &Context) -> Option<Choice> {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if rule.condition.eval(ctx) {
+                return Some(Choice {
+                    activity: rule.activity.clone(),
+                    reason: format!("rule {}: {}", i + 1, self.source.rules[i]),
+                });
+            }
+        }
+        let minute = ctx.time.minute_of_day();
+        self.source
+            .blocks
+            .iter()
+            .find(|block| block.contains(minute))
+            .map(|block| Choice {
+                activity: block.activity.clone(),
+                reason: format!("block {block}"),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{EntityType, Position, Rotation};
+    use crate::needs::Needs;
+
+    fn player(needs: Needs) -> Player {
+        Player {
+            id: "p1".to_string(),
+            username: "p1".to_string(),
+            position: Position {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            rotation: 0.0,
+            is_moving: false,
+            activity: Activity::Idle,
+            needs,
+            mood: Default::default(),
+            timed_activity: None,
+            offline: false,
+            npc: false,
+            guest: false,
+            health: Default::default(),
+            genome: Default::default(),
+            appearance: Default::default(),
+            exposure: Default::default(),
+        }
+    }
+
+    #[test]
+    fn parses_and_evaluates_rules() {
+        let schedule = Schedule {
+            blocks: vec![TimeBlock {
+                start: "22:30".parse().unwrap(),
+                end: "06:30".parse().unwrap(),
+                activity: Activity::Sleeping,
+            }],
+            rules: vec!["if hunger > 70 and kitchen has cookware then Cooking".to_string()],
+        }
+        .compile()
+        .expect("valid schedule");
+
+        let mut catalogue = Catalogue::core();
+        for (name, tags) in [("stove", "kitchen"), ("cooking_pot", "cookware")] {
+            let mut object_type = catalogue.get("ball").unwrap().clone();
+            object_type.name = name.to_string();
+            object_type.tags = vec![tags.to_string()];
+            catalogue.insert(object_type);
+        }
+        let mut entities = HashMap::new();
+        for (id, x) in [("stove", 3.0), ("cooking_pot", 3.5)] {
+            entities.insert(
+                id.to_string(),
+                Entity {
+                    id: id.to_string(),
+                    entity_type: EntityType::new(id),
+                    position: Position { x, y: 1.0, z: 0.0 },
+                    rotation: Rotation {
+                        x: 0.0,
+                        y: 0.0,
+                        z: 0.0,
+                    },
+                    owner: None,
+                    properties: Default::default(),
+                },
+            );
+        }
+
+        let hungry = player(Needs {
+            hunger: 80.0,
+            ..Needs::default()
+        });
+        let ctx = Context {
+            player: &hungry,
+            time: GameTime::from_minutes(23 * 60),
+            entities: &entities,
+            catalogue: &catalogue,
+        };
+        assert_eq!(schedule.choose(&ctx).unwrap().activity, Activity::Cooking);
+
+        let fed = player(Needs::default());
+        let ctx = Context {
+            player: &fed,
+            time: GameTime::from_minutes(5 * 60),
+            ..ctx
+        };
+        assert_eq!(schedule.choose(&ctx).unwrap().activity, Activity::Sleeping);
+        let ctx = Context {
+            time: GameTime::from_minutes(12 * 60),
+            ..ctx
+        };
+        assert_eq!(schedule.choose(&ctx), None);
+    }
+
+    #[test]
+    fn autopilot_schedule_is_valid() {
+        assert!(Schedule::autopilot().compile().is_ok());
+    }
+
+    #[test]
+    fn reports_invalid_rules_and_overlaps() {
+        let schedule = Schedule {
+            blocks: vec![
+                TimeBlock {
+                    start: "07:00".parse().unwrap(),
+                    end: "09:00".parse().unwrap(),
+                    activity: Activity::Eating,
+                },
+                TimeBlock {
+                    start: "08:30".parse().unwrap(),
+                    end: "17:00".parse().unwrap(),
+                    activity: Activity::Working,
+                },
+            ],
+            rules: vec![
+                "if (sleepiness >= 90 or time >= 23:00) and activity is not sleeping then Sleeping"
+                    .to_string(),
+                "if happiness > 3 then Reading".to_string(),
+                "hunger > 70 then Eating".to_string(),
+            ],
+        };
+        let errors = schedule.compile().unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                "rule 2: unknown quantity 'happiness'",
+                "rule 3: expected 'if', found 'hunger'",
+                "blocks 1 and 2 overlap at 08:30",
+            ]
+        );
+    }
+}
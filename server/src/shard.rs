@@ -0,0 +1,1079 @@
+//! Sharding module.
+//!
+//! Zones can be spread over several server processes. The zone directory lists
+//! which process (by base URL) hosts each zone, the home zone first. Each process
+//! hosts the zones the directory gives to its own URL (`SHARD_URL`, by default
+//! `http://127.0.0.1:$PORT`); without a directory one process hosts every zone.
+//!
+//! Handoff: a character moving to a zone on another process leaves its zone here
+//! and is posted to that process (`POST /shard/arrivals`), which puts it down and
+//! returns a one-time ticket. The ticket only becomes valid once this process
+//! confirms the transfer (`POST /shard/transfers/confirm`); if the arrival or the
+//! confirmation fails, this process rolls the transfer back instead
+//! (`POST /shard/transfers/rollback`) and puts the character back down here. All
+//! three requests carry a transfer ID, so repeating them is harmless, and an
+//! arrival never confirmed is dropped after [`PENDING_LIFETIME`]. The client is
+//! told to connect to the other process (`ZoneHandoff`) and presents the ticket
+//! there (`ResumeHandoff`) to control the character again. Selecting a character
+//! that lives on another process works the same way, with a ticket from
+//! `POST /shard/claims`. Processes only accept each other's requests with the
+//! shared `SHARD_SECRET`.
+//!
+//! Tickets carry the account logged in on the connection, so a client can go on
+//! selecting its characters after a handoff. Accounts live in the home zone: a
+//! process that does not host it sends logins, character creation and account
+//! lookups to the process that does (`POST /shard/accounts`).
+//!
+//! Clients connected through the gateway never see a handoff: the gateway follows
+//! it for them.
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::account::{Account, Character, CharacterSummary};
+use crate::game::{GameState, Traveller};
+use crate::genetics::AppearanceChoice;
+use crate::zone::{Zone, Zones};
+use crate::AppState;
+
+/// Header carrying the shared secret on requests between processes.
+const SECRET_HEADER: &str = "x-shard-secret";
+/// How long a handoff ticket can be redeemed.
+const TICKET_LIFETIME: Duration = Duration::from_secs(60);
+/// Longest wait for another process to answer a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+/// Tries of a request that is safe to repeat, when no answer comes.
+const REQUEST_ATTEMPTS: u32 = 2;
+/// How long an arrival waits for its transfer to be confirmed.
+pub const PENDING_LIFETIME: Duration = Duration::from_secs(30);
+/// How long a settled transfer is remembered, to answer repeated requests.
+const SETTLED_LIFETIME: Duration = Duration::from_secs(600);
+
+/// Where a zone is hosted.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DirectoryEntry {
+    pub zone: String,
+    /// Base URL of the hosting process (e.g. "http://127.0.0.1:8081")
+    pub server: String,
+}
+
+/// Which process hosts each zone; the first entry is the home zone.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(transparent)]
+pub struct Directory {
+    entries: Vec<DirectoryEntry>,
+}
+
+impl Directory {
+    pub fn new(entries: Vec<DirectoryEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Load a zone directory from a JSON file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Load the zone directory from `ZONE_DIRECTORY_PATH`, if set.
+    pub fn load_configured() -> Option<Self> {
+        let path = PathBuf::from(std::env::var("ZONE_DIRECTORY_PATH").ok()?);
+        match Self::load(&path) {
+            Ok(directory) => {
+                tracing::info!(
+                    "Loaded zone directory with {} zones from {}",
+                    directory.entries.len(),
+                    path.display()
+                );
+                Some(directory)
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to load zone directory from {}: {e}; hosting every zone",
+                    path.display()
+                );
+                None
+            }
+        }
+    }
+
+    /// Where the home zone is hosted.
+    pub fn home(&self) -> Option<&DirectoryEntry> {
+        self.entries.first()
+    }
+
+    /// Base URL of the process hosting a zone.
+    pub fn server_of(&self, zone: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.zone == zone)
+            .map(|entry| entry.server.as_str())
+    }
+
+    pub fn zones(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|entry| entry.zone.clone())
+            .collect()
+    }
+
+    /// Base URLs of every process, each once.
+    pub fn servers(&self) -> Vec<&str> {
+        let mut servers: Vec<&str> = Vec::new();
+        for entry in &self.entries {
+            if !servers.contains(&entry.server.as_str()) {
+                servers.push(&entry.server);
+            }
+        }
+        servers
+    }
+}
+
+/// Where a client has to go to control its character again.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Handoff {
+    pub zone: String,
+    /// Base URL of the process now hosting the character
+    pub server: String,
+    /// One-time ticket to present with `ResumeHandoff`
+    pub ticket: String,
+}
+
+/// What a ticket gives control of.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Control {
+    pub player_id: String,
+    /// Account logged in on the connection that handed the character off
+    pub account_id: Option<String>,
+}
+
+/// A character handed to a zone on this process.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Arrival {
+    /// Identifies the transfer in retries, its confirmation and rollback
+    pub transfer_id: String,
+    pub zone: String,
+    pub traveller: Traveller,
+    pub account_id: Option<String>,
+}
+
+/// Request for a ticket to control a character hosted on this process.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Claim {
+    pub player_id: String,
+    pub account_id: Option<String>,
+}
+
+/// Account operation, carried out where the home zone is hosted.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum AccountRequest {
+    Login {
+        account_id: String,
+        username: String,
+    },
+    Get {
+        account_id: String,
+    },
+    CreateCharacter {
+        account_id: String,
+        name: String,
+        appearance: AppearanceChoice,
+        parents: Option<[String; 2]>,
+    },
+}
+
+impl AccountRequest {
+    /// Carry out the request on the home zone, answering with the account.
+    fn apply(self, game: &mut GameState) -> Result<Account, String> {
+        match self {
+            AccountRequest::Login {
+                account_id,
+                username,
+            } => game.accounts.login(&account_id, &username).cloned(),
+            AccountRequest::Get { account_id } => game
+                .accounts
+                .get(&account_id)
+                .cloned()
+                .ok_or_else(|| "not logged in".to_string()),
+            AccountRequest::CreateCharacter {
+                account_id,
+                name,
+                appearance,
+                parents,
+            } => {
+                game.create_character(&account_id, &name, &appearance, parents)?;
+                game.accounts
+                    .get(&account_id)
+                    .cloned()
+                    .ok_or_else(|| "not logged in".to_string())
+            }
+        }
+    }
+}
+
+/// Why a request to another process failed.
+#[derive(Debug)]
+enum PeerError {
+    /// The other process answered with a refusal and its reason
+    Refused(StatusCode, String),
+    /// No answer was had (not configured, unreachable, timed out, bad reply)
+    Failed(String),
+}
+
+impl std::fmt::Display for PeerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PeerError::Refused(status, reason) => write!(f, "answered {status}: {reason}"),
+            PeerError::Failed(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<reqwest::Error> for PeerError {
+    fn from(e: reqwest::Error) -> Self {
+        PeerError::Failed(e.to_string())
+    }
+}
+
+/// State of a transfer to this process.
+#[derive(Clone, Debug, PartialEq)]
+enum Inbound {
+    /// The character is here, but nobody can control it until confirmed
+    Pending {
+        zone: String,
+        control: Control,
+        ticket: String,
+    },
+    /// The ticket is valid
+    Confirmed { ticket: String },
+    /// The character was taken out again, or never put down
+    RolledBack,
+}
+
+/// This process's part in the zone directory and the tickets it has issued.
+pub struct Shard {
+    directory: Option<Directory>,
+    /// Base URL of this process, as listed in the directory
+    own_url: String,
+    secret: Option<String>,
+    client: reqwest::Client,
+    /// What each unredeemed ticket gives control of, and its expiry
+    tickets: Mutex<HashMap<String, (Control, Instant)>>,
+    /// Transfers to this process by transfer ID, and when they were last updated
+    transfers: Mutex<HashMap<String, (Inbound, Instant)>>,
+}
+
+impl Shard {
+    pub fn new(directory: Option<Directory>, own_url: String, secret: Option<String>) -> Self {
+        Self {
+            directory,
+            own_url,
+            secret,
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("HTTP client configuration is valid"),
+            tickets: Mutex::new(HashMap::new()),
+            transfers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Shard from `ZONE_DIRECTORY_PATH`, `SHARD_URL` and `SHARD_SECRET`.
+    pub fn configured(port: u16) -> Self {
+        let own_url =
+            std::env::var("SHARD_URL").unwrap_or_else(|_| format!("http://127.0.0.1:{port}"));
+        let secret = std::env::var("SHARD_SECRET").ok().filter(|s| !s.is_empty());
+        let directory = Directory::load_configured();
+        if directory.is_some() && secret.is_none() {
+            tracing::warn!("SHARD_SECRET is not set; characters cannot move between servers");
+        }
+        Self::new(directory, own_url, secret)
+    }
+
+    /// Whether this process hosts a zone (every zone without a directory).
+    pub fn hosts(&self, zone: &str) -> bool {
+        match &self.directory {
+            Some(directory) => directory.server_of(zone) == Some(self.own_url.as_str()),
+            None => true,
+        }
+    }
+
+    /// Base URL of the other process hosting a zone.
+    pub fn remote_server(&self, zone: &str) -> Option<&str> {
+        let server = self.directory.as_ref()?.server_of(zone)?;
+        (server != self.own_url).then_some(server)
+    }
+
+    /// Base URL of the process hosting the home zone.
+    fn home_server(&self) -> Option<&str> {
+        Some(self.directory.as_ref()?.home()?.server.as_str())
+    }
+
+    /// Every zone in the directory.
+    pub fn zones(&self) -> Vec<String> {
+        self.directory
+            .as_ref()
+            .map(Directory::zones)
+            .unwrap_or_default()
+    }
+
+    /// Other processes, each once.
+    fn peers(&self) -> Vec<&str> {
+        let Some(directory) = &self.directory else {
+            return Vec::new();
+        };
+        directory
+            .servers()
+            .into_iter()
+            .filter(|server| *server != self.own_url)
+            .collect()
+    }
+
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(secret) = &self.secret else {
+            return false;
+        };
+        headers
+            .get(SECRET_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value == secret)
+    }
+
+    /// Issue a one-time ticket to control a character on this process.
+    pub fn issue_ticket(&self, control: Control) -> String {
+        let ticket = uuid::Uuid::new_v4().to_string();
+        let mut tickets = self.tickets.lock().expect("ticket lock poisoned");
+        let now = Instant::now();
+        tickets.retain(|_, (_, expires)| *expires > now);
+        tickets.insert(ticket.clone(), (control, now + TICKET_LIFETIME));
+        ticket
+    }
+
+    /// Redeem a ticket, returning the character and account it is for.
+    pub fn redeem(&self, ticket: &str) -> Option<Control> {
+        let mut tickets = self.tickets.lock().expect("ticket lock poisoned");
+        let (control, expires) = tickets.remove(ticket)?;
+        (expires > Instant::now()).then_some(control)
+    }
+
+    /// Record an arrival, answering with its ticket and whether the character
+    /// still has to be put down; None if the transfer was rolled back.
+    fn receive(&self, transfer_id: &str, zone: &str, control: Control) -> Option<(String, bool)> {
+        let mut transfers = self.transfers.lock().expect("transfer lock poisoned");
+        match transfers.get(transfer_id) {
+            Some((Inbound::Pending { ticket, .. } | Inbound::Confirmed { ticket }, _)) => {
+                Some((ticket.clone(), false))
+            }
+            Some((Inbound::RolledBack, _)) => None,
+            None => {
+                let ticket = uuid::Uuid::new_v4().to_string();
+                let pending = Inbound::Pending {
+                    zone: zone.to_string(),
+                    control,
+                    ticket: ticket.clone(),
+                };
+                transfers.insert(transfer_id.to_string(), (pending, Instant::now()));
+                Some((ticket, true))
+            }
+        }
+    }
+
+    /// Make the ticket of a pending arrival valid.
+    fn confirm(&self, transfer_id: &str) -> Result<(), Refusal> {
+        let mut transfers = self.transfers.lock().expect("transfer lock poisoned");
+        let Some((inbound, since)) = transfers.get_mut(transfer_id) else {
+            return Err((StatusCode::NOT_FOUND, "unknown transfer".to_string()));
+        };
+        match inbound.clone() {
+            Inbound::Pending {
+                control, ticket, ..
+            } => {
+                let mut tickets = self.tickets.lock().expect("ticket lock poisoned");
+                tickets.insert(ticket.clone(), (control, Instant::now() + TICKET_LIFETIME));
+                *inbound = Inbound::Confirmed { ticket };
+                *since = Instant::now();
+                Ok(())
+            }
+            Inbound::Confirmed { .. } => Ok(()),
+            Inbound::RolledBack => {
+                Err((StatusCode::CONFLICT, "transfer was rolled back".to_string()))
+            }
+        }
+    }
+
+    /// Call off a transfer, answering with the zone and character to take out
+    /// again if it was put down. A transfer not seen yet is refused when it comes.
+    fn roll_back(&self, transfer_id: &str) -> Result<Option<(String, String)>, Refusal> {
+        let mut transfers = self.transfers.lock().expect("transfer lock poisoned");
+        let previous = transfers.insert(
+            transfer_id.to_string(),
+            (Inbound::RolledBack, Instant::now()),
+        );
+        match previous {
+            Some((Inbound::Pending { zone, control, .. }, _)) => {
+                Ok(Some((zone, control.player_id)))
+            }
+            Some((confirmed @ Inbound::Confirmed { .. }, since)) => {
+                transfers.insert(transfer_id.to_string(), (confirmed, since));
+                Err((StatusCode::CONFLICT, "transfer was confirmed".to_string()))
+            }
+            Some((Inbound::RolledBack, _)) | None => Ok(None),
+        }
+    }
+
+    fn rolled_back(&self, transfer_id: &str) -> bool {
+        let transfers = self.transfers.lock().expect("transfer lock poisoned");
+        matches!(transfers.get(transfer_id), Some((Inbound::RolledBack, _)))
+    }
+
+    /// Whether a character is here on a transfer that is not confirmed yet.
+    fn arriving(&self, player_id: &str) -> bool {
+        let transfers = self.transfers.lock().expect("transfer lock poisoned");
+        transfers.values().any(|(inbound, _)| {
+            matches!(inbound, Inbound::Pending { control, .. } if control.player_id == player_id)
+        })
+    }
+
+    /// Roll back arrivals left unconfirmed for [`PENDING_LIFETIME`] and forget
+    /// settled transfers, answering with the zones and characters to take out.
+    fn expire_transfers(&self, now: Instant) -> Vec<(String, String)> {
+        let mut transfers = self.transfers.lock().expect("transfer lock poisoned");
+        transfers.retain(|_, (inbound, since)| {
+            !matches!(inbound, Inbound::Confirmed { .. } | Inbound::RolledBack)
+                || now.duration_since(*since) < SETTLED_LIFETIME
+        });
+        let mut expired = Vec::new();
+        for (inbound, since) in transfers.values_mut() {
+            if let Inbound::Pending { zone, control, .. } = inbound {
+                if now.duration_since(*since) >= PENDING_LIFETIME {
+                    expired.push((zone.clone(), control.player_id.clone()));
+                    *inbound = Inbound::RolledBack;
+                    *since = now;
+                }
+            }
+        }
+        expired
+    }
+
+    /// POST a request to another process and read its JSON reply.
+    async fn post<T: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        server: &str,
+        path: &str,
+        body: &T,
+    ) -> Result<R, PeerError> {
+        let secret = self
+            .secret
+            .as_deref()
+            .ok_or_else(|| PeerError::Failed("SHARD_SECRET is not set".to_string()))?;
+        let response = self
+            .client
+            .post(format!("{}{path}", server.trim_end_matches('/')))
+            .header(SECRET_HEADER, secret)
+            .json(body)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let reason = response.text().await.unwrap_or_default();
+            return Err(PeerError::Refused(status, reason));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// POST a request that is safe to repeat, trying again if no answer comes.
+    async fn post_retried<T: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        server: &str,
+        path: &str,
+        body: &T,
+    ) -> Result<R, PeerError> {
+        let mut attempt = 1;
+        loop {
+            match self.post(server, path, body).await {
+                Err(PeerError::Failed(e)) if attempt < REQUEST_ATTEMPTS => {
+                    tracing::debug!("No answer from {server}{path}, trying again: {e}");
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Move a character from a zone here to a zone on another process.
+    ///
+    /// If the other process does not take the character, it stays in its zone
+    /// here (back at the spawn point). Should the other process not answer the
+    /// rollback either, it drops the character once its arrival expires.
+    pub async fn transfer(
+        &self,
+        zone: &Zone,
+        player_id: &str,
+        account_id: Option<&str>,
+        to: &str,
+    ) -> Result<Handoff, String> {
+        let server = self
+            .remote_server(to)
+            .ok_or_else(|| format!("no zone {to}"))?
+            .to_string();
+        let traveller = zone.game.write().await.depart(player_id)?;
+        let arrival = Arrival {
+            transfer_id: uuid::Uuid::new_v4().to_string(),
+            zone: to.to_string(),
+            traveller,
+            account_id: account_id.map(str::to_string),
+        };
+        let transfer_id = &arrival.transfer_id;
+        let handoff = |ticket| Handoff {
+            zone: to.to_string(),
+            server: server.clone(),
+            ticket,
+        };
+
+        let (ticket, e) = match self
+            .post_retried::<_, String>(&server, "/shard/arrivals", &arrival)
+            .await
+        {
+            Ok(ticket) => match self
+                .post_retried::<_, ()>(&server, "/shard/transfers/confirm", transfer_id)
+                .await
+            {
+                Ok(()) => {
+                    tracing::info!("Player {player_id} handed off to zone {to} on {server}");
+                    return Ok(handoff(ticket));
+                }
+                Err(e) => (Some(ticket), e),
+            },
+            Err(e) => (None, e),
+        };
+        tracing::error!("Failed to hand off {player_id} to {server}: {e}");
+        let rollback = self
+            .post_retried::<_, ()>(&server, "/shard/transfers/rollback", transfer_id)
+            .await;
+        match (rollback, ticket) {
+            (Ok(()), _) => {}
+            // The confirmation got through, only its answer was lost
+            (Err(PeerError::Refused(StatusCode::CONFLICT, _)), Some(ticket)) => {
+                tracing::info!("Player {player_id} handed off to zone {to} on {server}");
+                return Ok(handoff(ticket));
+            }
+            (Err(e), _) => tracing::error!(
+                "Failed to roll back handoff of {player_id} to {server}: {e}; \
+                 it drops the character in {}s",
+                PENDING_LIFETIME.as_secs()
+            ),
+        }
+        zone.game.write().await.arrive(arrival.traveller);
+        Err(format!("zone {to} is unreachable"))
+    }
+
+    /// Ask the other processes for a ticket to control a character they host.
+    ///
+    /// Refused with the reason of the process hosting the character, if one does.
+    pub async fn claim(
+        &self,
+        player_id: &str,
+        account_id: Option<&str>,
+    ) -> Result<Handoff, String> {
+        let claim = Claim {
+            player_id: player_id.to_string(),
+            account_id: account_id.map(str::to_string),
+        };
+        let mut refusal = None;
+        for server in self.peers() {
+            match self
+                .post::<_, Handoff>(server, "/shard/claims", &claim)
+                .await
+            {
+                Ok(handoff) => return Ok(handoff),
+                Err(PeerError::Refused(StatusCode::CONFLICT, reason)) => refusal = Some(reason),
+                Err(e) => tracing::debug!("No claim on {player_id} from {server}: {e}"),
+            }
+        }
+        Err(refusal.unwrap_or_else(|| format!("no character {player_id}")))
+    }
+
+    /// Ask the other processes about characters they host, for selection.
+    pub async fn summaries(&self, characters: &[Character]) -> Vec<CharacterSummary> {
+        let mut found = Vec::new();
+        if characters.is_empty() {
+            return found;
+        }
+        for server in self.peers() {
+            match self
+                .post::<_, Vec<CharacterSummary>>(server, "/shard/summaries", &characters)
+                .await
+            {
+                Ok(summaries) => found.extend(summaries),
+                Err(e) => tracing::warn!("Failed to list characters on {server}: {e}"),
+            }
+        }
+        found
+    }
+
+    /// Carry out an account request, here if the home zone is hosted here and on
+    /// the process hosting it otherwise.
+    pub async fn account(&self, zones: &Zones, request: AccountRequest) -> Result<Account, String> {
+        if let Some(home) = zones.home() {
+            return request.apply(&mut *home.game.write().await);
+        }
+        let server = self
+            .home_server()
+            .ok_or_else(|| "no home zone".to_string())?;
+        self.post(server, "/shard/accounts", &request)
+            .await
+            .map_err(|e| match e {
+                PeerError::Refused(_, reason) => reason,
+                PeerError::Failed(e) => {
+                    tracing::error!("Account request to {server} failed: {e}");
+                    "accounts are unreachable".to_string()
+                }
+            })
+    }
+}
+
+/// Routes other processes call during handoffs.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/shard/arrivals", post(accept_arrival))
+        .route("/shard/transfers/confirm", post(confirm_transfer))
+        .route("/shard/transfers/rollback", post(roll_back_transfer))
+        .route("/shard/claims", post(accept_claim))
+        .route("/shard/summaries", post(list_summaries))
+        .route("/shard/accounts", post(accept_account_request))
+}
+
+type Refusal = (StatusCode, String);
+
+fn check_secret(state: &AppState, headers: &HeaderMap) -> Result<(), Refusal> {
+    if state.shard.authorized(headers) {
+        Ok(())
+    } else {
+        Err((StatusCode::FORBIDDEN, "wrong shard secret".to_string()))
+    }
+}
+
+/// Put down a character handed off by another process, to be confirmed;
+/// answers with its ticket.
+async fn accept_arrival(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(arrival): Json<Arrival>,
+) -> Result<Json<String>, Refusal> {
+    check_secret(&state, &headers)?;
+    let zone = state.zones.get(&arrival.zone).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("zone {} is not hosted here", arrival.zone),
+        )
+    })?;
+    let control = Control {
+        player_id: arrival.traveller.player.id.clone(),
+        account_id: arrival.account_id,
+    };
+    let player_id = control.player_id.clone();
+    let rolled_back = || (StatusCode::CONFLICT, "transfer was rolled back".to_string());
+    let (ticket, new) = state
+        .shard
+        .receive(&arrival.transfer_id, &zone.name, control)
+        .ok_or_else(rolled_back)?;
+    if new {
+        let mut game = zone.game.write().await;
+        game.arrive(arrival.traveller);
+        // Nobody controls the character until the ticket is redeemed
+        game.disconnect_player(&player_id);
+    }
+    // The rollback may have come while the character was put down
+    if state.shard.rolled_back(&arrival.transfer_id) {
+        let _ = zone.game.write().await.depart(&player_id);
+        return Err(rolled_back());
+    }
+    Ok(Json(ticket))
+}
+
+/// Make the ticket of an arrival valid.
+async fn confirm_transfer(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(transfer_id): Json<String>,
+) -> Result<Json<()>, Refusal> {
+    check_secret(&state, &headers)?;
+    state.shard.confirm(&transfer_id).map(Json)
+}
+
+/// Take out a character whose transfer the sending process called off.
+async fn roll_back_transfer(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(transfer_id): Json<String>,
+) -> Result<Json<()>, Refusal> {
+    check_secret(&state, &headers)?;
+    if let Some((zone, player_id)) = state.shard.roll_back(&transfer_id)? {
+        depart_arrival(&state, &zone, &player_id).await;
+    }
+    Ok(Json(()))
+}
+
+/// Take out characters whose arrival was not confirmed in time.
+pub async fn expire_arrivals(state: &AppState) {
+    for (zone, player_id) in state.shard.expire_transfers(Instant::now()) {
+        tracing::warn!("Handoff of {player_id} to zone {zone} was never confirmed");
+        depart_arrival(state, &zone, &player_id).await;
+    }
+}
+
+/// Take a character that arrived by a called-off transfer out of its zone.
+async fn depart_arrival(state: &AppState, zone: &str, player_id: &str) {
+    if let Some(zone) = state.zones.get(zone) {
+        // Not there yet if the arrival is still being put down; it checks again
+        let _ = zone.game.write().await.depart(player_id);
+    }
+}
+
+/// Issue a ticket to control a character hosted here.
+async fn accept_claim(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(claim): Json<Claim>,
+) -> Result<Json<Handoff>, Refusal> {
+    check_secret(&state, &headers)?;
+    if state.shard.arriving(&claim.player_id) {
+        return Err((StatusCode::CONFLICT, "still arriving".to_string()));
+    }
+    let zone = state
+        .zones
+        .locate(&claim.player_id)
+        .await
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "not hosted here".to_string()))?;
+    // Another connection controls it
+    let controlled = zone
+        .game
+        .read()
+        .await
+        .players
+        .get(&claim.player_id)
+        .is_some_and(|player| !player.offline);
+    if controlled {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("{} is already controlled", claim.player_id),
+        ));
+    }
+    Ok(Json(Handoff {
+        zone: zone.name.clone(),
+        server: state.shard.own_url.clone(),
+        ticket: state.shard.issue_ticket(Control {
+            player_id: claim.player_id,
+            account_id: claim.account_id,
+        }),
+    }))
+}
+
+/// Summaries of the given characters that are hosted here.
+async fn list_summaries(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(characters): Json<Vec<Character>>,
+) -> Result<Json<Vec<CharacterSummary>>, Refusal> {
+    check_secret(&state, &headers)?;
+    let mut summaries = Vec::new();
+    for zone in state.zones.all() {
+        let game = zone.game.read().await;
+        summaries.extend(
+            characters
+                .iter()
+                .filter_map(|character| game.character_summary(character, &zone.name)),
+        );
+    }
+    Ok(Json(summaries))
+}
+
+/// Carry out an account request for a process without the home zone.
+async fn accept_account_request(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<AccountRequest>,
+) -> Result<Json<Account>, Refusal> {
+    check_secret(&state, &headers)?;
+    let home = state.zones.home().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            "the home zone is not hosted here".to_string(),
+        )
+    })?;
+    let result = request.apply(&mut *home.game.write().await);
+    result
+        .map(Json)
+        .map_err(|reason| (StatusCode::UNPROCESSABLE_ENTITY, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Player;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    fn entry(zone: &str, port: u16) -> DirectoryEntry {
+        DirectoryEntry {
+            zone: zone.to_string(),
+            server: format!("http://127.0.0.1:{port}"),
+        }
+    }
+
+    /// The process on port 8082 of a three-zone directory, home on 8081.
+    fn shard() -> Shard {
+        let directory = Directory::new(vec![
+            entry("arena", 8081),
+            entry("house", 8082),
+            entry("outdoor", 8082),
+        ]);
+        Shard::new(
+            Some(directory),
+            "http://127.0.0.1:8082".to_string(),
+            Some("secret".to_string()),
+        )
+    }
+
+    fn control(player_id: &str) -> Control {
+        Control {
+            player_id: player_id.to_string(),
+            account_id: Some("acc".to_string()),
+        }
+    }
+
+    #[test]
+    fn directory_lists_the_home_zone_first_and_each_server_once() {
+        let directory = Directory::new(vec![entry("arena", 8081), entry("house", 8082)]);
+        assert_eq!(directory.home().unwrap().zone, "arena");
+        assert_eq!(directory.server_of("house"), Some("http://127.0.0.1:8082"));
+        assert_eq!(
+            Directory::new(vec![entry("house", 8082), entry("outdoor", 8082)])
+                .servers()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn zones_are_hosted_where_the_directory_says() {
+        let shard = shard();
+        assert!(shard.hosts("house") && shard.hosts("outdoor"));
+        assert!(!shard.hosts("arena") && !shard.hosts("moon"));
+        assert_eq!(shard.remote_server("arena"), Some("http://127.0.0.1:8081"));
+        assert_eq!(shard.remote_server("house"), None);
+        assert_eq!(shard.home_server(), Some("http://127.0.0.1:8081"));
+        assert_eq!(shard.peers(), vec!["http://127.0.0.1:8081"]);
+        assert!(Shard::new(None, String::new(), None).hosts("anything"));
+    }
+
+    #[test]
+    fn tickets_are_redeemed_once() {
+        let shard = shard();
+        let ticket = shard.issue_ticket(control("ada"));
+        assert_eq!(shard.redeem(&ticket), Some(control("ada")));
+        assert_eq!(shard.redeem(&ticket), None);
+        assert_eq!(shard.redeem("made-up"), None);
+    }
+
+    #[test]
+    fn requests_need_the_shared_secret() {
+        let mut headers = HeaderMap::new();
+        assert!(!shard().authorized(&headers));
+        headers.insert(SECRET_HEADER, "wrong".parse().unwrap());
+        assert!(!shard().authorized(&headers));
+        headers.insert(SECRET_HEADER, "secret".parse().unwrap());
+        assert!(shard().authorized(&headers));
+        assert!(!Shard::new(None, String::new(), None).authorized(&headers));
+    }
+
+    #[test]
+    fn arrivals_are_controlled_only_once_confirmed() {
+        let shard = shard();
+        let (ticket, new) = shard.receive("t1", "house", control("ada")).unwrap();
+        assert!(new);
+        // A repeated arrival is not put down twice
+        assert_eq!(
+            shard.receive("t1", "house", control("ada")),
+            Some((ticket.clone(), false))
+        );
+        assert!(shard.arriving("ada"));
+        assert_eq!(shard.redeem(&ticket), None);
+
+        assert!(shard.confirm("t1").is_ok());
+        assert!(shard.confirm("t1").is_ok());
+        assert!(!shard.arriving("ada"));
+        assert_eq!(shard.roll_back("t1").unwrap_err().0, StatusCode::CONFLICT);
+        assert_eq!(shard.redeem(&ticket), Some(control("ada")));
+        assert_eq!(shard.confirm("t2").unwrap_err().0, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn rolled_back_arrivals_are_taken_out_and_refused() {
+        let shard = shard();
+        shard.receive("t1", "house", control("ada")).unwrap();
+        assert_eq!(
+            shard.roll_back("t1").unwrap(),
+            Some(("house".to_string(), "ada".to_string()))
+        );
+        assert_eq!(shard.roll_back("t1").unwrap(), None);
+        assert_eq!(shard.confirm("t1").unwrap_err().0, StatusCode::CONFLICT);
+
+        // A rollback overtaking its arrival keeps the arrival out
+        assert_eq!(shard.roll_back("t2").unwrap(), None);
+        assert!(shard.rolled_back("t2"));
+        assert_eq!(shard.receive("t2", "house", control("bea")), None);
+    }
+
+    #[test]
+    fn unconfirmed_arrivals_expire() {
+        let shard = shard();
+        shard.receive("t1", "house", control("ada")).unwrap();
+        shard.receive("t2", "outdoor", control("bea")).unwrap();
+        shard.confirm("t2").unwrap();
+        assert!(shard.expire_transfers(Instant::now()).is_empty());
+
+        let later = Instant::now() + PENDING_LIFETIME;
+        assert_eq!(
+            shard.expire_transfers(later),
+            vec![("house".to_string(), "ada".to_string())]
+        );
+        assert!(shard.rolled_back("t1"));
+        // Settled transfers are forgotten in the end
+        shard.expire_transfers(later + SETTLED_LIFETIME);
+        assert!(shard.transfers.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn account_requests_answer_with_the_account() {
+        let mut game = GameState::new();
+        let get = || AccountRequest::Get {
+            account_id: "acc".to_string(),
+        };
+        assert!(get().apply(&mut game).is_err());
+
+        let login = AccountRequest::Login {
+            account_id: "acc".to_string(),
+            username: "Ada".to_string(),
+        };
+        assert_eq!(login.apply(&mut game).unwrap().username, "Ada");
+        let create = AccountRequest::CreateCharacter {
+            account_id: "acc".to_string(),
+            name: "Bea".to_string(),
+            appearance: AppearanceChoice::default(),
+            parents: None,
+        };
+        let account = create.apply(&mut game).unwrap();
+        assert_eq!(account.characters[0].name, "Bea");
+        assert_eq!(get().apply(&mut game).unwrap(), account);
+    }
+
+    /// The arena on a process that is not listening, the house on `house`.
+    fn arena_and_house(house: &str) -> Directory {
+        Directory::new(vec![
+            DirectoryEntry {
+                zone: "arena".to_string(),
+                server: "http://127.0.0.1:9".to_string(),
+            },
+            DirectoryEntry {
+                zone: "house".to_string(),
+                server: house.to_string(),
+            },
+        ])
+    }
+
+    /// Serve the handoff routes for a process hosting the given zones.
+    async fn serve_zones(zones: Vec<Zone>) -> (String, AppState) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let directory = arena_and_house(&url);
+        let state = AppState {
+            zones: Arc::new(Zones::new(zones, "arena")),
+            shard: Arc::new(Shard::new(
+                Some(directory),
+                url.clone(),
+                Some("secret".to_string()),
+            )),
+            db: None,
+            clients: Arc::new(RwLock::new(HashMap::new())),
+        };
+        let app = routes().with_state(state.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, state)
+    }
+
+    /// A process hosting the arena, with Ada in it, next to the house on `house`.
+    async fn arena_with_ada(house: &str) -> (Shard, Zone) {
+        let directory = arena_and_house(house);
+        let shard = Shard::new(
+            Some(directory),
+            "http://127.0.0.1:9".to_string(),
+            Some("secret".to_string()),
+        );
+        let zone = Zone::new("arena", GameState::new());
+        let player: Player = serde_json::from_value(serde_json::json!({
+            "id": "ada",
+            "username": "Ada",
+            "position": { "x": 3.0, "y": 1.0, "z": 3.0 },
+            "rotation": 0.0
+        }))
+        .unwrap();
+        zone.game.write().await.add_player(player);
+        (shard, zone)
+    }
+
+    #[tokio::test]
+    async fn transfers_hand_the_character_to_the_other_process() {
+        let (url, house) = serve_zones(vec![Zone::new("house", GameState::new())]).await;
+        let (shard, arena) = arena_with_ada(&url).await;
+
+        let handoff = shard
+            .transfer(&arena, "ada", Some("acc"), "house")
+            .await
+            .unwrap();
+        assert_eq!(
+            (handoff.zone.as_str(), handoff.server.as_str()),
+            ("house", url.as_str())
+        );
+        assert!(!arena.game.read().await.players.contains_key("ada"));
+        let zone = house.zones.locate("ada").await.unwrap();
+        assert!(zone.game.read().await.players["ada"].offline);
+        assert_eq!(house.shard.redeem(&handoff.ticket), Some(control("ada")));
+    }
+
+    #[tokio::test]
+    async fn failed_transfers_leave_the_character_here() {
+        // The house is not hosted where the directory says
+        let (url, _) = serve_zones(vec![Zone::new("yard", GameState::new())]).await;
+        let (shard, arena) = arena_with_ada(&url).await;
+        assert!(shard.transfer(&arena, "ada", None, "house").await.is_err());
+        assert!(arena.game.read().await.players.contains_key("ada"));
+
+        // Nothing answers at all
+        let (shard, arena) = arena_with_ada("http://127.0.0.1:9").await;
+        assert!(shard.transfer(&arena, "ada", None, "house").await.is_err());
+        assert!(arena.game.read().await.players.contains_key("ada"));
+    }
+
+    #[tokio::test]
+    async fn claims_on_a_controlled_character_are_refused() {
+        let (_, house) = arena_with_ada("http://127.0.0.1:9").await;
+        let (url, _) = serve_zones(vec![house.clone()]).await;
+        let (shard, _) = arena_with_ada(&url).await;
+
+        let refusal = shard.claim("ada", Some("acc")).await.unwrap_err();
+        assert_eq!(refusal, "ada is already controlled");
+
+        house.game.write().await.disconnect_player("ada");
+        let handoff = shard.claim("ada", Some("acc")).await.unwrap();
+        assert_eq!(handoff.zone, "arena");
+    }
+}
@@ -5,17 +5,39 @@
 use axum::body::Bytes;
 use axum::extract::ws::{Message, WebSocket};
 use futures_util::{SinkExt, StreamExt};
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 
+use crate::account::{Account, Character, CharacterSummary};
+use crate::economy::shop_items;
+use crate::game::{Activity, GameState, Player};
+use crate::genetics::HAIR_STYLES;
+use crate::interaction;
 use crate::messages::GameMessage;
+use crate::mood::Decision;
+use crate::shard::{AccountRequest, Handoff};
+use crate::zone::Zone;
 use crate::AppState;
 
+/// Squared distance (m²) between requested and corrected positions above which
+/// a `PositionCorrection` is sent back to the client (1 cm).
+const POSITION_CORRECTION_EPSILON_SQ: f32 = 0.01 * 0.01;
+
+/// Reason given when a command names a character the connection does not control.
+const NOT_CONTROLLED: &str = "can only command the character you control";
+
 /// Handle a WebSocket connection from a game client.
 ///
 /// Sets up bidirectional communication:
-/// - Receives messages from client (Join, Move, SetActivity)
-/// - Sends messages to client (WorldState, TimeSync, PlayerJoin/Leave)
-/// - Subscribes to broadcast channel for world state updates
+/// - Receives messages from client (Join, Login, CreateCharacter, SelectCharacter, TransferZone, ResumeHandoff, Move, MoveIntent, SetActivity, Interact, Cook,
+///   UploadSchedule, ValidateSchedule, GetSchedule, PickUp, DropItem, PlaceItem, StoreItem, TakeItem,
+///   GetInventory, GetShop, Purchase, GetAccount, Socialize, GetRelationships, Chat,
+///   JoinChatGroup, LeaveChatGroup, BlockPlayer)
+/// - Sends messages to client (CharacterList, WorldState, TimeSync, Recipes, PositionCorrection, CommandRefused,
+///   ActivityDelayed, ScheduleValidation, ScheduleInfo, WelcomeBack, InventoryContents,
+///   ShopCatalogue, OrderPlaced, Account, DeliveryArrived, PropertiesChanged, SocialInteraction,
+///   Relationships, ChatReceived, ChatGroups, BlockedPlayers, Environment, ZoneEntered,
+///   ZoneHandoff, PlayerJoin/Leave)
+/// - Subscribes to the broadcast channel of the character's zone for world state updates
 /// - Sends periodic ping messages to keep connection alive
 ///
 /// # Arguments
@@ -26,14 +48,22 @@ pub async fn handle_websocket(socket: WebSocket, state: AppState) {
     let (mut sender, mut receiver) = socket.split();
     // Track player ID for cleanup on disconnect
     let mut player_id: Option<String> = None;
+    // Account logged in on this connection, if any
+    let mut account_id: Option<String> = None;
 
     // Create channel for sending messages directly to this client
     // Capacity: 32 messages
     let (tx, mut rx) = mpsc::channel::<String>(32);
 
-    // Subscribe to broadcast channel for world state updates
+    // Zone the connection's character is in; until a character is chosen, the home
+    // zone (or the first zone hosted here without it)
+    let mut zone = state.zones.entry().clone();
+
+    // Subscribe to the zone's broadcast channel for world state updates
     // This client will receive periodic world state broadcasts
-    let mut broadcast_rx = state.broadcast_tx.subscribe();
+    let mut broadcast_rx = zone.broadcast_tx.subscribe();
+    // Subscription to the new zone's channel after a zone change
+    let (zone_tx, mut zone_rx) = mpsc::channel::<broadcast::Receiver<String>>(1);
 
     // Send initial time sync message to client
     // Game time is derived from Unix timestamp (1 real second = 1 game minute)
@@ -48,7 +78,7 @@ pub async fn handle_websocket(socket: WebSocket, state: AppState) {
     // Spawn task to handle outgoing messages to the client
     // Handles:
     // - Direct messages via channel (tx/rx)
-    // - Broadcast world state updates
+    // - Broadcast world state updates of the character's zone
     // - Periodic ping messages (every 30 seconds) to keep connection alive
     let sender_task = tokio::spawn(async move {
         let mut ping_interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
@@ -76,6 +106,10 @@ pub async fn handle_websocket(socket: WebSocket, state: AppState) {
                         Err(_) => break,
                     }
                 }
+                // The character moved to another zone
+                Some(rx) = zone_rx.recv() => {
+                    broadcast_rx = rx;
+                }
                 // Periodic ping to keep connection alive
                 _ = ping_interval.tick() => {
                     if sender.send(Message::Ping(Bytes::new())).await.is_err() {
@@ -94,28 +128,258 @@ pub async fn handle_websocket(socket: WebSocket, state: AppState) {
                     // Parse JSON message
                     let message: Result<GameMessage, _> = serde_json::from_str(&text);
                     match message {
-                        // Player joining the game
+                        // Player joining the game with a character of their own
                         Ok(GameMessage::Join { player }) => {
-                            player_id = Some(player.id.clone());
-                            let mut game = state.game.write().await;
-                            game.add_player(player.clone());
-
-                            // Send complete world state to the newly joined player
-                            let all_players = game.get_all_players();
-                            let all_entities = game.get_all_entities();
-                            let player_count = all_players.len();
-                            let world_state = GameMessage::WorldState {
-                                players: all_players,
-                                entities: all_entities,
-                            };
-                            let player_id_ref = &player.id;
-                            tracing::debug!(
-                                "Player {player_id_ref} joined, total players: {player_count}"
+                            // A character of its own is found wherever it is
+                            let target = state.zones.locate(&player.id).await.cloned();
+                            // Characters of an account are only controlled after logging
+                            // in, and non-player characters never; a guest is only
+                            // resumed while no other connection controls it
+                            let taken = match &target {
+                                Some(target) => {
+                                    let game = target.game.read().await;
+                                    match game.players.get(&player.id) {
+                                        Some(existing) if !existing.guest => {
+                                            Some("character belongs to an account")
+                                        }
+                                        Some(existing)
+                                            if !existing.offline
+                                                && player_id.as_deref() != Some(&player.id) =>
+                                        {
+                                            Some("character is already controlled")
+                                        }
+                                        _ => None,
+                                    }
+                                }
+                                None => match state.zones.home() {
+                                    Some(home) => home
+                                        .game
+                                        .read()
+                                        .await
+                                        .accounts
+                                        .has_character(&player.id)
+                                        .then_some("character belongs to an account"),
+                                    None => None,
+                                },
+                            };
+                            // New characters start in the home zone
+                            let target = match target.or_else(|| state.zones.home().cloned()) {
+                                Some(target) if taken.is_none() => target,
+                                target => {
+                                    let reason = match target {
+                                        Some(_) => taken.unwrap_or_default(),
+                                        None => "new characters join in the home zone",
+                                    };
+                                    let refusal = GameMessage::CommandRefused {
+                                        player_id: player.id.clone(),
+                                        command: "join".to_string(),
+                                        reason: reason.to_string(),
+                                    };
+                                    send_message(&tx, &refusal).await;
+                                    continue;
+                                }
+                            };
+                            let previous = player_id.replace(player.id.clone());
+                            switch_zone(&zone_tx, &mut zone, target).await;
+                            enter_world(&state, &tx, &zone, *player, previous).await;
+                        }
+                        // Account login, answered with the characters to choose from.
+                        // No credential is checked, so it is only served on
+                        // development servers (`DEV_LOGIN`)
+                        Ok(GameMessage::Login {
+                            account_id: id,
+                            username,
+                        }) => {
+                            if !dev_login() {
+                                let refusal = GameMessage::CommandRefused {
+                                    player_id: id,
+                                    command: "login".to_string(),
+                                    reason: "login is disabled on this server".to_string(),
+                                };
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let request = AccountRequest::Login {
+                                account_id: id.clone(),
+                                username,
+                            };
+                            let reply = match state.shard.account(&state.zones, request).await {
+                                Ok(account) => GameMessage::CharacterList {
+                                    characters: character_list(&state, &account).await,
+                                    hair_styles: hair_styles(),
+                                    account_id: id.clone(),
+                                },
+                                Err(reason) => GameMessage::CommandRefused {
+                                    player_id: id.clone(),
+                                    command: "login".to_string(),
+                                    reason,
+                                },
+                            };
+                            if matches!(reply, GameMessage::CharacterList { .. }) {
+                                account_id = Some(id);
+                            }
+                            send_message(&tx, &reply).await;
+                        }
+                        Ok(GameMessage::CreateCharacter {
+                            name,
+                            appearance,
+                            parents,
+                        }) => {
+                            let Some(id) = account_id.clone() else {
+                                continue;
+                            };
+                            // New characters start in the home zone
+                            let request = AccountRequest::CreateCharacter {
+                                account_id: id.clone(),
+                                name,
+                                appearance,
+                                parents,
+                            };
+                            let reply = match state.shard.account(&state.zones, request).await {
+                                Ok(account) => GameMessage::CharacterList {
+                                    characters: character_list(&state, &account).await,
+                                    hair_styles: hair_styles(),
+                                    account_id: id,
+                                },
+                                Err(reason) => GameMessage::CommandRefused {
+                                    player_id: id,
+                                    command: "create_character".to_string(),
+                                    reason,
+                                },
+                            };
+                            send_message(&tx, &reply).await;
+                        }
+                        // Take control of one of the account's characters; the one
+                        // controlled before goes back to its schedule
+                        Ok(GameMessage::SelectCharacter { character_id }) => {
+                            let Some(id) = account_id.clone() else {
+                                continue;
+                            };
+                            let request = AccountRequest::Get {
+                                account_id: id.clone(),
+                            };
+                            let owned = state.shard.account(&state.zones, request).await.is_ok_and(
+                                |account| account.characters.iter().any(|c| c.id == character_id),
                             );
-                            if let Ok(world_json) = serde_json::to_string(&world_state) {
-                                let _ = tx.send(world_json).await;
+                            let target = match owned {
+                                true => state.zones.locate(&character_id).await.cloned(),
+                                false => None,
+                            };
+                            let character = match &target {
+                                Some(target) => {
+                                    target.game.read().await.players.get(&character_id).cloned()
+                                }
+                                None => None,
+                            };
+                            // A character on another server is reached by handoff
+                            let refusal = if owned && target.is_none() {
+                                match state.shard.claim(&character_id, Some(&id)).await {
+                                    Ok(handoff) => {
+                                        release_character(&state, player_id.take()).await;
+                                        send_handoff(&tx, character_id, handoff).await;
+                                        continue;
+                                    }
+                                    Err(reason) => Some(reason),
+                                }
+                            } else {
+                                match &character {
+                                    None => Some(format!("no character {character_id}")),
+                                    // Another connection controls it
+                                    Some(character)
+                                        if !character.offline
+                                            && player_id.as_deref() != Some(&character.id) =>
+                                    {
+                                        Some(format!("{character_id} is already controlled"))
+                                    }
+                                    Some(_) => None,
+                                }
+                            };
+                            let (Some(target), Some(character), None) =
+                                (target, character, &refusal)
+                            else {
+                                let refusal = GameMessage::CommandRefused {
+                                    player_id: id,
+                                    command: "select_character".to_string(),
+                                    reason: refusal.unwrap_or_default(),
+                                };
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            };
+                            let previous = player_id.replace(character.id.clone());
+                            switch_zone(&zone_tx, &mut zone, target).await;
+                            enter_world(&state, &tx, &zone, character, previous).await;
+                        }
+                        // Move the controlled character to another zone
+                        Ok(GameMessage::TransferZone {
+                            player_id: pid,
+                            zone: to,
+                        }) => {
+                            let result = if player_id.as_deref() != Some(pid.as_str()) {
+                                Err("can only move the character you control".to_string())
+                            } else if state.zones.get(&to).is_none()
+                                && state.shard.remote_server(&to).is_some()
+                            {
+                                // The zone is on another server: hand the character over
+                                match state
+                                    .shard
+                                    .transfer(&zone, &pid, account_id.as_deref(), &to)
+                                    .await
+                                {
+                                    Ok(handoff) => {
+                                        release_character(&state, player_id.take()).await;
+                                        send_handoff(&tx, pid, handoff).await;
+                                        continue;
+                                    }
+                                    Err(reason) => Err(reason),
+                                }
+                            } else {
+                                state.zones.transfer(&pid, &zone.name, &to).await.cloned()
+                            };
+                            let target = match result {
+                                Ok(target) => target,
+                                Err(reason) => {
+                                    let refusal = GameMessage::CommandRefused {
+                                        player_id: pid,
+                                        command: "transfer_zone".to_string(),
+                                        reason,
+                                    };
+                                    send_message(&tx, &refusal).await;
+                                    continue;
+                                }
+                            };
+                            let character = target.game.read().await.players.get(&pid).cloned();
+                            switch_zone(&zone_tx, &mut zone, target).await;
+                            if let Some(character) = character {
+                                enter_world(&state, &tx, &zone, character, None).await;
                             }
                         }
+                        // Take control of a character handed off from another server
+                        Ok(GameMessage::ResumeHandoff { ticket }) => {
+                            let Some(control) = state.shard.redeem(&ticket) else {
+                                let refusal = GameMessage::CommandRefused {
+                                    player_id: String::new(),
+                                    command: "resume_handoff".to_string(),
+                                    reason: "unknown or expired ticket".to_string(),
+                                };
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            };
+                            let character_id = control.player_id;
+                            let Some(target) = state.zones.locate(&character_id).await.cloned()
+                            else {
+                                continue;
+                            };
+                            let character =
+                                target.game.read().await.players.get(&character_id).cloned();
+                            let Some(character) = character else {
+                                continue;
+                            };
+                            // The connection is logged in as it was before the handoff
+                            account_id = control.account_id;
+                            let previous = player_id.replace(character_id);
+                            switch_zone(&zone_tx, &mut zone, target).await;
+                            enter_world(&state, &tx, &zone, character, previous).await;
+                        }
                         // Player movement update
                         Ok(GameMessage::Move {
                             player_id: pid,
@@ -123,42 +387,586 @@ pub async fn handle_websocket(socket: WebSocket, state: AppState) {
                             rotation,
                             is_moving,
                         }) => {
-                            let mut game = state.game.write().await;
-                            game.update_player_position(
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "move")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let mut game = zone.game.write().await;
+                            if let Decision::Refuse { reason } = game.check_movement(&pid) {
+                                // Keep the character where it is and tell the client why
+                                let current = game.players.get(&pid).map(|p| p.position.clone());
+                                drop(game);
+                                let refusal = GameMessage::CommandRefused {
+                                    player_id: pid.clone(),
+                                    command: "move".to_string(),
+                                    reason,
+                                };
+                                send_message(&tx, &refusal).await;
+                                if let Some(position) = current {
+                                    let correction = GameMessage::PositionCorrection {
+                                        player_id: pid,
+                                        position,
+                                    };
+                                    send_message(&tx, &correction).await;
+                                }
+                                continue;
+                            }
+                            let corrected = game.update_player_position(
                                 &pid,
                                 position.clone(),
                                 rotation,
                                 is_moving,
                             );
+                            drop(game);
+
+                            // Tell the owning client where its character actually ended up
+                            // when the server moved it somewhere else (wall, slope, fall)
+                            if let Some(corrected) = corrected {
+                                let dx = corrected.x - position.x;
+                                let dy = corrected.y - position.y;
+                                let dz = corrected.z - position.z;
+                                if dx * dx + dy * dy + dz * dz > POSITION_CORRECTION_EPSILON_SQ {
+                                    let correction = GameMessage::PositionCorrection {
+                                        player_id: pid.clone(),
+                                        position: corrected,
+                                    };
+                                    send_message(&tx, &correction).await;
+                                }
+                            }
 
                             // Note: Movement updates are broadcast via periodic WorldState messages
                             // (10 FPS) rather than individual Move messages for efficiency
-                            let move_msg = GameMessage::Move {
-                                player_id: pid.clone(),
-                                position: position.clone(),
+                        }
+                        // Continuous walking intent
+                        Ok(GameMessage::MoveIntent {
+                            player_id: pid,
+                            direction,
+                            speed,
+                            rotation,
+                        }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "move")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let mut game = zone.game.write().await;
+                            // The player takes over from any route the server was walking
+                            game.stop_walking(&pid);
+                            let walking = direction.x != 0.0 || direction.z != 0.0;
+                            if walking {
+                                if let Decision::Refuse { reason } = game.check_movement(&pid) {
+                                    game.set_player_move_intent(&pid, (0.0, 0.0), speed, rotation);
+                                    drop(game);
+                                    let refusal = GameMessage::CommandRefused {
+                                        player_id: pid,
+                                        command: "move".to_string(),
+                                        reason,
+                                    };
+                                    send_message(&tx, &refusal).await;
+                                    continue;
+                                }
+                            }
+                            game.set_player_move_intent(
+                                &pid,
+                                (direction.x, direction.z),
+                                speed,
                                 rotation,
-                                is_moving,
-                            };
-                            let _move_json = serde_json::to_string(&move_msg).unwrap();
-                            // In a real implementation, broadcast to all connected clients
+                            );
                         }
-                        // Player activity change
+                        // Player activity change, subject to the character's mood
                         Ok(GameMessage::SetActivity {
                             player_id: pid,
                             activity,
                         }) => {
-                            let mut game = state.game.write().await;
-                            game.update_player_activity(&pid, activity.clone());
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "set_activity")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let mut game = zone.game.write().await;
+                            let decision = game.request_activity(&pid, activity.clone());
+                            let events = game.take_activity_events();
+                            drop(game);
+
+                            for event in events {
+                                if let Ok(json) = serde_json::to_string(&GameMessage::from(event)) {
+                                    let _ = zone.broadcast_tx.send(json);
+                                }
+                            }
+
+                            match decision {
+                                Decision::Allow => {
+                                    tracing::debug!("Player {pid} activity changed");
+                                }
+                                Decision::Delay { minutes, reason } => {
+                                    let delayed = GameMessage::ActivityDelayed {
+                                        player_id: pid,
+                                        activity,
+                                        delay_minutes: minutes,
+                                        reason,
+                                    };
+                                    send_message(&tx, &delayed).await;
+                                }
+                                Decision::Refuse { reason } => {
+                                    let refusal = GameMessage::CommandRefused {
+                                        player_id: pid,
+                                        command: "set_activity".to_string(),
+                                        reason,
+                                    };
+                                    send_message(&tx, &refusal).await;
+                                }
+                            }
+                        }
+                        // Object interaction, validated for distance and object state
+                        Ok(GameMessage::Interact {
+                            player_id: pid,
+                            entity_id,
+                            verb,
+                        }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "interact")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let mut game = zone.game.write().await;
+                            let decision = game.interact(&pid, &entity_id, &verb);
+                            let events = game.take_activity_events();
+                            let inventory = game.inventory(&pid);
+                            drop(game);
+
+                            for event in events {
+                                if let Ok(json) = serde_json::to_string(&GameMessage::from(event)) {
+                                    let _ = zone.broadcast_tx.send(json);
+                                }
+                            }
+
+                            match decision {
+                                Decision::Allow => {
+                                    if let Some(inventory) = inventory.filter(|_| verb == "pick_up")
+                                    {
+                                        let contents = GameMessage::InventoryContents {
+                                            owner_id: pid,
+                                            inventory,
+                                        };
+                                        send_message(&tx, &contents).await;
+                                    }
+                                }
+                                Decision::Delay { minutes, reason } => {
+                                    let activity = interaction::verb(&verb)
+                                        .and_then(|v| v.activity().cloned())
+                                        .unwrap_or_default();
+                                    let delayed = GameMessage::ActivityDelayed {
+                                        player_id: pid,
+                                        activity,
+                                        delay_minutes: minutes,
+                                        reason,
+                                    };
+                                    send_message(&tx, &delayed).await;
+                                }
+                                Decision::Refuse { reason } => {
+                                    let refusal = GameMessage::CommandRefused {
+                                        player_id: pid,
+                                        command: "interact".to_string(),
+                                        reason,
+                                    };
+                                    send_message(&tx, &refusal).await;
+                                }
+                            }
+                        }
+                        // Cooking a recipe with ingredients from the inventory
+                        Ok(GameMessage::Cook {
+                            player_id: pid,
+                            entity_id,
+                            recipe,
+                        }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "cook")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let mut game = zone.game.write().await;
+                            let decision = game.cook(&pid, &entity_id, &recipe);
+                            let events = game.take_activity_events();
+                            let inventory = game.inventory(&pid);
+                            drop(game);
+
+                            for event in events {
+                                if let Ok(json) = serde_json::to_string(&GameMessage::from(event)) {
+                                    let _ = zone.broadcast_tx.send(json);
+                                }
+                            }
+
+                            match decision {
+                                Decision::Allow => {
+                                    if let Some(inventory) = inventory {
+                                        let contents = GameMessage::InventoryContents {
+                                            owner_id: pid,
+                                            inventory,
+                                        };
+                                        send_message(&tx, &contents).await;
+                                    }
+                                }
+                                Decision::Delay { minutes, reason } => {
+                                    let delayed = GameMessage::ActivityDelayed {
+                                        player_id: pid,
+                                        activity: Activity::Cooking,
+                                        delay_minutes: minutes,
+                                        reason,
+                                    };
+                                    send_message(&tx, &delayed).await;
+                                }
+                                Decision::Refuse { reason } => {
+                                    let refusal = GameMessage::CommandRefused {
+                                        player_id: pid,
+                                        command: "cook".to_string(),
+                                        reason,
+                                    };
+                                    send_message(&tx, &refusal).await;
+                                }
+                            }
+                        }
+                        // Schedule management
+                        Ok(GameMessage::UploadSchedule {
+                            player_id: pid,
+                            schedule,
+                        }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "upload_schedule")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let mut game = zone.game.write().await;
+                            let result = game.set_schedule(&pid, schedule);
+                            drop(game);
+                            let validation = GameMessage::ScheduleValidation {
+                                player_id: pid,
+                                accepted: result.is_ok(),
+                                errors: result.err().unwrap_or_default(),
+                            };
+                            send_message(&tx, &validation).await;
+                        }
+                        Ok(GameMessage::ValidateSchedule {
+                            player_id: pid,
+                            schedule,
+                        }) => {
+                            let validation = GameMessage::ScheduleValidation {
+                                player_id: pid,
+                                accepted: false,
+                                errors: schedule.compile().err().unwrap_or_default(),
+                            };
+                            send_message(&tx, &validation).await;
+                        }
+                        Ok(GameMessage::GetSchedule { player_id: pid }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "get_schedule")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let game = zone.game.read().await;
+                            let (schedule, choice) = game.schedule_status(&pid);
+                            drop(game);
+                            let info = GameMessage::ScheduleInfo {
+                                player_id: pid,
+                                schedule,
+                                current_activity: choice.as_ref().map(|c| c.activity.clone()),
+                                reason: choice.map(|c| c.reason),
+                            };
+                            send_message(&tx, &info).await;
+                        }
+                        // Inventory operations, validated by the game state
+                        Ok(
+                            message @ (GameMessage::PickUp { .. }
+                            | GameMessage::DropItem { .. }
+                            | GameMessage::PlaceItem { .. }
+                            | GameMessage::StoreItem { .. }
+                            | GameMessage::TakeItem { .. }),
+                        ) => {
+                            let mut game = zone.game.write().await;
+                            let Some((pid, command, result, container)) =
+                                apply_inventory_operation(&mut game, message, player_id.as_deref())
+                            else {
+                                continue;
+                            };
+                            let contents: Vec<_> = std::iter::once(pid.clone())
+                                .chain(container)
+                                .filter_map(|owner_id| {
+                                    let inventory = game.inventory(&owner_id)?;
+                                    Some(GameMessage::InventoryContents {
+                                        owner_id,
+                                        inventory,
+                                    })
+                                })
+                                .collect();
+                            drop(game);
 
-                            // Note: Activity changes could be broadcast, but currently
-                            // they're included in periodic WorldState messages
-                            let activity_msg = GameMessage::ActivityChanged {
-                                player_id: pid.clone(),
-                                activity,
+                            match result {
+                                Ok(()) => {
+                                    for message in &contents {
+                                        send_message(&tx, message).await;
+                                    }
+                                }
+                                Err(reason) => {
+                                    let refusal = GameMessage::CommandRefused {
+                                        player_id: pid,
+                                        command: command.to_string(),
+                                        reason,
+                                    };
+                                    send_message(&tx, &refusal).await;
+                                }
+                            }
+                        }
+                        Ok(GameMessage::GetInventory {
+                            player_id: pid,
+                            owner_id,
+                        }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "get_inventory")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let game = zone.game.read().await;
+                            let result = game.view_inventory(&pid, &owner_id);
+                            drop(game);
+                            let reply = match result {
+                                Ok(inventory) => GameMessage::InventoryContents {
+                                    owner_id,
+                                    inventory,
+                                },
+                                Err(reason) => GameMessage::CommandRefused {
+                                    player_id: pid,
+                                    command: "get_inventory".to_string(),
+                                    reason,
+                                },
                             };
-                            let _activity_json = serde_json::to_string(&activity_msg).unwrap();
-                            tracing::debug!("Player {pid} activity changed");
-                            // In a real implementation, broadcast to all connected clients
+                            send_message(&tx, &reply).await;
+                        }
+                        // Shop and money
+                        Ok(GameMessage::GetShop { player_id: pid }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "get_shop")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let game = zone.game.read().await;
+                            let shop = GameMessage::ShopCatalogue {
+                                items: shop_items(&game.catalogue),
+                                balance: game.ledger.balance(&pid).unwrap_or(0),
+                            };
+                            drop(game);
+                            send_message(&tx, &shop).await;
+                        }
+                        Ok(GameMessage::Purchase {
+                            player_id: pid,
+                            lines,
+                        }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "purchase")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let mut game = zone.game.write().await;
+                            let result = game.purchase(&pid, lines);
+                            let balance = game.ledger.balance(&pid).unwrap_or(0);
+                            drop(game);
+                            let reply = match result {
+                                Ok(order) => GameMessage::OrderPlaced { order, balance },
+                                Err(reason) => GameMessage::CommandRefused {
+                                    player_id: pid,
+                                    command: "purchase".to_string(),
+                                    reason,
+                                },
+                            };
+                            send_message(&tx, &reply).await;
+                        }
+                        // Social actions between characters close to each other
+                        Ok(GameMessage::Socialize {
+                            player_id: pid,
+                            target_id,
+                            action,
+                        }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "socialize")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let mut game = zone.game.write().await;
+                            let decision = game.socialize(&pid, &target_id, action);
+                            let relationships = game.relationships.of(&pid);
+                            drop(game);
+
+                            match decision {
+                                Decision::Refuse { reason } => {
+                                    let refusal = GameMessage::CommandRefused {
+                                        player_id: pid,
+                                        command: "socialize".to_string(),
+                                        reason,
+                                    };
+                                    send_message(&tx, &refusal).await;
+                                }
+                                _ => {
+                                    let interaction = GameMessage::SocialInteraction {
+                                        player_id: pid.clone(),
+                                        target_id,
+                                        action,
+                                    };
+                                    if let Ok(json) = serde_json::to_string(&interaction) {
+                                        let _ = zone.broadcast_tx.send(json);
+                                    }
+                                    let reply = GameMessage::Relationships {
+                                        player_id: pid,
+                                        relationships,
+                                    };
+                                    send_message(&tx, &reply).await;
+                                }
+                            }
+                        }
+                        // Chat on the say, whisper and group channels
+                        Ok(GameMessage::Chat {
+                            player_id: pid,
+                            channel,
+                            text,
+                        }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "chat")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let result = zone.game.write().await.send_chat(&pid, channel, &text);
+                            match result {
+                                Ok((message, recipients)) => {
+                                    let received = GameMessage::ChatReceived { message };
+                                    if let Ok(json) = serde_json::to_string(&received) {
+                                        let clients = state.clients.read().await;
+                                        for id in &recipients {
+                                            // Never wait on a slow client while holding the lock
+                                            if let Some(client) = clients.get(id) {
+                                                let _ = client.try_send(json.clone());
+                                            }
+                                        }
+                                    }
+                                }
+                                Err(reason) => {
+                                    let refusal = GameMessage::CommandRefused {
+                                        player_id: pid,
+                                        command: "chat".to_string(),
+                                        reason,
+                                    };
+                                    send_message(&tx, &refusal).await;
+                                }
+                            }
+                        }
+                        Ok(GameMessage::JoinChatGroup {
+                            player_id: pid,
+                            group,
+                        }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "join_chat_group")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let mut game = zone.game.write().await;
+                            let reply = match game.chat.join_group(&pid, &group) {
+                                Ok(()) => GameMessage::ChatGroups {
+                                    groups: game.chat.groups_of(&pid),
+                                    player_id: pid,
+                                },
+                                Err(reason) => GameMessage::CommandRefused {
+                                    player_id: pid,
+                                    command: "join_chat_group".to_string(),
+                                    reason,
+                                },
+                            };
+                            drop(game);
+                            send_message(&tx, &reply).await;
+                        }
+                        Ok(GameMessage::LeaveChatGroup {
+                            player_id: pid,
+                            group,
+                        }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "leave_chat_group")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let mut game = zone.game.write().await;
+                            game.chat.leave_group(&pid, &group);
+                            let reply = GameMessage::ChatGroups {
+                                groups: game.chat.groups_of(&pid),
+                                player_id: pid,
+                            };
+                            drop(game);
+                            send_message(&tx, &reply).await;
+                        }
+                        Ok(GameMessage::BlockPlayer {
+                            player_id: pid,
+                            target_id,
+                            blocked,
+                        }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "block_player")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let mut game = zone.game.write().await;
+                            game.chat.set_blocked(&pid, &target_id, blocked);
+                            let reply = GameMessage::BlockedPlayers {
+                                blocked: game.chat.blocked_by(&pid),
+                                player_id: pid,
+                            };
+                            drop(game);
+                            send_message(&tx, &reply).await;
+                        }
+                        Ok(GameMessage::GetRelationships { player_id: pid }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "get_relationships")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let relationships = zone.game.read().await.relationships.of(&pid);
+                            let reply = GameMessage::Relationships {
+                                player_id: pid,
+                                relationships,
+                            };
+                            send_message(&tx, &reply).await;
+                        }
+                        Ok(GameMessage::GetAccount { player_id: pid }) => {
+                            if let Some(refusal) =
+                                refuse_unless_controlled(&player_id, &pid, "get_account")
+                            {
+                                send_message(&tx, &refusal).await;
+                                continue;
+                            }
+                            let game = zone.game.read().await;
+                            let account = GameMessage::Account {
+                                balance: game.ledger.balance(&pid).unwrap_or(0),
+                                pending_orders: game
+                                    .orders
+                                    .pending()
+                                    .iter()
+                                    .filter(|order| order.player_id == pid)
+                                    .cloned()
+                                    .collect(),
+                                player_id: pid,
+                            };
+                            drop(game);
+                            send_message(&tx, &account).await;
                         }
                         Err(e) => {
                             tracing::error!("Failed to parse message: {:?}", e);
@@ -178,10 +986,11 @@ pub async fn handle_websocket(socket: WebSocket, state: AppState) {
             }
         }
 
-        // Clean up on disconnect: remove player from game state
+        // On disconnect the character stays in the world and keeps living
         if let Some(pid) = player_id {
-            let mut game = state.game.write().await;
-            game.remove_player(&pid);
+            state.clients.write().await.remove(&pid);
+            let mut game = zone.game.write().await;
+            game.disconnect_player(&pid);
         }
     });
 
@@ -191,3 +1000,259 @@ pub async fn handle_websocket(socket: WebSocket, state: AppState) {
         _ = rx_task => {}
     }
 }
+
+/// Whether clients may log in to any account by its ID alone (`DEV_LOGIN`).
+///
+/// Off by default: accounts have no credentials yet, so this is for development.
+fn dev_login() -> bool {
+    std::env::var("DEV_LOGIN")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Point a connection at another zone: its broadcasts from now on come from there.
+async fn switch_zone(
+    zone_tx: &mpsc::Sender<broadcast::Receiver<String>>,
+    zone: &mut Zone,
+    target: Zone,
+) {
+    if target.name != zone.name {
+        let _ = zone_tx.send(target.broadcast_tx.subscribe()).await;
+        *zone = target;
+    }
+}
+
+/// Put a connection in control of a character in a zone and send it everything a
+/// client needs to start playing: the zone, object types, recipes, terrain, the
+/// world and, for a returning player, what the character did meanwhile.
+///
+/// The character the connection controlled before, if another, is left in the
+/// world like that of a disconnected player.
+async fn enter_world(
+    state: &AppState,
+    tx: &mpsc::Sender<String>,
+    zone: &Zone,
+    player: Player,
+    previous: Option<String>,
+) {
+    if let Some(previous) = previous.filter(|id| *id != player.id) {
+        state.clients.write().await.remove(&previous);
+        if let Some(previous_zone) = state.zones.locate(&previous).await {
+            previous_zone
+                .game
+                .write()
+                .await
+                .disconnect_player(&previous);
+        }
+    }
+    let zone_msg = GameMessage::ZoneEntered {
+        player_id: player.id.clone(),
+        zone: zone.name.clone(),
+        zones: zone_names(state),
+    };
+    send_message(tx, &zone_msg).await;
+    state
+        .clients
+        .write()
+        .await
+        .insert(player.id.clone(), tx.clone());
+    let mut game = zone.game.write().await;
+    let away_summary = game.connect_player(player.clone());
+
+    // Send object types so the client can render any entity
+    let catalogue_msg = GameMessage::ObjectCatalogue {
+        types: game.catalogue.types().into_iter().cloned().collect(),
+    };
+    if let Ok(catalogue_json) = serde_json::to_string(&catalogue_msg) {
+        let _ = tx.send(catalogue_json).await;
+    }
+    let recipes_msg = GameMessage::Recipes {
+        recipes: game.recipes(),
+    };
+    send_message(tx, &recipes_msg).await;
+
+    // Send terrain before the first world state so the client
+    // can place the player on the correct ground
+    let terrain = game.level.terrain();
+    if !terrain.is_empty() {
+        let terrain_msg = GameMessage::Terrain { pieces: terrain };
+        if let Ok(terrain_json) = serde_json::to_string(&terrain_msg) {
+            let _ = tx.send(terrain_json).await;
+        }
+    }
+
+    // Light and weather, so the client can draw the sky before the world
+    let environment_msg = GameMessage::Environment {
+        environment: game.environment(),
+    };
+    send_message(tx, &environment_msg).await;
+
+    // Send complete world state to the newly joined player
+    let all_players = game.get_all_players();
+    let all_entities = game.get_all_entities();
+    let player_count = all_players.len();
+    let world_state = GameMessage::WorldState {
+        players: all_players,
+        entities: all_entities,
+    };
+    let player_id_ref = &player.id;
+    tracing::debug!("Player {player_id_ref} joined, total players: {player_count}");
+    if let Ok(world_json) = serde_json::to_string(&world_state) {
+        let _ = tx.send(world_json).await;
+    }
+
+    // Tell a returning player what their character did meanwhile
+    if let Some(summary) = away_summary {
+        let welcome = GameMessage::WelcomeBack {
+            player_id: player.id.clone(),
+            summary,
+        };
+        send_message(tx, &welcome).await;
+    }
+}
+
+/// Refusal of a command that names a character other than the one the
+/// connection controls.
+fn refuse_unless_controlled(
+    player_id: &Option<String>,
+    pid: &str,
+    command: &str,
+) -> Option<GameMessage> {
+    if player_id.as_deref() == Some(pid) {
+        return None;
+    }
+    Some(GameMessage::CommandRefused {
+        player_id: pid.to_string(),
+        command: command.to_string(),
+        reason: NOT_CONTROLLED.to_string(),
+    })
+}
+
+/// Zones a character can be moved to: those hosted here and, with a zone
+/// directory, those on other servers.
+fn zone_names(state: &AppState) -> Vec<String> {
+    let mut names = state.zones.names();
+    for name in state.shard.zones() {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Characters of an account for selection, including those on other servers.
+async fn character_list(state: &AppState, account: &Account) -> Vec<CharacterSummary> {
+    let mut list = state.zones.character_list(&account.characters).await;
+    let elsewhere: Vec<Character> = account
+        .characters
+        .iter()
+        .filter(|character| !list.iter().any(|summary| summary.id == character.id))
+        .cloned()
+        .collect();
+    list.extend(state.shard.summaries(&elsewhere).await);
+    list
+}
+
+/// Give up control of a character, leaving it in the world like that of a
+/// disconnected player if it is still here.
+async fn release_character(state: &AppState, player_id: Option<String>) {
+    let Some(player_id) = player_id else {
+        return;
+    };
+    state.clients.write().await.remove(&player_id);
+    if let Some(zone) = state.zones.locate(&player_id).await {
+        zone.game.write().await.disconnect_player(&player_id);
+    }
+}
+
+/// Tell the client (or gateway) where to resume control of a character.
+async fn send_handoff(tx: &mpsc::Sender<String>, player_id: String, handoff: Handoff) {
+    let message = GameMessage::ZoneHandoff {
+        player_id,
+        zone: handoff.zone,
+        server: handoff.server,
+        ticket: handoff.ticket,
+    };
+    send_message(tx, &message).await;
+}
+
+/// Hair styles offered at character creation.
+fn hair_styles() -> Vec<String> {
+    HAIR_STYLES.iter().map(|style| style.to_string()).collect()
+}
+
+/// Outcome of an inventory operation: player ID, command name, result, and
+/// the container involved, if any.
+type InventoryOutcome = (String, &'static str, Result<(), String>, Option<String>);
+
+/// Carry out an inventory message for the character the connection controls
+/// (`controlled`); None for other messages.
+fn apply_inventory_operation(
+    game: &mut GameState,
+    message: GameMessage,
+    controlled: Option<&str>,
+) -> Option<InventoryOutcome> {
+    let allowed = |player_id: &str| {
+        if controlled == Some(player_id) {
+            Ok(())
+        } else {
+            Err(NOT_CONTROLLED.to_string())
+        }
+    };
+    Some(match message {
+        GameMessage::PickUp {
+            player_id,
+            entity_id,
+        } => {
+            let result = allowed(&player_id).and_then(|()| game.pick_up(&player_id, &entity_id));
+            (player_id, "pick_up", result, None)
+        }
+        GameMessage::DropItem {
+            player_id,
+            slot,
+            count,
+        } => {
+            let result =
+                allowed(&player_id).and_then(|()| game.drop_items(&player_id, slot, count));
+            (player_id, "drop_item", result, None)
+        }
+        GameMessage::PlaceItem {
+            player_id,
+            slot,
+            count,
+            position,
+        } => {
+            let result = allowed(&player_id)
+                .and_then(|()| game.place_items(&player_id, slot, count, position));
+            (player_id, "place_item", result, None)
+        }
+        GameMessage::StoreItem {
+            player_id,
+            slot,
+            count,
+            container_id,
+        } => {
+            let result = allowed(&player_id)
+                .and_then(|()| game.store_items(&player_id, slot, count, &container_id));
+            (player_id, "store_item", result, Some(container_id))
+        }
+        GameMessage::TakeItem {
+            player_id,
+            container_id,
+            slot,
+            count,
+        } => {
+            let result = allowed(&player_id)
+                .and_then(|()| game.take_items(&player_id, &container_id, slot, count));
+            (player_id, "take_item", result, Some(container_id))
+        }
+        _ => return None,
+    })
+}
+
+/// Serialize a message and queue it for this client only.
+async fn send_message(tx: &mpsc::Sender<String>, message: &GameMessage) {
+    if let Ok(json) = serde_json::to_string(message) {
+        let _ = tx.send(json).await;
+    }
+}
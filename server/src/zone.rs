@@ -0,0 +1,234 @@
+//! Zones module.
+//!
+//! One server hosts several zones (e.g. the arena, the house, the outdoor area),
+//! each a world of its own: a `GameState` with its level, physics world and tick
+//! loops, and a broadcast channel for the clients whose character is there. Every
+//! character is in exactly one zone.
+//!
+//! The home zone is built from the configured level (`LEVEL_PATH`); it holds the
+//! accounts, and new characters and non-player characters start there. With a
+//! zone directory the home zone may be hosted by another server, which then
+//! answers this one's account requests (see `shard`). Further zones are listed in
+//! `ZONES_PATH` or `data/zones.json`. A zone transfer takes a character out of one
+//! zone and puts it down at the spawn point of another with its needs, mood,
+//! health, inventory, money and schedule intact.
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+
+use crate::account::{Character, CharacterSummary};
+use crate::game::GameState;
+use crate::level::{data_dir, Level};
+
+/// Zone listed in the zones file.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ZoneDef {
+    /// Zone name, as used in transfer requests
+    pub name: String,
+    /// Level file, relative to the data directory
+    pub level: PathBuf,
+}
+
+/// A world hosted by this server.
+#[derive(Clone)]
+pub struct Zone {
+    pub name: String,
+    /// Thread-safe game state containing players, entities, and physics simulation
+    pub game: Arc<RwLock<GameState>>,
+    /// Broadcast channel for the clients whose character is in the zone
+    pub broadcast_tx: broadcast::Sender<String>,
+}
+
+impl Zone {
+    pub fn new(name: impl Into<String>, game: GameState) -> Self {
+        // Channel capacity: 100 messages
+        let (broadcast_tx, _) = broadcast::channel::<String>(100);
+        Self {
+            name: name.into(),
+            game: Arc::new(RwLock::new(game)),
+            broadcast_tx,
+        }
+    }
+}
+
+/// Every zone hosted by this server.
+pub struct Zones {
+    zones: Vec<Zone>,
+    /// Name of the home zone, which may be hosted by another server
+    home: String,
+}
+
+impl Zones {
+    pub fn new(zones: Vec<Zone>, home: impl Into<String>) -> Self {
+        assert!(!zones.is_empty(), "a server hosts at least one zone");
+        Self {
+            zones,
+            home: home.into(),
+        }
+    }
+
+    /// Zone where accounts live and new characters start, if it is hosted here.
+    pub fn home(&self) -> Option<&Zone> {
+        self.get(&self.home)
+    }
+
+    /// Zone a connection starts in: the home zone, or the first zone hosted here.
+    pub fn entry(&self) -> &Zone {
+        self.home().unwrap_or(&self.zones[0])
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Zone> {
+        self.zones.iter().find(|zone| zone.name == name)
+    }
+
+    pub fn all(&self) -> &[Zone] {
+        &self.zones
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.zones.iter().map(|zone| zone.name.clone()).collect()
+    }
+
+    /// Zone a character is in.
+    pub async fn locate(&self, player_id: &str) -> Option<&Zone> {
+        for zone in &self.zones {
+            if zone.game.read().await.players.contains_key(player_id) {
+                return Some(zone);
+            }
+        }
+        None
+    }
+
+    /// Summaries of the given characters that are hosted here, for selection.
+    pub async fn character_list(&self, characters: &[Character]) -> Vec<CharacterSummary> {
+        let mut list = Vec::new();
+        for character in characters {
+            for zone in &self.zones {
+                let game = zone.game.read().await;
+                if let Some(summary) = game.character_summary(character, &zone.name) {
+                    list.push(summary);
+                    break;
+                }
+            }
+        }
+        list
+    }
+
+    /// Move a character from one zone to another, returning the zone it is in now.
+    pub async fn transfer(&self, player_id: &str, from: &str, to: &str) -> Result<&Zone, String> {
+        let target = self.get(to).ok_or_else(|| format!("no zone {to}"))?;
+        if from == to {
+            return Err(format!("already in {to}"));
+        }
+        let source = self.get(from).ok_or_else(|| format!("no zone {from}"))?;
+        let traveller = source.game.write().await.depart(player_id)?;
+        target.game.write().await.arrive(traveller);
+        tracing::info!("Player {player_id} moved from zone {from} to {to}");
+        Ok(target)
+    }
+}
+
+/// Load zone definitions from a JSON file.
+pub fn load(path: &Path) -> anyhow::Result<Vec<ZoneDef>> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+/// Load the zones listed in `ZONES_PATH` or `data/zones.json`, with their levels.
+///
+/// Only the home zone is hosted if the file is missing or invalid; zones whose
+/// level fails to load are skipped.
+pub fn load_configured() -> Vec<(String, Level)> {
+    let path = std::env::var("ZONES_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| data_dir().join("zones.json"));
+    let defs = match load(&path) {
+        Ok(defs) => defs,
+        Err(e) => {
+            tracing::warn!("Failed to load zones from {}: {e}", path.display());
+            return Vec::new();
+        }
+    };
+    defs.into_iter()
+        .filter_map(|def| {
+            let level_path = data_dir().join(&def.level);
+            match Level::load(&level_path) {
+                Ok(level) => {
+                    tracing::info!("Loaded zone {} from {}", def.name, level_path.display());
+                    Some((def.name, level))
+                }
+                Err(e) => {
+                    tracing::warn!("Skipping zone {}: {}: {e}", def.name, level_path.display());
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalogue::Catalogue;
+    use crate::game::Player;
+    use crate::relationship::SocialAction;
+
+    #[tokio::test]
+    async fn characters_keep_their_state_between_zones() {
+        let zones = Zones::new(
+            vec![
+                Zone::new("arena", GameState::new()),
+                Zone::new(
+                    "yard",
+                    GameState::with_level(Level::arena(), Catalogue::core()),
+                ),
+            ],
+            "arena",
+        );
+        let player: Player = serde_json::from_value(serde_json::json!({
+            "id": "ada",
+            "username": "Ada",
+            "position": { "x": 3.0, "y": 1.0, "z": 3.0 },
+            "rotation": 0.0
+        }))
+        .unwrap();
+        let (balance, relationship) = {
+            let mut game = zones.home().unwrap().game.write().await;
+            game.add_player(player);
+            game.players.get_mut("ada").unwrap().needs.hunger = 42.0;
+            let relationship = game
+                .relationships
+                .apply("ada", "bruno", SocialAction::Chat, 0)
+                .unwrap();
+            let balance = game
+                .ledger
+                .pay("ada", 100, "test".to_string(), None, 0)
+                .unwrap()
+                .balance_after;
+            (balance, relationship)
+        };
+
+        assert!(zones.transfer("ada", "arena", "arena").await.is_err());
+        assert!(zones.transfer("ada", "arena", "moon").await.is_err());
+        let zone = zones.transfer("ada", "arena", "yard").await.unwrap();
+        assert_eq!(zone.name, "yard");
+        assert_eq!(zones.locate("ada").await.unwrap().name, "yard");
+
+        let game = zone.game.read().await;
+        assert_eq!(game.players["ada"].needs.hunger, 42.0);
+        assert_eq!(game.ledger.balance("ada"), Some(balance));
+        assert_eq!(game.relationships.get("ada", "bruno"), relationship);
+        let home = zones.home().unwrap().game.read().await;
+        assert!(!home.players.contains_key("ada"));
+        assert!(home.relationships.of("ada").is_empty());
+    }
+
+    #[test]
+    fn connections_start_in_a_hosted_zone_without_the_home_zone() {
+        let zones = Zones::new(vec![Zone::new("yard", GameState::new())], "arena");
+        assert!(zones.home().is_none());
+        assert_eq!(zones.entry().name, "yard");
+    }
+}